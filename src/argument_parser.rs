@@ -7,13 +7,21 @@ pub enum Argument {
     Quiet,
     Debug,
     Release,
-    Color(bool),
+    /// `-c <value>`; the value is skipped, since output is always colored
+    Color,
+    Target(String),
+    NoFpu,
+    ErrorLimit(String),
+    Include(String),
 }
 
 pub fn parse_args(args: &[String]) -> Vec<Argument> {
     let mut arguments = Vec::new();
     let mut expecting_output = false;
     let mut expecting_color = false;
+    let mut expecting_target = false;
+    let mut expecting_error_limit = false;
+    let mut expecting_include = false;
     for arg in args {
         match arg.as_str() {
             "-h" | "-H" | "--help" | "?" => {
@@ -28,7 +36,16 @@ pub fn parse_args(args: &[String]) -> Vec<Argument> {
             "-c" | "-C" | "--color" => {
                 expecting_color = true;
             }
-            "-i" | "-I" | "--verbose" => {
+            "-t" | "-T" | "--target" => {
+                expecting_target = true;
+            }
+            "-e" | "-E" | "--error-limit" => {
+                expecting_error_limit = true;
+            }
+            "-I" | "--include" => {
+                expecting_include = true;
+            }
+            "-i" | "--verbose" => {
                 arguments.push(Argument::Verbose);
             }
             "-q" | "-Q" | "--quiet" => {
@@ -40,6 +57,9 @@ pub fn parse_args(args: &[String]) -> Vec<Argument> {
             "-r" | "-R" | "--release" => {
                 arguments.push(Argument::Release);
             }
+            "--no-fpu" => {
+                arguments.push(Argument::NoFpu);
+            }
             _ => {
                 if expecting_output {
                     arguments.push(Argument::Output(arg.clone()));
@@ -47,10 +67,25 @@ pub fn parse_args(args: &[String]) -> Vec<Argument> {
                     continue;
                 }
                 if expecting_color {
-                    arguments.push(Argument::Color(arg.as_str() == "true"));
+                    arguments.push(Argument::Color);
                     expecting_color = false;
                     continue;
                 }
+                if expecting_target {
+                    arguments.push(Argument::Target(arg.clone()));
+                    expecting_target = false;
+                    continue;
+                }
+                if expecting_error_limit {
+                    arguments.push(Argument::ErrorLimit(arg.clone()));
+                    expecting_error_limit = false;
+                    continue;
+                }
+                if expecting_include {
+                    arguments.push(Argument::Include(arg.clone()));
+                    expecting_include = false;
+                    continue;
+                }
                 println!("Unknown argument: {}", arg);
             }
         }
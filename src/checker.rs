@@ -0,0 +1,1587 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use crate::Span;
+use crate::error::Error;
+use crate::operator::Operator;
+use crate::statement::{Float, Number, Statement};
+use crate::types::{field_struct_name, literal_value, Type};
+
+/// A function that can be called from anywhere in the program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnSymbol {
+    /// the name of each parameter, along with its default value if it has one
+    pub params: Vec<(String, Option<Statement>)>,
+    pub span: Span,
+}
+
+/// A struct and the names of its fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructSymbol {
+    pub fields: Vec<String>,
+    /// the names of the structs its fields hold by value
+    pub holds: Vec<String>,
+    pub span: Span,
+}
+
+/// An enum and the names of its variants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumSymbol {
+    pub variants: Vec<String>,
+    pub span: Span,
+}
+
+/// A function, struct, enum, const or static, which files other than its own can only use if it is `pub`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Item {
+    /// the file the item is defined in
+    pub file: Option<String>,
+    pub public: bool,
+    /// the name the rest of the compiler knows the item by, which is the name it was written with
+    /// unless an item in another file has the same name
+    pub name: String,
+}
+
+/// The names a file can use: its own items, the `pub` items of the files it uses, and the items of the
+/// files it uses through an alias, which can only be used as `alias::item`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Module {
+    /// the items defined at the top level of the file, by the name they were written with
+    pub items: HashMap<String, Item>,
+    /// the files used without an alias
+    pub uses: Vec<String>,
+    /// the file each `use ... as alias` refers to, by the alias
+    pub aliases: HashMap<String, String>,
+}
+
+/// A loop that is being checked, which `break` and `continue` inside of it can leave
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoopFrame {
+    /// the loop's label as it was written, and the name it was given so it is unique in its function
+    pub label: Option<(String, String)>,
+    /// only `loop` can give a value with `break`
+    pub gives_value: bool,
+}
+
+/// What a name refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Param,
+    Local,
+    Const,
+    Static,
+    StaticMut,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub kind: SymbolKind,
+    pub span: Span,
+}
+
+/// A value that is known while compiling, such as the value of a const
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    /// a variant of an enum, as the path naming it
+    Variant(Statement),
+    Array(Vec<Constant>),
+    /// `[value; size]`, which is kept as it is so large arrays aren't written out
+    Repeat(Box<Constant>, u64),
+    /// a struct, as the name of the struct and the value given to each field
+    Struct(Statement, Vec<(Statement, Constant)>),
+}
+
+impl Constant {
+    /// the literal for the value, typed as `target` if it is an integer so a const keeps its type where it is used
+    fn to_literal(&self, target: Option<&Type>, span: Span) -> Statement {
+        match self {
+            Constant::Int(i) => {
+                let mut value = Number::new(i.unsigned_abs() as u64, *i < 0);
+                value.suffix = target.filter(|t| t.is_integer()).cloned();
+                Statement::NumberLiteral { value, span }
+            }
+            Constant::Float(v) => Statement::FloatLiteral { value: Float::new(format!("{:?}", v.abs()), *v < 0.0), span },
+            Constant::Bool(value) => Statement::BoolLiteral { value: *value, span },
+            Constant::Char(value) => Statement::CharLiteral { value: *value, span },
+            Constant::Str(value) => Statement::StringLiteral { value: value.clone(), span },
+            Constant::Variant(path) => match path.clone() {
+                Statement::Path { base, member, .. } => Statement::Path { base, member, span },
+                path => path
+            },
+            Constant::Array(items) => {
+                let inner = match target {
+                    Some(Type::Array(inner, _)) => Some(inner.as_ref()),
+                    _ => None
+                };
+                Statement::ArrayLiteral { items: items.iter().map(|i| i.to_literal(inner, span.clone())).collect(), span }
+            }
+            Constant::Repeat(value, size) => {
+                let inner = match target {
+                    Some(Type::Array(inner, _)) => Some(inner.as_ref()),
+                    _ => None
+                };
+                let value = Box::new(value.to_literal(inner, span.clone()));
+                let size = Box::new(Constant::Int(*size as i128).to_literal(None, span.clone()));
+                Statement::ArrayRepeat { value, size, span }
+            }
+            Constant::Struct(ident, fields) => {
+                let fields = fields.iter().map(|(field, value)| Statement::Assignment {
+                    ident: Box::new(field.clone()),
+                    value: Box::new(value.to_literal(None, span.clone())),
+                    span: span.clone()
+                }).collect();
+                Statement::StructLiteral { ident: Box::new(ident.clone()), fields, span }
+            }
+        }
+    }
+
+    /// whether the value is an array or struct, which C can only give a global as an initializer, so a
+    /// const holding one is used by name instead of being replaced by its value
+    fn is_aggregate(&self) -> bool {
+        matches!(self, Constant::Array(_) | Constant::Repeat(..) | Constant::Struct(..))
+    }
+}
+
+/// converts an integer to `target` the way C does, wrapping it to fit
+fn wrap_int(value: i128, target: &Type) -> i128 {
+    match target {
+        Type::U8 | Type::Char => value as u8 as i128,
+        Type::U16 => value as u16 as i128,
+        Type::U32 => value as u32 as i128,
+        Type::U64 => value as u64 as i128,
+        Type::I8 => value as i8 as i128,
+        Type::I16 => value as i16 as i128,
+        Type::I32 | Type::Int => value as i32 as i128,
+        _ => value as i64 as i128,
+    }
+}
+
+/// applies a binary operator to two constants, or returns None if it can't be applied to them
+fn binary_constant(op: Operator, left: Constant, right: Constant) -> Option<Result<Constant, String>> {
+    Some(Ok(match (left, right) {
+        (Constant::Int(l), Constant::Int(r)) => match op {
+            Operator::Add => Constant::Int(l.checked_add(r)?),
+            Operator::Sub => Constant::Int(l.checked_sub(r)?),
+            Operator::Mul => Constant::Int(l.checked_mul(r)?),
+            Operator::Div | Operator::Mod if r == 0 => return Some(Err("attempted to divide by zero".to_string())),
+            Operator::Div => Constant::Int(l / r),
+            Operator::Mod => Constant::Int(l % r),
+            Operator::And => Constant::Int(l & r),
+            Operator::Or => Constant::Int(l | r),
+            Operator::Xor => Constant::Int(l ^ r),
+            Operator::Shl | Operator::Shlu => Constant::Int(wrap_int(l.checked_shl(u32::try_from(r).ok()?)?, &Type::I64)),
+            Operator::Shr => Constant::Int(l.checked_shr(u32::try_from(r).ok()?)?),
+            Operator::Shru => Constant::Int((l as u64).checked_shr(u32::try_from(r).ok()?)? as i128),
+            Operator::Eq => Constant::Bool(l == r),
+            Operator::Neq => Constant::Bool(l != r),
+            Operator::Lt => Constant::Bool(l < r),
+            Operator::Lte => Constant::Bool(l <= r),
+            Operator::Gt => Constant::Bool(l > r),
+            Operator::Gte => Constant::Bool(l >= r),
+            _ => return None
+        },
+        (Constant::Float(l), Constant::Float(r)) => match op {
+            Operator::Add => Constant::Float(l + r),
+            Operator::Sub => Constant::Float(l - r),
+            Operator::Mul => Constant::Float(l * r),
+            Operator::Div => Constant::Float(l / r),
+            Operator::Lt => Constant::Bool(l < r),
+            Operator::Lte => Constant::Bool(l <= r),
+            Operator::Gt => Constant::Bool(l > r),
+            Operator::Gte => Constant::Bool(l >= r),
+            _ => return None
+        },
+        (Constant::Bool(l), Constant::Bool(r)) => match op {
+            Operator::BoolAnd | Operator::And => Constant::Bool(l && r),
+            Operator::BoolOr | Operator::Or => Constant::Bool(l || r),
+            Operator::Xor | Operator::Neq => Constant::Bool(l != r),
+            Operator::Eq => Constant::Bool(l == r),
+            _ => return None
+        },
+        (Constant::Char(l), Constant::Char(r)) => match op {
+            Operator::Eq => Constant::Bool(l == r),
+            Operator::Neq => Constant::Bool(l != r),
+            Operator::Lt => Constant::Bool(l < r),
+            Operator::Lte => Constant::Bool(l <= r),
+            Operator::Gt => Constant::Bool(l > r),
+            Operator::Gte => Constant::Bool(l >= r),
+            _ => return None
+        },
+        _ => return None
+    }))
+}
+
+/// converts a constant to the type it is cast to
+fn cast_constant(value: Constant, target: &Type) -> Option<Constant> {
+    let int = match value {
+        Constant::Int(i) => i,
+        Constant::Char(c) => c as i128,
+        Constant::Bool(b) => b as i128,
+        Constant::Float(v) if target.is_float() => return Some(Constant::Float(v)),
+        Constant::Float(v) => v as i128,
+        _ => return None
+    };
+    Some(match target {
+        t if t.is_float() => Constant::Float(int as f64),
+        Type::Char => Constant::Char(wrap_int(int, target) as u8 as char),
+        t if t.is_integer() => Constant::Int(wrap_int(int, t)),
+        _ => return None
+    })
+}
+
+/// collects the names of the variables an expression reads, leaving out the names of fields, methods,
+/// functions and types
+fn variables_used(stmt: &Statement, names: &mut Vec<String>) {
+    match stmt {
+        Statement::Identifier { ident, .. } => names.push(ident.clone()),
+        Statement::PropertyAccess { expr, .. } | Statement::Cast { expr, .. } => variables_used(expr, names),
+        Statement::StructLiteral { fields, .. } => {
+            for f in fields {
+                if let Statement::Assignment { value, .. } = f {
+                    variables_used(value, names);
+                }
+            }
+        }
+        Statement::FnCall { params, .. } => params.iter().for_each(|p| variables_used(p, names)),
+        Statement::MethodCall { receiver, params, .. } => {
+            variables_used(receiver, names);
+            params.iter().for_each(|p| variables_used(p, names));
+        }
+        Statement::Path { .. } | Statement::Type { .. } | Statement::ArrayType { .. } => {}
+        other => other.children().into_iter().for_each(|c| variables_used(c, names)),
+    }
+}
+
+/// puts the arguments of a call in the order of the function's parameters, moving named arguments
+/// into place and filling in the default value of every parameter that wasn't given one. Returns
+/// None if the arguments don't match the parameters. `is_local` says whether a name is a local variable
+/// where the call is, which would hide an item a default value uses
+pub fn order_args(name: &str, declared: &[(String, Option<Statement>)], params: &[Statement], span: &Span,
+                  is_local: &dyn Fn(&str) -> bool, errors: &mut Vec<Error>) -> Option<Vec<Statement>> {
+    let mut args: Vec<Option<Statement>> = vec![None; declared.len()];
+    let positional = params.iter().take_while(|p| !matches!(p, Statement::Assignment { .. })).count();
+    let required = declared.iter().filter(|(_, default)| default.is_none()).count();
+    let named = params.len() > positional;
+    // without named arguments, any missing argument is reported as the wrong number of arguments
+    if positional > declared.len() || (!named && positional < required) {
+        let count = if required == declared.len() {
+            format!("{}", declared.len())
+        } else {
+            format!("{} to {}", required, declared.len())
+        };
+        let msg = format!("`{}` takes {} argument{} but {} {} supplied", name,
+                          count, if declared.len() == 1 { "" } else { "s" },
+                          positional, if positional == 1 { "was" } else { "were" });
+        errors.push(Error::new("Wrong number of arguments", msg, span.clone()));
+        return None;
+    }
+    let mut valid = true;
+    for (i, p) in params.iter().cloned().enumerate() {
+        if i < positional {
+            args[i] = Some(p);
+            continue;
+        }
+        let (param, value, arg_span) = match p {
+            Statement::Assignment { ident, value, span } => match *ident {
+                Statement::Identifier { ident, .. } => (ident, *value, span),
+                _ => continue
+            },
+            p => {
+                let arg_span = p.span().cloned().unwrap_or_else(|| span.clone());
+                errors.push(Error::new("Unexpected argument", "positional arguments must come before named arguments", arg_span));
+                valid = false;
+                continue;
+            }
+        };
+        match declared.iter().position(|(n, _)| *n == param) {
+            Some(index) if args[index].is_some() => {
+                errors.push(Error::new("Duplicate argument", format!("`{}` was already given a value", param), arg_span));
+                valid = false;
+            }
+            Some(index) => args[index] = Some(value),
+            None => {
+                errors.push(Error::new("Unknown argument", format!("`{}` has no parameter named `{}`", name, param), arg_span));
+                valid = false;
+            }
+        }
+    }
+    let mut missing = Vec::new();
+    for (arg, (param, default)) in args.iter_mut().zip(declared) {
+        if arg.is_none() {
+            match default {
+                Some(value) => {
+                    // the value is used where the call is, so a local there can't have the name of an item it uses
+                    let mut used = Vec::new();
+                    variables_used(value, &mut used);
+                    if let Some(hidden) = used.into_iter().find(|n| is_local(n)) {
+                        let msg = format!("the default value of `{}` uses `{}`, which a local variable hides here, \
+                                           so `{}` has to be given", param, hidden, param);
+                        errors.push(Error::new("Hidden default value", msg, span.clone()));
+                        valid = false;
+                    }
+                    *arg = Some(value.clone());
+                }
+                None => missing.push(format!("`{}`", param))
+            }
+        }
+    }
+    if !missing.is_empty() {
+        errors.push(Error::new("Missing arguments", format!("`{}` is missing {}", name, missing.join(", ")), span.clone()));
+        return None;
+    }
+    if valid { args.into_iter().collect() } else { None }
+}
+
+/// the variable, field or array a place is in, like `p.x`. An element of an array is the whole array, and
+/// what a pointer points to has no path since it could be anything
+fn place_path(stmt: &Statement) -> Option<String> {
+    match stmt {
+        Statement::Identifier { ident, .. } => Some(ident.clone()),
+        Statement::PropertyAccess { expr, property, .. } => match property.as_ref() {
+            Statement::Identifier { ident, .. } => Some(format!("{}.{}", place_path(expr)?, ident)),
+            _ => None
+        },
+        Statement::ArrayAccess { ident, .. } => place_path(ident),
+        _ => None
+    }
+}
+
+/// collects the places an expression uses, as their paths, along with whether `++` or `--` changes them
+/// and where
+fn accesses(stmt: &Statement, found: &mut Vec<(String, bool, Span)>) {
+    let span = || stmt.span().cloned().unwrap_or_default();
+    match stmt {
+        Statement::Unary { op: Operator::Inc | Operator::Dec, expr, .. } => {
+            if let Some(path) = place_path(expr) {
+                found.push((path, true, span()));
+            }
+            accesses(expr, found);
+        }
+        Statement::Identifier { ident, .. } => found.push((ident.clone(), false, span())),
+        Statement::PropertyAccess { expr, .. } => match place_path(stmt) {
+            Some(path) => found.push((path, false, span())),
+            None => accesses(expr, found)
+        },
+        // named arguments and struct fields are names rather than places
+        Statement::Assignment { ident, value, .. } if matches!(ident.as_ref(), Statement::Identifier { .. }) => {
+            accesses(value, found)
+        }
+        Statement::Path { .. } | Statement::Type { .. } | Statement::ArrayType { .. } => {}
+        other => other.children().into_iter().for_each(|c| accesses(c, found)),
+    }
+}
+
+/// Walks the AST after parsing to find errors the parser can't see, such as undefined
+/// identifiers, duplicate functions, calls with the wrong number of arguments and
+/// `break` outside of a loop. Calls are rewritten to pass every argument in order, with
+/// named arguments moved into place and default values filled in, every use of a const
+/// that isn't an array or struct is replaced by its value, and `alias::item` is replaced by the item from the aliased file.
+/// A label used by more than one loop in a function is renamed after its first use, so C
+/// can jump to each of them.
+/// Every file has its own namespace, holding its own items and the `pub` items of the files it uses without
+/// an alias. Items are renamed so their names are unique in the program, so later passes can treat every
+/// file as one.
+struct Checker {
+    functions: HashMap<String, FnSymbol>,
+    structs: HashMap<String, StructSymbol>,
+    enums: HashMap<String, EnumSymbol>,
+    /// where each method was defined, by type and then method name
+    methods: HashMap<String, HashMap<String, Span>>,
+    /// the namespace of every file, by its path
+    modules: HashMap<Option<String>, Module>,
+    /// the first scope holds the consts and statics, which every function can see
+    scopes: Vec<HashMap<String, Symbol>>,
+    /// the value and type of every const, which are evaluated the first time they are used
+    consts: HashMap<String, (Statement, Statement)>,
+    /// the value of every const that has been evaluated, or None if it couldn't be
+    const_values: HashMap<String, Option<Constant>>,
+    /// the consts being evaluated, used to find consts that depend on themselves
+    evaluating: Vec<String>,
+    /// the loops around the statement being checked, innermost last
+    loops: Vec<LoopFrame>,
+    /// how many loops in the function being checked have used each label
+    labels: HashMap<String, usize>,
+    errors: Vec<Error>,
+}
+
+impl Checker {
+    fn new() -> Self {
+        Self {
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            methods: HashMap::new(),
+            modules: HashMap::new(),
+            scopes: vec![HashMap::new()],
+            consts: HashMap::new(),
+            const_values: HashMap::new(),
+            evaluating: Vec::new(),
+            loops: Vec::new(),
+            labels: HashMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn error<S: Into<String>, S2: Into<String>>(&mut self, core: S, msg: S2, span: Span) {
+        self.errors.push(Error::new(core, msg, span));
+    }
+
+    /// reports a place that `++` or `--` changes in one part of an expression and that another part uses,
+    /// since C leaves which of them happens first undefined
+    fn check_sequenced(&mut self, parts: &[&Statement]) {
+        let found: Vec<Vec<(String, bool, Span)>> = parts.iter().map(|part| {
+            let mut found = Vec::new();
+            accesses(part, &mut found);
+            found
+        }).collect();
+        let overlap = |a: &str, b: &str| a == b || a.starts_with(&format!("{}.", b)) || b.starts_with(&format!("{}.", a));
+        for (i, part) in found.iter().enumerate() {
+            for other in &found[i + 1..] {
+                let clash = part.iter().flat_map(|a| other.iter().map(move |b| (a, b)))
+                    .find(|(a, b)| (a.1 || b.1) && overlap(&a.0, &b.0));
+                if let Some((a, b)) = clash {
+                    let (path, _, span) = if a.1 { a } else { b };
+                    let msg = format!("`{}` is changed and used again in the same expression, so which happens first is undefined", path);
+                    return self.error("Unsequenced change", msg, span.clone());
+                }
+            }
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: String, kind: SymbolKind, span: Span) {
+        self.scopes.last_mut().unwrap().insert(name, Symbol { kind, span });
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.scopes.iter().rev().find_map(|s| s.get(name))
+    }
+
+    /// whether a name is a parameter or local variable, rather than an item
+    fn is_local(&self, name: &str) -> bool {
+        self.scopes[1..].iter().any(|s| s.contains_key(name))
+    }
+
+    /// collects the items every file defines and the files it uses
+    fn collect_modules(&mut self, exprs: &Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Fn { ident, public, .. } | Statement::Struct { ident, public, .. } |
+                Statement::Enum { ident, public, .. } | Statement::Const { ident, public, .. } |
+                Statement::Static { ident, public, .. } => {
+                    // duplicates are reported when the items are collected, the first definition is the one that is kept
+                    if let Statement::Identifier { ident, span, .. } = ident.as_ref() {
+                        let item = Item { file: span.start.file.clone(), public: *public, name: ident.clone() };
+                        self.modules.entry(span.start.file.clone()).or_default().items.entry(ident.clone()).or_insert(item);
+                    }
+                }
+                Statement::Use { exprs, path, alias, span } => {
+                    let module = self.modules.entry(span.start.file.clone()).or_default();
+                    match alias.as_deref() {
+                        Some(Statement::Identifier { ident, span: alias_span, .. }) => match module.aliases.get(ident) {
+                            Some(existing) if existing != path => {
+                                let msg = format!("`{}` already refers to `{}`", ident, existing);
+                                self.error("Duplicate alias", msg, alias_span.clone());
+                            }
+                            _ => {
+                                module.aliases.insert(ident.clone(), path.clone());
+                            }
+                        },
+                        _ if !module.uses.contains(path) => module.uses.push(path.clone()),
+                        _ => {}
+                    }
+                    self.collect_modules(exprs);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// renames every item that has the same name as an item in another file after its file, such as
+    /// `uart_init`, so no two items in the program have the same name. Items in `root`, the file being
+    /// compiled, keep their names
+    fn name_items(&mut self, root: &Option<String>) {
+        let mut files: HashMap<String, Vec<Option<String>>> = HashMap::new();
+        for (file, module) in &self.modules {
+            for name in module.items.keys() {
+                files.entry(name.clone()).or_default().push(file.clone());
+            }
+        }
+        let mut taken: HashSet<String> = files.keys().cloned().collect();
+        let mut shared: Vec<(Option<String>, String)> = files.into_iter().filter(|(_, files)| files.len() > 1)
+            .flat_map(|(name, files)| files.into_iter().filter(|f| f != root).map(move |f| (f, name.clone())))
+            .collect();
+        // sorted so an item is given the same name every time
+        shared.sort();
+        for (file, name) in shared {
+            let stem: String = file.as_deref().and_then(|f| Path::new(f).file_stem()).map(|s| s.to_string_lossy())
+                .unwrap_or_default().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+            let mut unique = format!("{}_{}", stem, name);
+            let mut count = 1;
+            while taken.contains(&unique) {
+                count += 1;
+                unique = format!("{}_{}_{}", stem, name, count);
+            }
+            taken.insert(unique.clone());
+            if let Some(item) = self.modules.get_mut(&file).and_then(|m| m.items.get_mut(&name)) {
+                item.name = unique;
+            }
+        }
+    }
+
+    /// finds the item a name refers to in a file, which is one of the file's own items or a `pub` item of
+    /// a file it uses, giving the item's unique name. Errors give why the name can't be used
+    fn find_item(&self, name: &str, file: &Option<String>) -> Result<Option<String>, (&'static str, String)> {
+        let module = match self.modules.get(file) {
+            Some(module) => module,
+            None => return Ok(None)
+        };
+        if let Some(item) = module.items.get(name) {
+            return Ok(Some(item.name.clone()));
+        }
+        let found: Vec<&Item> = module.uses.iter()
+            .filter_map(|path| self.modules.get(&Some(path.clone()))?.items.get(name))
+            .collect();
+        let public: Vec<&&Item> = found.iter().filter(|item| item.public).collect();
+        match (public.as_slice(), found.first()) {
+            ([item], _) => Ok(Some(item.name.clone())),
+            ([], Some(item)) => {
+                let msg = format!("`{}` is not `pub`, so only `{}` can use it", name, item.file.as_deref().unwrap_or("code"));
+                Err(("Private item", msg))
+            }
+            ([], None) => Ok(None),
+            (items, _) => {
+                let files: Vec<String> = items.iter().map(|i| format!("`{}`", i.file.as_deref().unwrap_or("code"))).collect();
+                let msg = format!("`{}` is defined by {}, use the files through an alias to pick one", name, files.join(" and "));
+                Err(("Ambiguous item", msg))
+            }
+        }
+    }
+
+    /// replaces the name of an item, or `alias::item`, with the item's unique name. The base of
+    /// `Enum::Variant` is the name of an item. Returns Some(false) if no item has the name, or None if
+    /// the item can't be used, which has been reported
+    fn resolve_item(&mut self, stmt: &mut Statement) -> Option<bool> {
+        let (base, member, span) = match stmt {
+            Statement::Identifier { ident, span, .. } => {
+                return match self.find_item(ident, &span.start.file) {
+                    Ok(Some(name)) => {
+                        *ident = name;
+                        Some(true)
+                    }
+                    Ok(None) => Some(false),
+                    Err((core, msg)) => {
+                        self.error(core, msg, span.clone());
+                        None
+                    }
+                };
+            }
+            Statement::Path { base, member, span } => (base, member, span),
+            _ => return Some(true)
+        };
+        let (alias, name) = match (base.as_ref(), member.as_ref()) {
+            (Statement::Identifier { ident: alias, .. }, Statement::Identifier { ident: name, .. }) => (alias, name),
+            // `alias::Enum::Variant` is a path inside of a path
+            _ => return self.resolve_item(base)
+        };
+        let path = match self.modules.get(&span.start.file).and_then(|m| m.aliases.get(alias)) {
+            Some(path) => path.clone(),
+            None => return self.resolve_item(base)
+        };
+        let item = match self.modules.get(&Some(path.clone())).and_then(|m| m.items.get(name)) {
+            Some(item) => item.clone(),
+            None => {
+                let msg = format!("`{}` has no item named `{}`", path, name);
+                self.error("Unknown item", msg, span.clone());
+                return None;
+            }
+        };
+        if !item.public {
+            let msg = format!("`{}` is not `pub`, so only `{}` can use it", name, path);
+            self.error("Private item", msg, span.clone());
+            return None;
+        }
+        let span = match member.as_ref() {
+            Statement::Identifier { span, .. } => span.clone(),
+            _ => span.clone()
+        };
+        *stmt = Statement::Identifier { ident: item.name, span };
+        Some(true)
+    }
+
+    /// the unique name of the item a name or `alias::item` refers to, without reporting why it can't be used
+    fn peek_item(&self, stmt: &Statement) -> Option<String> {
+        match stmt {
+            Statement::Identifier { ident, span, .. } => self.find_item(ident, &span.start.file).ok().flatten(),
+            Statement::Path { base, member, span } => match (base.as_ref(), member.as_ref()) {
+                (Statement::Identifier { ident: alias, .. }, Statement::Identifier { ident: name, .. }) => {
+                    let path = self.modules.get(&span.start.file)?.aliases.get(alias)?;
+                    self.modules.get(&Some(path.clone()))?.items.get(name).map(|item| item.name.clone())
+                }
+                _ => None
+            },
+            _ => None
+        }
+    }
+
+    /// collects every function in the program, including those from imported files, giving each item its
+    /// unique name
+    fn collect_functions(&mut self, exprs: &mut Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Fn { ident, .. } | Statement::Struct { ident, .. } | Statement::Enum { ident, .. } |
+                Statement::Const { ident, .. } | Statement::Static { ident, .. } => {
+                    if let Statement::Identifier { ident, span, .. } = ident.as_mut() {
+                        if let Some(item) = self.modules.get(&span.start.file).and_then(|m| m.items.get(ident)) {
+                            *ident = item.name.clone();
+                        }
+                    }
+                }
+                _ => {}
+            }
+            match expr {
+                Statement::Fn { ident, params, .. } => {
+                    let (name, span) = match ident.as_ref() {
+                        Statement::Identifier { ident, span, .. } => (ident.clone(), span.clone()),
+                        _ => continue
+                    };
+                    if let Some(existing) = self.functions.get(&name) {
+                        let msg = format!("`{}` was already defined at {}", name, existing.span);
+                        self.error("Duplicate function", msg, span);
+                        continue;
+                    }
+                    let params = params.iter().filter_map(|p| match p {
+                        Statement::Declaration { ident, value, .. } => match ident.as_ref() {
+                            Statement::Identifier { ident, .. } => Some((ident.clone(), value.as_deref().cloned())),
+                            _ => None
+                        },
+                        _ => None
+                    }).collect();
+                    self.functions.insert(name, FnSymbol { params, span });
+                }
+                Statement::Struct { ident, fields, .. } => self.collect_struct(ident, fields),
+                Statement::Enum { ident, variants, .. } => self.collect_enum(ident, variants),
+                Statement::Const { ident, type_ident, value, .. } => {
+                    if let Some(name) = self.collect_global(ident, SymbolKind::Const) {
+                        self.consts.insert(name, (value.as_ref().clone(), type_ident.as_ref().clone()));
+                    }
+                }
+                Statement::Static { ident, mutable, .. } => {
+                    self.collect_global(ident, if *mutable { SymbolKind::StaticMut } else { SymbolKind::Static });
+                }
+                Statement::Use { exprs, .. } => self.collect_functions(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    /// adds a const or static to the global scope, returning its name if it wasn't already defined
+    fn collect_global(&mut self, ident: &Statement, kind: SymbolKind) -> Option<String> {
+        let (name, span) = match ident {
+            Statement::Identifier { ident, span, .. } => (ident.clone(), span.clone()),
+            _ => return None
+        };
+        if let Some(existing) = self.scopes[0].get(&name) {
+            let msg = format!("`{}` was already defined at {}", name, existing.span);
+            self.error("Duplicate global", msg, span);
+            return None;
+        }
+        self.scopes[0].insert(name.clone(), Symbol { kind, span });
+        Some(name)
+    }
+
+    /// the type of a const or static, if it is a primitive type
+    fn global_type(type_ident: &Statement) -> Option<Type> {
+        Type::from_statement(type_ident, &|_| None).ok()
+    }
+
+    /// evaluates a const, reporting why if it can't be. Every const is only evaluated once
+    fn const_value(&mut self, name: &str) -> Option<Constant> {
+        if let Some(value) = self.const_values.get(name) {
+            return value.clone();
+        }
+        let (value, type_ident) = self.consts.get(name)?.clone();
+        let span = value.span().cloned().unwrap_or_default();
+        if self.evaluating.iter().any(|n| n == name) {
+            let chain = self.evaluating.iter().chain([&name.to_string()]).map(|n| format!("`{}`", n))
+                .collect::<Vec<String>>().join(" -> ");
+            self.error("Recursive constant", format!("the value of `{}` depends on itself: {}", name, chain), span);
+            return None;
+        }
+        self.evaluating.push(name.to_string());
+        let result = self.global_value(&value, &type_ident);
+        self.evaluating.pop();
+        self.const_values.insert(name.to_string(), result.clone());
+        result
+    }
+
+    /// evaluates the value of a const or static, checking that it fits in the global's type
+    fn global_value(&mut self, value: &Statement, type_ident: &Statement) -> Option<Constant> {
+        let target = Checker::global_type(type_ident);
+        let result = match self.eval_constant(value, target.as_ref()) {
+            Ok(result) => result,
+            Err(Some((msg, span))) => {
+                self.error("Not a constant", msg, span);
+                return None;
+            }
+            Err(None) => return None,
+        };
+        if let (Constant::Int(i), Some((min, max))) = (&result, target.as_ref().and_then(|t| t.value_range())) {
+            if *i < min || *i > max {
+                let msg = format!("`{}` does not fit in `{}`, which holds {} to {}", i, target.unwrap(), min, max);
+                self.error("Constant out of range", msg, value.span().cloned().unwrap_or_default());
+                return None;
+            }
+        }
+        Some(result)
+    }
+
+    /// evaluates an expression made of literals, array and struct literals, consts, enum variants, operators and
+    /// casts. Errors give the reason along with where the part that can't be evaluated is, or None if it was
+    /// already reported
+    /// `expected` is the type the value is used as, which untyped integer literals take like they do at runtime
+    fn eval_constant(&mut self, expr: &Statement, expected: Option<&Type>) -> Result<Constant, Option<(String, Span)>> {
+        let span = expr.span().cloned().unwrap_or_default();
+        match expr {
+            Statement::NumberLiteral { value, .. } => Ok(Constant::Int(value.signed_value())),
+            Statement::FloatLiteral { value, .. } => {
+                let parsed = value.value.parse::<f64>().map_err(|_| Some((format!("`{}` is not a valid float", value), span)))?;
+                Ok(Constant::Float(if value.negative { -parsed } else { parsed }))
+            }
+            Statement::BoolLiteral { value, .. } => Ok(Constant::Bool(*value)),
+            Statement::CharLiteral { value, .. } => Ok(Constant::Char(*value)),
+            Statement::StringLiteral { value, .. } => Ok(Constant::Str(value.clone())),
+            Statement::Path { .. } => {
+                let mut path = expr.clone();
+                match self.resolve_item(&mut path) {
+                    Some(true) => Ok(Constant::Variant(path)),
+                    Some(false) => Err(Some(("this path does not name an enum variant".to_string(), span))),
+                    None => Err(None)
+                }
+            }
+            Statement::Identifier { ident, .. } => {
+                // a local variable hides an item with the same name
+                let mut item = expr.clone();
+                let found = if self.is_local(ident) { Some(false) } else { self.resolve_item(&mut item) };
+                match (found, &item) {
+                    (None, _) => Err(None),
+                    // the const reports its own error, so this only needs to stop evaluating
+                    (Some(true), Statement::Identifier { ident: name, .. })
+                        if self.lookup(name).is_some_and(|s| s.kind == SymbolKind::Const) => self.const_value(name).ok_or(None),
+                    _ => Err(Some((format!("`{}` is not a constant, only consts can be used in a constant", ident), span)))
+                }
+            }
+            Statement::Unary { op, expr: operand, postfix: false, .. } => {
+                let operand_type = self.constant_type(operand).or(expected.cloned());
+                match (op, self.eval_constant(operand, operand_type.as_ref())?) {
+                    (Operator::Sub, Constant::Int(i)) => Ok(Constant::Int(-i)),
+                    (Operator::Sub, Constant::Float(v)) => Ok(Constant::Float(-v)),
+                    (Operator::Not, Constant::Bool(b)) => Ok(Constant::Bool(!b)),
+                    // the bits are flipped within the type, so `!0` is the type's largest unsigned value
+                    (Operator::Not, Constant::Int(i)) => Ok(Constant::Int(match &operand_type {
+                        Some(t) if t.is_integer() => wrap_int(!i, t),
+                        _ => !i
+                    })),
+                    _ => Err(Some((format!("{} can not be used in a constant here", op), span)))
+                }
+            }
+            Statement::Binary { op, left: left_expr, right: right_expr, .. } => {
+                // the operands of an operator share a type, except a shift's amount, and untyped literals
+                // take the type the result is used as
+                let shift = matches!(op, Operator::Shl | Operator::Shlu | Operator::Shr | Operator::Shru);
+                let operand_type = match shift {
+                    true => self.constant_type(left_expr),
+                    false => self.constant_type(left_expr).or_else(|| self.constant_type(right_expr))
+                }.or_else(|| expected.filter(|_| op.is_arithmetic()).cloned());
+                let mut left = self.eval_constant(left_expr, operand_type.as_ref())?;
+                let right = self.eval_constant(right_expr, if shift { None } else { operand_type.as_ref() })?;
+                let operand_type = operand_type.filter(|t| t.is_integer() && op.is_arithmetic());
+                // `>>>` shifts zeros in at the top of the type's own width
+                if let (Operator::Shru, Some(t), Constant::Int(l)) = (op, &operand_type, &left) {
+                    left = Constant::Int(wrap_int(*l, &t.unsigned()));
+                }
+                let result = match binary_constant(*op, left, right) {
+                    Some(result) => result.map_err(|msg| Some((msg, span.clone())))?,
+                    None => return Err(Some((format!("{} can not be applied to these values when compiling", op), span)))
+                };
+                match (result, &operand_type) {
+                    (Constant::Int(i), Some(t)) if *op == Operator::Shru => Ok(Constant::Int(wrap_int(i, t))),
+                    // the operator works in the operands' type at runtime, so a value outside of it would be
+                    // different there
+                    (Constant::Int(i), Some(t)) => {
+                        let (min, max) = t.value_range().unwrap_or((i128::MIN, i128::MAX));
+                        if i < min || i > max {
+                            let msg = format!("this is {} when compiling, which overflows `{}`, which holds {} to {}", i, t, min, max);
+                            self.error("Constant out of range", msg, span);
+                            return Err(None);
+                        }
+                        Ok(Constant::Int(i))
+                    }
+                    (result, _) => Ok(result)
+                }
+            }
+            Statement::Cast { expr: operand, cast_type, .. } => {
+                let value = self.eval_constant(operand, None)?;
+                Checker::global_type(cast_type).and_then(|t| cast_constant(value, &t))
+                    .ok_or_else(|| Some(("this cast can not be evaluated when compiling".to_string(), span)))
+            }
+            Statement::ArrayLiteral { items, .. } => {
+                let element = match expected {
+                    Some(Type::Array(element, _)) => Some(element.as_ref()),
+                    _ => None
+                };
+                items.iter().map(|i| self.eval_constant(i, element)).collect::<Result<Vec<Constant>, _>>().map(Constant::Array)
+            }
+            Statement::ArrayRepeat { value, size, .. } => {
+                let element = match expected {
+                    Some(Type::Array(element, _)) => Some(element.as_ref()),
+                    _ => None
+                };
+                let value = self.eval_constant(value, element)?;
+                match self.eval_constant(size, None)? {
+                    Constant::Int(i) if i > 0 && i <= i64::MAX as i128 => Ok(Constant::Repeat(Box::new(value), i as u64)),
+                    _ => Err(Some(("an array's size must be a positive integer".to_string(), span)))
+                }
+            }
+            Statement::StructLiteral { ident, fields, .. } => {
+                let mut ident = ident.as_ref().clone();
+                self.check_struct_fields(&mut ident, fields);
+                let mut values = Vec::new();
+                for f in fields {
+                    if let Statement::Assignment { ident: field, value, .. } = f {
+                        values.push((field.as_ref().clone(), self.eval_constant(value, None)?));
+                    }
+                }
+                Ok(Constant::Struct(ident, values))
+            }
+            _ => Err(Some(("only literals, array and struct literals, consts, enum variants, operators and casts \
+                can be used in a constant".to_string(), span)))
+        }
+    }
+
+    /// the integer type the operators in a constant work in, which is the type of a const or cast, or
+    /// of the operands of an operator. None for literals without a suffix, which take the type they are used as
+    fn constant_type(&self, expr: &Statement) -> Option<Type> {
+        match expr {
+            Statement::NumberLiteral { value, .. } => value.suffix.clone(),
+            Statement::Identifier { ident, .. } if self.is_local(ident) => None,
+            Statement::Identifier { .. } | Statement::Path { .. } => {
+                let name = self.peek_item(expr)?;
+                self.consts.get(&name).and_then(|(_, t)| Checker::global_type(t))
+            }
+            Statement::Cast { cast_type, .. } => Checker::global_type(cast_type),
+            Statement::Unary { op: Operator::Sub | Operator::Not, expr, .. } => self.constant_type(expr),
+            Statement::Binary { op: Operator::Shl | Operator::Shlu | Operator::Shr | Operator::Shru, left, .. } => {
+                self.constant_type(left)
+            }
+            Statement::Binary { op, left, right, .. } if op.is_arithmetic() => {
+                self.constant_type(left).or_else(|| self.constant_type(right))
+            }
+            _ => None
+        }
+    }
+
+    /// checks that an expression, such as a for loop's step or an array's size, is a positive constant
+    /// integer, and replaces it with its value. `what` names the expression in errors
+    fn check_positive(&mut self, expr: &mut Statement, core: &str, what: &str) {
+        let span = expr.span().cloned().unwrap_or_default();
+        match self.eval_constant(expr, None) {
+            Ok(Constant::Int(i)) if i > 0 && i <= i64::MAX as i128 => *expr = Constant::Int(i).to_literal(None, span),
+            Ok(Constant::Int(i)) => self.error(core, format!("{} must be positive, but it is {}", what, i), span),
+            Ok(_) => self.error(core, format!("{} must be an integer", what), span),
+            Err(Some((msg, span))) => self.error("Not a constant", msg, span),
+            Err(None) => {}
+        }
+    }
+
+    /// evaluates the size of every array in a type, so later passes only see literal sizes, and replaces the
+    /// name of a struct or enum with its unique name
+    fn check_type(&mut self, type_stmt: &mut Statement) {
+        match type_stmt {
+            Statement::ArrayType { array_type, size, .. } => {
+                self.check_type(array_type);
+                self.check_positive(size, "Invalid array size", "an array's size");
+            }
+            Statement::Type { type_ident, .. } => {
+                let (name, span) = match type_ident.as_ref() {
+                    Statement::Identifier { ident, span, .. } if Type::from_name(ident).is_none() => (ident.clone(), span.clone()),
+                    Statement::Path { member, span, .. } => match member.as_ref() {
+                        Statement::Identifier { ident, .. } => (ident.clone(), span.clone()),
+                        _ => return
+                    },
+                    _ => return
+                };
+                if self.resolve_item(type_ident) == Some(false) {
+                    self.error("Unknown type", format!("`{}` is not a type", name), span);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// reports assignments to consts and to statics that aren't `static mut`
+    fn check_assignable(&mut self, target: &Statement) {
+        let (name, span) = match target {
+            Statement::Identifier { ident, span, .. } if !self.is_local(ident) => (ident, span),
+            Statement::PropertyAccess { expr, .. } => return self.check_assignable(expr),
+            Statement::ArrayAccess { ident, .. } => return self.check_assignable(ident),
+            _ => return
+        };
+        // names that don't refer to an item are reported when the target is checked
+        let item = self.find_item(name, &span.start.file).ok().flatten();
+        match item.and_then(|item| self.lookup(&item)).map(|s| s.kind) {
+            Some(SymbolKind::Const) => {
+                self.error("Assignment to a constant", format!("`{}` is a const, so it can't be changed", name), span.clone());
+            }
+            Some(SymbolKind::Static) => {
+                let msg = format!("`{}` can't be changed because it is not a `static mut`", name);
+                self.error("Assignment to an immutable static", msg, span.clone());
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_struct(&mut self, ident: &Statement, fields: &Vec<Statement>) {
+        let (name, span) = match ident {
+            Statement::Identifier { ident, span, .. } => (ident.clone(), span.clone()),
+            _ => return
+        };
+        if let Some(existing) = self.type_span(&name) {
+            let msg = format!("`{}` was already defined at {}", name, existing);
+            self.error("Duplicate type", msg, span);
+            return;
+        }
+        let mut field_names: Vec<String> = Vec::new();
+        for f in fields {
+            if let Statement::Declaration { ident, .. } = f {
+                if let Statement::Identifier { ident, span, .. } = ident.as_ref() {
+                    if field_names.contains(ident) {
+                        let msg = format!("`{}` already has a field named `{}`", name, ident);
+                        self.error("Duplicate field", msg, span.clone());
+                        continue;
+                    }
+                    field_names.push(ident.clone());
+                }
+            }
+        }
+        // the fields' types are renamed when they are checked, which is after struct cycles are found
+        let holds = fields.iter().filter_map(field_struct_name).filter_map(|held| self.peek_item(held)).collect();
+        self.structs.insert(name, StructSymbol { fields: field_names, holds, span });
+    }
+
+    /// reports structs that hold themselves by value, directly or through other structs, which would be
+    /// infinitely large. Each cycle is reported once, on the struct in it that is defined first
+    fn check_struct_cycles(&mut self) {
+        let mut names: Vec<String> = self.structs.keys().cloned().collect();
+        names.sort_by_key(|n| {
+            let start = &self.structs[n].span.start;
+            (start.file.clone(), start.line, start.ch)
+        });
+        let mut reported: Vec<String> = Vec::new();
+        for name in names {
+            if reported.contains(&name) {
+                continue;
+            }
+            if let Some(cycle) = self.struct_cycle(&mut vec![name.clone()]) {
+                let path: Vec<String> = cycle.iter().chain([&name]).map(|n| format!("`{}`", n)).collect();
+                let msg = format!("{} holds itself by value ({}), so it would be infinitely large. \
+                                   Hold one of them through a pointer instead", path[0], path.join(" -> "));
+                let span = self.structs[&name].span.clone();
+                self.error("Recursive struct", msg, span);
+                reported.extend(cycle);
+            }
+        }
+    }
+
+    /// finds the structs held by value that lead from the last struct in `path` back to the first one
+    fn struct_cycle(&self, path: &mut Vec<String>) -> Option<Vec<String>> {
+        let current = self.structs.get(path.last()?)?;
+        for held in &current.holds {
+            if *held == path[0] {
+                return Some(path.clone());
+            }
+            // a cycle that doesn't go through the first struct is reported from a struct in it
+            if path.contains(held) {
+                continue;
+            }
+            path.push(held.clone());
+            if let Some(cycle) = self.struct_cycle(path) {
+                return Some(cycle);
+            }
+            path.pop();
+        }
+        None
+    }
+
+    /// where a struct or enum with the given name was defined
+    fn type_span(&self, name: &str) -> Option<Span> {
+        self.structs.get(name).map(|s| s.span.clone()).or_else(|| self.enums.get(name).map(|e| e.span.clone()))
+    }
+
+    fn collect_enum(&mut self, ident: &Statement, variants: &Vec<Statement>) {
+        let (name, span) = match ident {
+            Statement::Identifier { ident, span, .. } => (ident.clone(), span.clone()),
+            _ => return
+        };
+        if let Some(existing) = self.type_span(&name) {
+            let msg = format!("`{}` was already defined at {}", name, existing);
+            self.error("Duplicate type", msg, span);
+            return;
+        }
+        let mut variant_names: Vec<String> = Vec::new();
+        // discriminants count up from the last one given, like C
+        let mut values: Vec<(i128, String)> = Vec::new();
+        let mut next: i128 = 0;
+        for v in variants {
+            if let Statement::Declaration { ident, value, .. } = v {
+                if let Statement::Identifier { ident, span, .. } = ident.as_ref() {
+                    if variant_names.contains(ident) {
+                        let msg = format!("`{}` already has a variant named `{}`", name, ident);
+                        self.error("Duplicate variant", msg, span.clone());
+                        continue;
+                    }
+                    if let Some(value) = value {
+                        match literal_value(value) {
+                            Some(v) if !matches!(value.as_ref(), Statement::CharLiteral { .. }) => next = v,
+                            _ => {
+                                let msg = format!("the discriminant of `{}` must be an integer", ident);
+                                self.error("Invalid discriminant", msg, span.clone());
+                            }
+                        }
+                    }
+                    if let Some((_, other)) = values.iter().find(|(v, _)| *v == next) {
+                        let msg = format!("`{}` has the same value as `{}` ({})", ident, other, next);
+                        self.error("Duplicate discriminant", msg, span.clone());
+                    }
+                    values.push((next, ident.clone()));
+                    next += 1;
+                    variant_names.push(ident.clone());
+                }
+            }
+        }
+        self.enums.insert(name, EnumSymbol { variants: variant_names, span });
+    }
+
+    fn check_path(&mut self, base: &mut Statement, member: &Statement) {
+        let (name, span) = match base {
+            Statement::Identifier { ident, span, .. } => (ident, span),
+            _ => {
+                self.check(base);
+                return;
+            }
+        };
+        let variant = match member {
+            Statement::Identifier { ident, span, .. } => (ident, span),
+            _ => return
+        };
+        match self.enums.get(name) {
+            Some(e) if !e.variants.contains(variant.0) => {
+                let msg = format!("`{}` has no variant named `{}`", name, variant.0);
+                self.error("Unknown variant", msg, variant.1.clone());
+            }
+            Some(_) => {}
+            None => {
+                self.error("Undefined enum", format!("no enum named `{}` exists", name), span.clone());
+            }
+        }
+    }
+
+    fn check_struct_literal(&mut self, ident: &mut Statement, fields: &mut [Statement]) {
+        for f in fields.iter_mut() {
+            if let Statement::Assignment { value, .. } = f {
+                self.check(value);
+            }
+        }
+        self.check_struct_fields(ident, fields);
+    }
+
+    /// checks that a struct literal names a struct and gives each of its fields a value once
+    fn check_struct_fields(&mut self, ident: &mut Statement, fields: &[Statement]) {
+        let mut given: Vec<String> = Vec::new();
+        for f in fields {
+            if let Statement::Assignment { ident: field, .. } = f {
+                if let Statement::Identifier { ident: field, span, .. } = field.as_ref() {
+                    if given.contains(field) {
+                        self.error("Duplicate field", format!("`{}` was already given a value", field), span.clone());
+                    }
+                    given.push(field.clone());
+                }
+            }
+        }
+        let (name, span) = match ident {
+            Statement::Identifier { ident, span, .. } => (ident.clone(), span.clone()),
+            Statement::Path { member, span, .. } => match member.as_ref() {
+                Statement::Identifier { ident, .. } => (ident.clone(), span.clone()),
+                _ => return
+            },
+            _ => return
+        };
+        let found = match self.resolve_item(ident) {
+            Some(found) => found,
+            None => return
+        };
+        let declared = match (found, ident) {
+            (true, Statement::Identifier { ident, .. }) if self.structs.contains_key(ident) => self.structs[ident].fields.clone(),
+            _ => {
+                self.error("Undefined struct", format!("no struct named `{}` exists", name), span.clone());
+                return;
+            }
+        };
+        for f in fields.iter() {
+            if let Statement::Assignment { ident: field, .. } = f {
+                if let Statement::Identifier { ident: field, span, .. } = field.as_ref() {
+                    if !declared.contains(field) {
+                        let msg = format!("`{}` has no field named `{}`", name, field);
+                        self.error("Unknown field", msg, span.clone());
+                    }
+                }
+            }
+        }
+        let missing: Vec<String> = declared.into_iter().filter(|f| !given.contains(f)).collect();
+        if !missing.is_empty() {
+            let msg = format!("`{}` is missing {}", name, missing.iter().map(|f| format!("`{}`", f))
+                .collect::<Vec<String>>().join(", "));
+            self.error("Missing fields", msg, span.clone());
+        }
+    }
+
+    /// collects the methods of every impl block, once all structs are known
+    fn collect_methods(&mut self, exprs: &mut Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Impl { ident, methods, .. } => {
+                    let (written, span) = match ident.as_ref() {
+                        Statement::Identifier { ident, span, .. } => (ident.clone(), span.clone()),
+                        _ => continue
+                    };
+                    let type_name = match (self.resolve_item(ident), ident.as_ref()) {
+                        (None, _) => continue,
+                        (Some(true), Statement::Identifier { ident, .. }) => ident.clone(),
+                        _ => written.clone()
+                    };
+                    if !self.structs.contains_key(&type_name) {
+                        self.error("Undefined struct", format!("no struct named `{}` exists", written), span);
+                        continue;
+                    }
+                    for m in methods {
+                        let (name, span) = match m {
+                            Statement::Fn { ident, .. } => match ident.as_ref() {
+                                Statement::Identifier { ident, span, .. } => (ident.clone(), span.clone()),
+                                _ => continue
+                            },
+                            _ => continue
+                        };
+                        let defined = self.methods.entry(type_name.clone()).or_default();
+                        if let Some(existing) = defined.get(&name) {
+                            let msg = format!("`{}` already has a method named `{}` at {}", type_name, name, existing);
+                            self.error("Duplicate method", msg, span);
+                            continue;
+                        }
+                        defined.insert(name, span);
+                    }
+                }
+                Statement::Use { exprs, .. } => self.collect_methods(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_globals(&mut self, exprs: &mut Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Fn { params, return_type, body, .. } => {
+                    for p in params.iter_mut() {
+                        if let Statement::Declaration { type_ident: Some(t), .. } = p {
+                            self.check_type(t);
+                        }
+                    }
+                    self.check_type(return_type);
+                    self.check_fn(params, body);
+                }
+                Statement::Impl { methods, .. } => self.check_globals(methods),
+                Statement::Struct { fields, .. } => {
+                    for f in fields {
+                        if let Statement::Declaration { type_ident: Some(t), .. } = f {
+                            self.check_type(t);
+                        }
+                    }
+                }
+                // consts and statics are replaced by their value, so backends only see literals
+                Statement::Const { ident, type_ident, value, .. } => {
+                    self.check_type(type_ident);
+                    let name = match ident.as_ref() {
+                        Statement::Identifier { ident, .. } => ident.clone(),
+                        _ => continue
+                    };
+                    // a duplicate const isn't in the global scope, so it is evaluated on its own
+                    let result = if self.consts.get(&name).is_some_and(|(v, _)| v == value.as_ref()) {
+                        self.const_value(&name)
+                    } else {
+                        self.global_value(value, type_ident)
+                    };
+                    if let Some(result) = result {
+                        let span = value.span().cloned().unwrap_or_default();
+                        **value = result.to_literal(Checker::global_type(type_ident).as_ref(), span);
+                    }
+                }
+                Statement::Static { type_ident, value, .. } => {
+                    self.check_type(type_ident);
+                    if let Some(result) = self.global_value(value, type_ident) {
+                        let span = value.span().cloned().unwrap_or_default();
+                        **value = result.to_literal(Checker::global_type(type_ident).as_ref(), span);
+                    }
+                }
+                Statement::Use { exprs, .. } => self.check_globals(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    /// checks the default values of every function's parameters before any call is, since they are copied
+    /// into every call that leaves them out. They are checked where the function is defined, outside of any
+    /// function, so they can't use other parameters and the names in them refer to the callee's items
+    fn check_defaults(&mut self, exprs: &mut Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Fn { ident, params, .. } => {
+                    let mut declared = Vec::new();
+                    for p in params.iter_mut() {
+                        if let Statement::Declaration { ident, value, .. } = p {
+                            if let Some(v) = value {
+                                self.check(v);
+                            }
+                            if let Statement::Identifier { ident, .. } = ident.as_ref() {
+                                declared.push((ident.clone(), value.as_deref().cloned()));
+                            }
+                        }
+                    }
+                    // a duplicate function's defaults aren't the ones calls use
+                    if let Statement::Identifier { ident, span, .. } = ident.as_ref() {
+                        if let Some(f) = self.functions.get_mut(ident).filter(|f| f.span == *span) {
+                            f.params = declared;
+                        }
+                    }
+                }
+                Statement::Impl { methods: exprs, .. } | Statement::Use { exprs, .. } => self.check_defaults(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_fn(&mut self, params: &mut Vec<Statement>, body: &mut Statement) {
+        self.labels.clear();
+        self.push_scope();
+        for p in params {
+            if let Statement::Declaration { ident, .. } = p {
+                if let Statement::Identifier { ident, span, .. } = ident.as_ref() {
+                    if let Some(existing) = self.lookup(ident) {
+                        let msg = format!("`{}` was already used as a parameter at {}", ident, existing.span);
+                        self.error("Duplicate parameter", msg, span.clone());
+                        continue;
+                    }
+                    self.declare(ident.clone(), SymbolKind::Param, span.clone());
+                }
+            }
+        }
+        self.check(body);
+        self.pop_scope();
+    }
+
+    fn check_call(&mut self, ident: &mut Statement, params: &mut Vec<Statement>) {
+        for p in params.iter_mut() {
+            match p {
+                Statement::Assignment { value, .. } => self.check(value),
+                p => self.check(p)
+            }
+        }
+        let written = match ident {
+            Statement::Identifier { ident, .. } => ident.clone(),
+            Statement::Path { member, .. } => match member.as_ref() {
+                Statement::Identifier { ident, .. } => ident.clone(),
+                _ => return
+            },
+            _ => {
+                self.check(ident);
+                return;
+            }
+        };
+        let found = match self.resolve_item(ident) {
+            Some(found) => found,
+            None => return
+        };
+        let span = ident.span().cloned().unwrap_or_default();
+        let declared = match ident {
+            Statement::Identifier { ident, .. } if found => self.functions.get(ident).map(|f| f.params.clone()),
+            _ => None
+        };
+        match declared {
+            Some(declared) => {
+                let mut errors = Vec::new();
+                if let Some(args) = order_args(&written, &declared, params, &span, &|n| self.is_local(n), &mut errors) {
+                    *params = args;
+                }
+                self.errors.extend(errors);
+            }
+            None => self.error("Undefined function", format!("no function named `{}` exists", written), span)
+        }
+    }
+
+    fn check_loop_body(&mut self, label: &mut Option<Box<Statement>>, gives_value: bool, body: &mut Statement) {
+        let label = match label.as_deref_mut() {
+            Some(Statement::Identifier { ident, span, .. }) => {
+                if self.loops.iter().any(|l| l.label.as_ref().is_some_and(|(name, _)| name == ident)) {
+                    let msg = format!("`'{}` already labels a loop this one is inside of", ident);
+                    self.error("Duplicate label", msg, span.clone());
+                }
+                let original = ident.clone();
+                let count = self.labels.entry(original.clone()).or_insert(0);
+                if *count > 0 {
+                    *ident = format!("{}_{}", original, count);
+                }
+                *count += 1;
+                Some((original, ident.clone()))
+            }
+            _ => None
+        };
+        self.loops.push(LoopFrame { label, gives_value });
+        self.check(body);
+        self.loops.pop();
+    }
+
+    /// finds the loop a `break` or `continue` leaves, giving its label the name the loop's label was given
+    fn loop_target(&mut self, label: &mut Option<Box<Statement>>, is_break: bool, span: &Span) -> Option<usize> {
+        let keyword = if is_break { "break" } else { "continue" };
+        if self.loops.is_empty() {
+            let core = if is_break { "Break outside of a loop" } else { "Continue outside of a loop" };
+            self.error(core, format!("`{}` can only be used inside of a loop", keyword), span.clone());
+            return None;
+        }
+        let (ident, label_span) = match label.as_deref_mut() {
+            Some(Statement::Identifier { ident, span, .. }) => (ident, span),
+            _ => return Some(self.loops.len() - 1)
+        };
+        let found = self.loops.iter().rposition(|l| l.label.as_ref().is_some_and(|(name, _)| name == ident));
+        match found {
+            Some(index) => {
+                *ident = self.loops[index].label.as_ref().unwrap().1.clone();
+                Some(index)
+            }
+            None => {
+                let msg = format!("no loop labeled `'{}` is around this `{}`", ident, keyword);
+                self.error("Undefined label", msg, label_span.clone());
+                None
+            }
+        }
+    }
+
+    fn check(&mut self, stmt: &mut Statement) {
+        // a name that isn't a local is an item, which is replaced by the item's unique name
+        let missing = match stmt {
+            Statement::Identifier { ident, span, .. } if !self.is_local(ident) => {
+                Some(("Undefined identifier", format!("no variable named `{}` is in scope", ident), span.clone()))
+            }
+            Statement::Path { base, .. } => {
+                let mut enum_name = base.as_ref();
+                while let Statement::Path { base, .. } = enum_name {
+                    enum_name = base;
+                }
+                match enum_name {
+                    Statement::Identifier { ident, span, .. } => {
+                        Some(("Undefined enum", format!("no enum named `{}` exists", ident), span.clone()))
+                    }
+                    _ => None
+                }
+            }
+            _ => None
+        };
+        if let Some((core, msg, span)) = missing {
+            match self.resolve_item(stmt) {
+                Some(true) => {}
+                Some(false) => return self.error(core, msg, span),
+                None => return
+            }
+        }
+        let parts: Vec<&Statement> = match &*stmt {
+            Statement::Binary { op, left, right, .. } if !matches!(op, Operator::BoolAnd | Operator::BoolOr) => {
+                vec![left, right]
+            }
+            Statement::Assignment { ident, value, .. } | Statement::CompoundAssignment { ident, value, .. } => {
+                vec![ident, value]
+            }
+            Statement::ArrayAccess { ident, index, .. } => vec![ident, index],
+            Statement::MethodCall { receiver, params, .. } => std::iter::once(receiver.as_ref()).chain(params).collect(),
+            Statement::FnCall { params: parts, .. } | Statement::ArrayLiteral { items: parts, .. } |
+            Statement::StructLiteral { fields: parts, .. } => parts.iter().collect(),
+            _ => Vec::new()
+        };
+        self.check_sequenced(&parts);
+        match stmt {
+            Statement::Block { exprs, .. } => {
+                self.push_scope();
+                for expr in exprs {
+                    self.check(expr);
+                }
+                self.pop_scope();
+            }
+            Statement::Declaration { ident, type_ident, value, .. } => {
+                if let Some(t) = type_ident {
+                    self.check_type(t);
+                }
+                // the value is checked first so `let x: int = x` can't see itself
+                if let Some(v) = value {
+                    self.check(v);
+                }
+                if let Statement::Identifier { ident, span, .. } = ident.as_ref() {
+                    self.declare(ident.clone(), SymbolKind::Local, span.clone());
+                }
+            }
+            Statement::Assignment { ident, value, .. } | Statement::CompoundAssignment { ident, value, .. } => {
+                self.check_assignable(ident);
+                self.check(ident);
+                self.check(value);
+            }
+            // a const is replaced by its value wherever it is used, unless it is an array or struct
+            Statement::Identifier { ident, span, .. } if self.lookup(ident).is_some_and(|s| s.kind == SymbolKind::Const) => {
+                let (name, span) = (ident.clone(), span.clone());
+                let target = self.consts.get(&name).and_then(|(_, t)| Checker::global_type(t));
+                if let Some(value) = self.const_value(&name).filter(|v| !v.is_aggregate()) {
+                    *stmt = value.to_literal(target.as_ref(), span);
+                }
+            }
+            Statement::Identifier { ident, .. } if self.lookup(ident)
+                .is_some_and(|s| matches!(s.kind, SymbolKind::Static | SymbolKind::StaticMut)) => {}
+            Statement::Identifier { ident, span, .. } if self.lookup(ident).is_none() => {
+                if self.functions.contains_key(ident) {
+                    let msg = format!("`{}` is a function, did you mean to call it?", ident);
+                    self.error("Expected a variable", msg, span.clone());
+                } else {
+                    self.error("Undefined identifier", format!("no variable named `{}` is in scope", ident), span.clone());
+                }
+            }
+            Statement::FnCall { ident, params, .. } => self.check_call(ident, params),
+            Statement::StructLiteral { ident, fields, .. } => self.check_struct_literal(ident, fields),
+            Statement::Path { base, member, .. } => self.check_path(base, member),
+            Statement::Match { value, arms, .. } => {
+                self.check(value);
+                for arm in arms {
+                    if let Statement::MatchArm { pattern, body, .. } = arm {
+                        self.check(pattern);
+                        self.check(body);
+                    }
+                }
+            }
+            // which methods exist depends on the type of the receiver, so the type checker puts the arguments in order
+            Statement::MethodCall { receiver, params, .. } => {
+                self.check(receiver);
+                for p in params {
+                    match p {
+                        Statement::Assignment { value, .. } => self.check(value),
+                        p => self.check(p)
+                    }
+                }
+            }
+            Statement::Binary { left, right, .. } => {
+                self.check(left);
+                self.check(right);
+            }
+            Statement::Unary { op: Operator::Inc | Operator::Dec, expr, .. } => {
+                self.check_assignable(expr);
+                self.check(expr);
+            }
+            Statement::Unary { expr, .. } => self.check(expr),
+            Statement::Cast { expr, cast_type, .. } => {
+                self.check(expr);
+                self.check_type(cast_type);
+            }
+            Statement::ArrayLiteral { items, .. } => {
+                for i in items {
+                    self.check(i);
+                }
+            }
+            Statement::ArrayRepeat { value, size, .. } => {
+                self.check(value);
+                self.check_positive(size, "Invalid array size", "an array's size");
+            }
+            Statement::If { condition, body, else_statement, .. } => {
+                self.check(condition);
+                self.check(body);
+                if let Some(else_body) = else_statement {
+                    self.check(else_body);
+                }
+            }
+            Statement::While { condition, body, label, .. } => {
+                self.check(condition);
+                self.check_loop_body(label, false, body);
+            }
+            Statement::Loop { body, label, .. } => self.check_loop_body(label, true, body),
+            Statement::For { ident, collection, step, body, label, .. } => {
+                self.check(collection);
+                if let Some(step) = step {
+                    self.check_positive(step, "Invalid step", "a for loop's step");
+                }
+                self.push_scope();
+                if let Statement::Identifier { ident, span, .. } = ident.as_ref() {
+                    self.declare(ident.clone(), SymbolKind::Local, span.clone());
+                }
+                self.check_loop_body(label, false, body);
+                self.pop_scope();
+            }
+            Statement::Break { label, value, span } => {
+                if let Some(value) = value {
+                    self.check(value);
+                }
+                let target = self.loop_target(label, true, span);
+                if let (Some(index), Some(value)) = (target, value) {
+                    if !self.loops[index].gives_value {
+                        let span = value.span().cloned().unwrap_or_else(|| span.clone());
+                        self.error("Break with a value", "only `loop` can give a value, `while` and `for` can't", span);
+                    }
+                }
+            }
+            Statement::Continue { label, span } => {
+                self.loop_target(label, false, span);
+            }
+            Statement::Return { value, .. } => self.check(value),
+            Statement::Panic { value, .. } => self.check(value),
+            Statement::Assert { expr, .. } => self.check(expr),
+            // properties are resolved against the type of the expression, which is not known here
+            Statement::PropertyAccess { expr, .. } => self.check(expr),
+            Statement::ArrayAccess { ident, index, .. } => {
+                self.check(ident);
+                self.check(index);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Checks the program for semantic errors, returning every error found. Every call passes all of its
+/// arguments in order afterwards
+pub fn check_program(ast: &mut Statement) -> Result<(), Vec<Error>> {
+    let mut checker = Checker::new();
+    if let Statement::Program { exprs, span } = ast {
+        checker.collect_modules(exprs);
+        checker.name_items(&span.start.file);
+        checker.collect_functions(exprs);
+        checker.check_struct_cycles();
+        checker.collect_methods(exprs);
+        checker.check_defaults(exprs);
+        checker.check_globals(exprs);
+    }
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
@@ -1,32 +1,40 @@
 use std::fmt::{Display, Formatter};
 use better_term::{Color, Style};
-use crate::{CodePos, read_file};
+use crate::{CodePos, read_file, Span};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Error {
     pub core_msg: Option<String>,
     pub msg: String,
-    pub pos: CodePos
+    pub pos: CodePos,
+    /// the byte offset the error's span ends at, which is where the carets under it stop
+    pub end: usize,
 }
 
 impl Error {
-    pub fn new<S: Into<String>, S2: Into<String>>(core: S, msg: S2, pos: CodePos) -> Self {
+    /// creates an error at a position or span
+    pub fn new<S: Into<String>, S2: Into<String>, P: Into<Span>>(core: S, msg: S2, pos: P) -> Self {
+        let span = pos.into();
         Self {
             core_msg: Some(core.into()),
             msg: msg.into(),
-            pos
+            pos: span.start,
+            end: span.end
         }
     }
-    pub fn new_singular<S: Into<String>>(msg: S, pos: CodePos) -> Self {
+    pub fn new_singular<S: Into<String>, P: Into<Span>>(msg: S, pos: P) -> Self {
+        let span = pos.into();
         Self {
             core_msg: None,
             msg: msg.into(),
-            pos
+            pos: span.start,
+            end: span.end
         }
     }
 }
 
 impl Display for Error {
+    #[allow(clippy::unnecessary_unwrap)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.core_msg.is_some() {
             write!(f, "{}error{}: {}: {}{}",
@@ -39,29 +47,33 @@ impl Display for Error {
     }
 }
 
+#[allow(clippy::unnecessary_unwrap, clippy::useless_format)]
 pub fn print_error(err: Error) {
     let mut code = String::new();
     if err.pos.file.is_some() {
         code = read_file(err.pos.file.as_ref().unwrap().clone());
     }
     let err_msg = format!("{}", err.clone()); // the message to display
-    // the line the error is on
-    let mut full_line = code.split("\n").collect::<Vec<&str>>().get(err.pos.line - 1).unwrap().to_string();
+    // the line the error is on, which is missing if the source couldn't be read
+    let mut full_line = code.split("\n").nth(err.pos.line - 1).unwrap_or("").to_string();
     // remove leading spaces
     let mut removed = 0;
-    while full_line.len() > 0 && full_line.chars().collect::<Vec<char>>().first().unwrap().clone() == ' ' {
+    while full_line.starts_with(' ') {
         full_line.remove(0);
         removed += 1;
     }
     // the line with the error with comments removed
-    let line = full_line.split("//").collect::<Vec<&str>>().get(0).unwrap().to_string();
+    let line = full_line.split("//").next().unwrap().to_string();
     // the number to display for the error
     let line_num = format!("{} | ", err.pos.line);
     // the pipes with spaces before them before and after the error line
     let pipe = format!("{} | ", " ".repeat(err.pos.line.to_string().len()));
-    // the carrot to display for the error
-    let carrot_spaces = " ".repeat(err.pos.ch - 1 - removed);
-    let carrot = carrot_spaces + "^" + if err.core_msg.is_some() {
+    // the carrot to display for the error, under every char of the span that is on this line
+    let carrot_spaces = " ".repeat((err.pos.ch - 1).saturating_sub(removed));
+    let width = code.get(err.pos.offset..err.end)
+        .map(|spanned| spanned.split('\n').next().unwrap().chars().count())
+        .unwrap_or(0).max(1);
+    let carrot = carrot_spaces + &"^".repeat(width) + if err.core_msg.is_some() {
         format!(" {}", err.core_msg.unwrap())
     } else {
         format!("")
@@ -90,17 +102,53 @@ pub fn print_error(err: Error) {
     }
 }
 
+/// how many errors are shown when no limit is given
+pub const DEFAULT_ERROR_LIMIT: usize = 20;
+
+/// prints errors in the order they were found. Only the first `limit` are shown so a badly broken
+/// file doesn't bury its first errors, which often cause the rest. A limit of 0 shows every error
+pub fn print_errors(errors: Vec<Error>, limit: usize) {
+    let total = errors.len();
+    let shown = if limit == 0 { total } else { total.min(limit) };
+    for e in errors.into_iter().take(shown) {
+        print_error(e);
+    }
+    if shown < total {
+        println!("{}...and {} more error{}{}", Color::BrightRed, total - shown,
+                 if total - shown == 1 { "" } else { "s" }, Style::reset());
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CompilerError {
     pub msg: String,
+    /// the code that could not be compiled, if it is known
+    pub span: Option<Span>,
 }
 
 impl CompilerError {
     pub fn new<S: Into<String>>(msg: S) -> Self {
         Self {
-            msg: msg.into()
+            msg: msg.into(),
+            span: None
         }
     }
+
+    /// gives the error a span if it doesn't have one yet, so the innermost statement is reported
+    pub fn at(mut self, span: Option<&Span>) -> Self {
+        if self.span.is_none() {
+            self.span = span.cloned();
+        }
+        self
+    }
+}
+
+/// prints a compiler error the same way as every other error when it is known where it came from
+pub fn print_compiler_error(err: CompilerError) {
+    match err.span {
+        Some(span) => print_error(Error::new_singular(err.msg, span)),
+        None => println!("{}", err)
+    }
 }
 
 impl Display for CompilerError {
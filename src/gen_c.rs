@@ -1,94 +1,826 @@
 use crate::error::CompilerError;
-use crate::statement::{Statement, ShuntedStackItem};
+use crate::statement::{Number, Statement};
 use crate::operator::Operator;
+use crate::types::{literal_value, Type};
+
+/// The processor the generated C is compiled for, which decides the assembly used for interrupts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Target {
+    #[default]
+    X86,
+    Arm,
+    RiscV,
+}
+
+impl Target {
+    pub fn from_name(name: &str) -> Option<Target> {
+        Some(match name {
+            "x86" | "x86_64" => Target::X86,
+            "arm" | "aarch64" => Target::Arm,
+            "riscv" | "riscv32" | "riscv64" => Target::RiscV,
+            _ => return None
+        })
+    }
+}
+
+/// Settings that change the C generated for a program
+#[derive(Debug, Clone, Default)]
+pub struct CodegenOptions {
+    pub target: Target,
+    /// leaves out asserts
+    pub release: bool,
+}
 
 impl Operator {
-    pub fn gen_c_code(&mut self) -> Result<String, CompilerError> {
+    pub fn gen_c_code(&self) -> Result<String, CompilerError> {
         Ok(self.as_raw())
     }
 }
 
+/// the C spelling of a boulder type name
+fn c_type_name(name: &str) -> &str {
+    match name {
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "u64" => "uint64_t",
+        "i8" => "int8_t",
+        "i16" => "int16_t",
+        "i32" => "int32_t",
+        "i64" => "int64_t",
+        "f32" => "float",
+        "f64" => "double",
+        "string" => "const char*",
+        name => name
+    }
+}
+
+/// an integer literal in the base it was written in. C has no suffix for most sized types, so a
+/// literal with a suffix is cast to its type instead
+fn c_number(number: &Number) -> String {
+    let digits = match number.radix {
+        2 => format!("0b{:b}", number.value),
+        8 => format!("0{:o}", number.value),
+        16 => format!("0x{:X}", number.value),
+        // decimal literals past the largest signed value have to be marked unsigned
+        _ if number.value > i64::MAX as u64 => format!("{}ULL", number.value),
+        _ => number.value.to_string(),
+    };
+    let literal = if number.negative { format!("-{}", digits) } else { digits };
+    match &number.suffix {
+        Some(t) => format!("(({}){})", c_type_name(&t.to_string()), literal),
+        None => literal
+    }
+}
+
+/// the name of a method once it is lowered to a free function
+fn mangle_method(type_name: &str, method: &str) -> String {
+    format!("{}__{}", type_name, method)
+}
+
+/// the C declaration of a function without its body, e.g. `int add(int a, int b)`
+fn fn_header(name: &str, params: &mut Vec<Statement>, return_type: &Statement, options: &CodegenOptions)
+    -> Result<String, CompilerError> {
+    let rt = c_type(return_type)?;
+    let mut c_params = Vec::new();
+    for p in params {
+        // the checker copies default values into every call that leaves them out, so C never sees them
+        if let Statement::Declaration { value, .. } = p {
+            *value = None;
+        }
+        c_params.push(p.gen_c_code(options)?);
+    }
+    Ok(format!("{} {}({})", rt, name, c_params.join(", ")))
+}
+
+/// declares every function and method in the program, including those from imported files
+fn fn_prototypes(exprs: &[Statement], code: &mut String, options: &CodegenOptions) -> Result<(), CompilerError> {
+    for expr in exprs {
+        match expr.clone() {
+            Statement::Fn { ident, mut params, return_type, .. } => {
+                let name = ident.clone().gen_c_code(options)?;
+                code.push_str(&format!("{};\n", fn_header(&name, &mut params, &return_type, options)?));
+            }
+            Statement::Impl { mut ident, methods, .. } => {
+                let type_name = ident.gen_c_code(options)?;
+                for m in methods {
+                    if let Statement::Fn { mut ident, mut params, return_type, .. } = m {
+                        let name = mangle_method(&type_name, &ident.gen_c_code(options)?);
+                        code.push_str(&format!("{};\n", fn_header(&name, &mut params, &return_type, options)?));
+                    }
+                }
+            }
+            Statement::Use { exprs, .. } => fn_prototypes(&exprs, code, options)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// defines every const and static in the program, including those from imported files. The checker has
+/// already replaced their values with literals
+fn globals(exprs: &[Statement], code: &mut String, options: &CodegenOptions) -> Result<(), CompilerError> {
+    for expr in exprs {
+        match expr.clone() {
+            Statement::Const { mut ident, type_ident, mut value, .. } => {
+                let decl = c_declaration(&type_ident, &ident.gen_c_code(options)?)?;
+                code.push_str(&format!("static const {} = {};\n", decl, c_initializer(&mut value, options)?));
+            }
+            Statement::Static { mut ident, type_ident, mut value, .. } => {
+                let decl = c_declaration(&type_ident, &ident.gen_c_code(options)?)?;
+                code.push_str(&format!("static {} = {};\n", decl, c_initializer(&mut value, options)?));
+            }
+            Statement::Use { exprs, .. } => globals(&exprs, code, options)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// returns true if the program, or a file it imports, defines a function with the given name
+fn has_function(exprs: &[Statement], name: &str) -> bool {
+    exprs.iter().any(|expr| match expr {
+        Statement::Fn { ident, .. } => matches!(ident.as_ref(), Statement::Identifier { ident, .. } if ident == name),
+        Statement::Use { exprs, .. } => has_function(exprs, name),
+        _ => false
+    })
+}
+
+/// prints a panic's message, passes it to the program's `panic_hook` if it has one, and halts
+fn panic_runtime(has_hook: bool) -> String {
+    format!("_Noreturn void boulder_panic(const char* format, ...) {{\n\
+             char message[256];\n\
+             va_list args;\n\
+             va_start(args, format);\n\
+             vsnprintf(message, sizeof(message), format, args);\n\
+             va_end(args);\n\
+             fprintf(stderr, \"%s\\n\", message);\n\
+             {}abort();\n\
+             }}\n", if has_hook { "panic_hook(message);\n" } else { "" })
+}
+
+/// checks an index into an array in debug builds, panicking with where the array was indexed if it is
+/// outside the array. The index is returned so the check can be used in place of it
+fn index_runtime() -> String {
+    "size_t boulder_index(long long index, size_t length, const char* location) {\n\
+     if (index < 0 || (size_t)index >= length) {\n\
+     boulder_panic(\"index out of bounds at %s: the length is %zu but the index is %lld\", location, length, index);\n\
+     }\n\
+     return (size_t)index;\n\
+     }\n".to_string()
+}
+
+/// the value a variable, field or global is declared with. Array literals can only be used here in C, as an
+/// initializer list for the struct the array is wrapped in
+fn c_initializer(value: &mut Statement, options: &CodegenOptions) -> Result<String, CompilerError> {
+    match value {
+        Statement::ArrayLiteral { items, .. } => {
+            let mut c_items = Vec::new();
+            for i in items {
+                c_items.push(c_initializer(i, options)?);
+            }
+            Ok(format!("{{{{{}}}}}", c_items.join(", ")))
+        }
+        Statement::ArrayRepeat { value, size, .. } => {
+            // the rest of an initializer list is zeroed, so zeros don't need to be written out
+            if literal_value(value) == Some(0) {
+                return Ok("{0}".to_string());
+            }
+            // the value is written once for every element, so it can't have side effects
+            if !matches!(value.as_ref(), Statement::NumberLiteral { .. } | Statement::FloatLiteral { .. } |
+                         Statement::BoolLiteral { .. } | Statement::CharLiteral { .. } | Statement::StringLiteral { .. } |
+                         Statement::Path { .. } | Statement::Identifier { .. } | Statement::ArrayLiteral { .. } |
+                         Statement::ArrayRepeat { .. }) {
+                return Err(CompilerError::new("A repeated array element can only be a literal or variable in C."));
+            }
+            let size = literal_value(size)
+                .ok_or_else(|| CompilerError::new("The size of an array literal was never evaluated."))?;
+            let value = c_initializer(value, options)?;
+            Ok(format!("{{{{{}}}}}", vec![value; size as usize].join(", ")))
+        }
+        // an initializer list rather than a compound literal, so it can be given to a global
+        Statement::StructLiteral { fields, .. } => {
+            let mut c_fields = Vec::new();
+            for f in fields {
+                if let Statement::Assignment { ident, value, .. } = f {
+                    c_fields.push(format!(".{} = {}", ident.gen_c_code(options)?, c_initializer(value, options)?));
+                }
+            }
+            Ok(format!("{{ {} }}", c_fields.join(", ")))
+        }
+        _ => value.gen_c_code(options)
+    }
+}
+
+/// escapes a character so it can be placed in a C string or char literal. Control characters use
+/// three digit octal escapes, which can't run into the characters after them like `\x` escapes can
+fn c_escape(c: char, quote: char) -> String {
+    match c {
+        '\n' => "\\n".to_string(),
+        '\t' => "\\t".to_string(),
+        '\r' => "\\r".to_string(),
+        '\\' => "\\\\".to_string(),
+        c if c == quote => format!("\\{}", c),
+        c if c.is_ascii_control() => format!("\\{:03o}", c as u32),
+        c => c.to_string()
+    }
+}
+
+/// a C string literal holding the text
+fn c_string(text: &str) -> String {
+    format!("\"{}\"", text.chars().map(|c| c_escape(c, '"')).collect::<String>())
+}
+
+/// text that can be placed in the format string of a panic
+fn c_format_text(text: &str) -> String {
+    text.chars().map(|c| c_escape(c, '"')).collect::<String>().replace('%', "%%")
+}
+
+/// the printf format and arguments that print a panic's value
+fn c_panic_value(value: &mut Statement, value_type: &Option<Box<Statement>>, options: &CodegenOptions)
+    -> Result<Option<(String, String)>, CompilerError> {
+    let type_name = match value_type.as_deref() {
+        Some(Statement::Void) => return Ok(None),
+        Some(Statement::Type { type_ident, modifiers, .. }) if modifiers.is_empty() => match type_ident.as_ref() {
+            Statement::Identifier { ident, .. } => ident.clone(),
+            _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
+        },
+        Some(Statement::Type { .. }) => return Ok(Some(("%p".to_string(), format!("(void*)({})", value.gen_c_code(options)?)))),
+        Some(_) => return Ok(Some(("%s".to_string(), "\"<array>\"".to_string()))),
+        None => return Err(CompilerError::new("The type of a panic's value was never checked."))
+    };
+    let value = value.gen_c_code(options)?;
+    Ok(Some(match type_name.as_str() {
+        "string" => ("%s".to_string(), value),
+        "char" => ("%c".to_string(), value),
+        "bool" => ("%s".to_string(), format!("({} ? \"true\" : \"false\")", value)),
+        "u8" | "u16" | "u32" | "u64" => ("%llu".to_string(), format!("(unsigned long long)({})", value)),
+        "i8" | "i16" | "i32" | "i64" | "int" => ("%lld".to_string(), format!("(long long)({})", value)),
+        "f32" | "f64" => ("%g".to_string(), format!("(double)({})", value)),
+        // structs and enums can't be printed by C, so only their type is
+        name => ("%s".to_string(), format!("\"<{}>\"", name)),
+    }))
+}
+
+/// finds every struct and enum in the program, including those from imported files
+fn find_types(exprs: &[Statement], structs: &mut Vec<Statement>, enums: &mut Vec<Statement>) {
+    for expr in exprs {
+        match expr {
+            Statement::Struct { .. } => structs.push(expr.clone()),
+            Statement::Enum { .. } => enums.push(expr.clone()),
+            Statement::Use { exprs, .. } => find_types(exprs, structs, enums),
+            _ => {}
+        }
+    }
+}
+
+/// a struct, or the struct an array is wrapped in, as C defines it, along with the types it holds by value
+struct TypeDefinition {
+    name: String,
+    holds: Vec<String>,
+    code: String,
+}
+
+/// the name of the type a field or array element holds by value, which C needs defined before it
+fn held_type(type_stmt: &Statement) -> Result<Option<String>, CompilerError> {
+    Ok(match type_stmt {
+        Statement::Type { type_ident, modifiers, .. } if modifiers.is_empty() => match type_ident.as_ref() {
+            Statement::Identifier { ident, .. } => Some(ident.clone()),
+            _ => None
+        },
+        Statement::ArrayType { modifiers, .. } if modifiers.is_empty() => Some(c_type(type_stmt)?),
+        _ => None
+    })
+}
+
+/// the number of elements in an array type, which the checker has already evaluated
+fn array_size(size: &Statement) -> Result<i128, CompilerError> {
+    literal_value(size).ok_or_else(|| CompilerError::new("The size of an array type was never evaluated."))
+}
+
+/// a name for a type that can be part of a C identifier, e.g. `ptr_u8` for `*u8`
+fn type_tag(type_stmt: &Statement) -> Result<String, CompilerError> {
+    match type_stmt {
+        Statement::Type { type_ident, modifiers, .. } => match type_ident.as_ref() {
+            Statement::Identifier { ident, .. } => Ok(format!("{}{}", "ptr_".repeat(modifiers.len()), ident)),
+            _ => Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
+        },
+        Statement::ArrayType { array_type, size, modifiers, .. } => {
+            Ok(format!("{}array_{}_{}", "ptr_".repeat(modifiers.len()), type_tag(array_type)?, array_size(size)?))
+        }
+        _ => Err(CompilerError::new("Expected type expression but AST provided an illegal expression."))
+    }
+}
+
+/// finds every array type in a statement and defines the struct C wraps it in, so it is copied by value
+/// when it is assigned, passed or returned like it is in boulder
+fn find_array_types(stmt: &Statement, arrays: &mut Vec<TypeDefinition>) -> Result<(), CompilerError> {
+    if let Statement::ArrayType { array_type, size, .. } = stmt {
+        let name = format!("boulder_array_{}_{}", type_tag(array_type)?, array_size(size)?);
+        if !arrays.iter().any(|a| a.name == name) {
+            let code = format!("typedef struct {name} {{\n{} items[{}];\n}} {name};\n", c_type(array_type)?,
+                               array_size(size)?, name = name);
+            arrays.push(TypeDefinition { name, holds: held_type(array_type)?.into_iter().collect(), code });
+        }
+    }
+    for child in stmt.children() {
+        find_array_types(child, arrays)?;
+    }
+    Ok(())
+}
+
+/// orders type definitions so every type comes after the types it holds by value
+fn order_types(types: Vec<TypeDefinition>) -> Vec<TypeDefinition> {
+    let mut ordered: Vec<TypeDefinition> = Vec::new();
+    let mut remaining = types;
+    while !remaining.is_empty() {
+        let names: Vec<String> = remaining.iter().map(|t| t.name.clone()).collect();
+        let (ready, waiting): (Vec<TypeDefinition>, Vec<TypeDefinition>) = remaining.into_iter()
+            .partition(|t| t.holds.iter().all(|n| !names.contains(n)));
+        // structs that hold each other can't be ordered, but the checker has already reported them
+        if ready.is_empty() {
+            ordered.extend(waiting);
+            break;
+        }
+        ordered.extend(ready);
+        remaining = waiting;
+    }
+    ordered
+}
+
+/// the C condition for a match pattern, or None if it matches everything
+fn pattern_condition(pattern: &mut Statement, value: &str, options: &CodegenOptions)
+    -> Result<Option<String>, CompilerError> {
+    Ok(Some(match pattern {
+        Statement::Wildcard { .. } => return Ok(None),
+        Statement::RangePattern { start, end, inclusive, .. } => {
+            format!("({} >= {} && {} {} {})", value, start.gen_c_code(options)?, value,
+                    if *inclusive { "<=" } else { "<" }, end.gen_c_code(options)?)
+        }
+        Statement::StringLiteral { .. } => return Err(CompilerError::new("Matching strings is not supported in C yet.")),
+        _ => format!("({} == {})", value, pattern.gen_c_code(options)?)
+    }))
+}
+
+/// returns true if a statement contains a break that isn't inside a nested loop, which a
+/// C switch would catch instead of the loop it was meant for
+fn breaks_out(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Break { .. } => true,
+        Statement::Block { exprs, .. } => exprs.iter().any(breaks_out),
+        Statement::If { body, else_statement, .. } => {
+            breaks_out(body) || else_statement.as_ref().is_some_and(|e| breaks_out(e))
+        }
+        Statement::Match { arms, .. } => arms.iter().any(breaks_out),
+        Statement::MatchArm { body, .. } => breaks_out(body),
+        _ => false
+    }
+}
+
+/// the name of a loop label, which C has no labelled loops for, so labelled loops jump to
+/// `name_continue` and `name_break` with goto instead
+fn c_label(label: &Option<Box<Statement>>) -> Option<String> {
+    match label.as_deref() {
+        Some(Statement::Identifier { ident, .. }) => Some(ident.clone()),
+        _ => None
+    }
+}
+
+/// a loop with a C header, e.g. `while (1)`, and a body that is a C statement. Labelled loops get a label at the end of the body
+/// for `continue` and one after the loop for `break`
+fn c_loop(header: String, body: String, label: &Option<Box<Statement>>) -> String {
+    match c_label(label) {
+        Some(name) => format!("{} {{\n{}\n{}_continue: ;\n}}\n{}_break:", header, body, name, name),
+        None => format!("{} {}", header, body)
+    }
+}
+
+/// a for loop as a C loop. Ranges count a hidden counter, while arrays count an index, and either
+/// declares the variable as the value, or a pointer to the element when iterating over a reference
+fn c_for(stmt: &mut Statement, options: &CodegenOptions) -> Result<String, CompilerError> {
+    let (ident, collection, step, body, label, element_type, length, through_pointer) = match stmt {
+        Statement::For { ident, collection, step, body, label, element_type, length, through_pointer, .. } => {
+            (ident, collection, step, body, label, element_type, length, *through_pointer)
+        }
+        _ => return Err(CompilerError::new("Expected for loop but AST provided an illegal expression."))
+    };
+    let name = ident.gen_c_code(options)?;
+    let element_type = element_type.as_ref()
+        .ok_or_else(|| CompilerError::new("The type of a for loop's variable was never checked."))?;
+    if let Statement::Binary { op: op @ (Operator::Range | Operator::IRange), left, right, .. } = collection.as_mut() {
+        let (setup, advance) = c_range_counter(&name, *op == Operator::IRange, left, right, step, element_type, options)?;
+        let body = format!("{{\n{}\n{}\n}}", advance, c_arm_body(body, options)?);
+        return Ok(format!("{{\n{}{};\n}}", setup, c_loop(format!("while (__{}_more)", name), body, label)));
+    }
+    let step = match step {
+        Some(step) => step.gen_c_code(options)?,
+        None => "1".to_string()
+    };
+    let length = length.ok_or_else(|| CompilerError::new("The length of a for loop's array was never checked."))?;
+    // arrays are wrapped in a struct, which a reference to the array points to
+    let (array, access) = match collection.as_mut() {
+        Statement::Unary { op: Operator::And, expr, postfix: false, .. } => (expr.as_mut(), "."),
+        array if through_pointer => (array, "->"),
+        array => (array, ".")
+    };
+    // the array is named in every iteration, so it can't be something that has to be evaluated once
+    if !matches!(array, Statement::Identifier { .. } | Statement::PropertyAccess { .. }) {
+        return Err(CompilerError::new("A for loop can only iterate over an array in C when it is a variable or field."));
+    }
+    let array = array.gen_c_code(options)?;
+    let index = format!("__{}_index", name);
+    let header = format!("for (size_t {i} = 0; {i} < {}; {i} += {})", length, step, i = index);
+    let element = format!("{} = {}{}{}items[{}];", c_declaration(element_type, &name)?,
+                          if through_pointer { "&" } else { "" }, array, access, index);
+    Ok(c_loop(header, format!("{{\n{}\n{}\n}}", element, c_arm_body(body, options)?), label))
+}
+
+/// the hidden counter a for loop over a range counts with, as the code that sets it up before the loop and
+/// the code that copies it into the variable and steps it at the start of each time around. The bounds and
+/// step are worked out once and changing the variable doesn't change the counter, so neither changes how
+/// many times the loop runs. Whether there is another value is found before the counter steps, so it never
+/// steps past the end of its type. The loop runs while `__name_more` is set
+fn c_range_counter(name: &str, inclusive: bool, start: &mut Statement, end: &mut Statement,
+                   step: &mut Option<Box<Statement>>, element_type: &Statement,
+                   options: &CodegenOptions) -> Result<(String, String), CompilerError> {
+    let hidden = |part: &str| format!("__{}_{}", name, part);
+    let mut code = String::new();
+    // the step is evaluated before the range, like it is in the interpreter
+    let step = match step {
+        Some(step) => {
+            code += &format!("{} = {};\n", c_declaration(element_type, &hidden("step"))?, step.gen_c_code(options)?);
+            hidden("step")
+        }
+        None => "1".to_string()
+    };
+    code += &format!("{} = {};\n", c_declaration(element_type, &hidden("next"))?, start.gen_c_code(options)?);
+    code += &format!("{} = {};\n", c_declaration(element_type, &hidden("end"))?, end.gen_c_code(options)?);
+    code += &format!("bool {} = {} {} {};\n", hidden("more"), hidden("next"), if inclusive { "<=" } else { "<" }, hidden("end"));
+    // the distance to the end is found unsigned, since it can be too big for a signed type
+    let unsigned = operand_type(Some(element_type)).map_or(Type::U64, |t| t.unsigned());
+    let unsigned = c_type_name(&unsigned.to_string()).to_string();
+    let advance = format!("{} = {next};\n{more} = ({u}){end} - ({u}){next} {} ({u}){step};\nif ({more}) {{\n{next} += {step};\n}}",
+                          c_declaration(element_type, name)?, if inclusive { ">=" } else { ">" },
+                          next = hidden("next"), more = hidden("more"), end = hidden("end"), u = unsigned, step = step);
+    Ok((code, advance))
+}
+
+/// makes every break with a value that leaves a loop store the value in `target` first, since C
+/// loops can't give a value. Breaks in nested loops only leave this one if they use its label
+fn assign_breaks(stmt: &mut Statement, target: &Statement, label: &Option<String>, nested: bool) {
+    match stmt {
+        Statement::Break { label: break_label, value, span } if value.is_some() => {
+            let leaves = match c_label(break_label) {
+                Some(name) => Some(&name) == label.as_ref(),
+                None => !nested
+            };
+            if leaves {
+                let span = span.clone();
+                let assignment = Statement::Assignment {
+                    ident: Box::new(target.clone()), value: value.take().unwrap(), span: span.clone()
+                };
+                let exit = Statement::Break { label: break_label.take(), value: None, span: span.clone() };
+                *stmt = Statement::Block { exprs: vec![assignment, exit], span };
+            }
+        }
+        Statement::Block { exprs, .. } => exprs.iter_mut().for_each(|e| assign_breaks(e, target, label, nested)),
+        Statement::If { body, else_statement, .. } => {
+            assign_breaks(body, target, label, nested);
+            if let Some(else_statement) = else_statement {
+                assign_breaks(else_statement, target, label, nested);
+            }
+        }
+        Statement::Match { arms, .. } => arms.iter_mut().for_each(|a| assign_breaks(a, target, label, nested)),
+        Statement::MatchArm { body, .. } => assign_breaks(body, target, label, nested),
+        Statement::While { body, .. } | Statement::For { body, .. } | Statement::Loop { body, .. } => {
+            assign_breaks(body, target, label, true)
+        }
+        _ => {}
+    }
+}
+
+/// a `loop` whose value is stored in `target`
+fn c_loop_value(looped: &Statement, target: &Statement, options: &CodegenOptions) -> Result<String, CompilerError> {
+    let mut looped = looped.clone();
+    if let Statement::Loop { body, label, .. } = &mut looped {
+        assign_breaks(body, target, &c_label(label), false);
+    }
+    looped.gen_c_code(options)
+}
+
+/// returns true if a match used as a value can be nested conditionals, which evaluate the value once
+/// per arm and can't hold statements, so the value has to be a variable, field or constant and no arm a block
+fn conditional_match(value: &Statement, arms: &[Statement]) -> bool {
+    matches!(value, Statement::Identifier { .. } | Statement::PropertyAccess { .. } | Statement::Path { .. } |
+                    Statement::NumberLiteral { .. } | Statement::CharLiteral { .. }) &&
+        arms.iter().all(|a| !matches!(a, Statement::MatchArm { body, .. } if matches!(body.as_ref(), Statement::Block { .. })))
+}
+
+/// makes the value of a match arm, the last statement when it is a block, be passed to `give`, which
+/// turns it into the statement that stores or returns it. Arms that end in a statement are left alone
+fn give_value(body: &mut Statement, give: &dyn Fn(Statement) -> Statement) {
+    match body {
+        Statement::Block { exprs, .. } => {
+            if let Some(last) = exprs.last_mut() {
+                give_value(last, give);
+            }
+        }
+        Statement::Declaration { .. } | Statement::Assignment { .. } | Statement::CompoundAssignment { .. } |
+        Statement::Return { .. } | Statement::Break { .. } | Statement::Continue { .. } | Statement::Panic { .. } |
+        Statement::Assert { .. } | Statement::If { .. } | Statement::While { .. } | Statement::For { .. } |
+        Statement::Loop { .. } | Statement::NOP => {}
+        _ => *body = give(std::mem::replace(body, Statement::NOP))
+    }
+}
+
+/// a match used as a value that can't be nested conditionals, as a match statement whose arms pass their
+/// value to `give`, like the breaks of a `loop` with a value store it
+fn c_match_value(matched: &Statement, give: &dyn Fn(Statement) -> Statement, options: &CodegenOptions)
+    -> Result<String, CompilerError> {
+    let mut matched = matched.clone();
+    match &mut matched {
+        Statement::Match { value, arms, value_type, .. } => {
+            for arm in arms.iter_mut() {
+                if let Statement::MatchArm { body, .. } = arm {
+                    give_value(body, give);
+                }
+            }
+            c_match_statement(value, arms, value_type, options)
+        }
+        _ => Err(CompilerError::new("Expected match expression but AST provided an illegal expression."))
+    }
+}
+
+/// a match arm's or loop's body as a C statement
+fn c_arm_body(body: &mut Statement, options: &CodegenOptions) -> Result<String, CompilerError> {
+    Ok(match body {
+        Statement::Block { .. } => body.gen_c_code(options)?,
+        _ => format!("{};", body.gen_c_code(options)?)
+    })
+}
+
+/// a match used as a statement. Literal and enum patterns become a switch, anything else
+/// becomes a chain of ifs comparing against a copy of the value
+fn c_match_statement(value: &mut Statement, arms: &mut [Statement], value_type: &Option<Box<Statement>>,
+                     options: &CodegenOptions) -> Result<String, CompilerError> {
+    let use_switch = arms.iter().all(|a| match a {
+        Statement::MatchArm { pattern, body, .. } => !breaks_out(body) && matches!(pattern.as_ref(),
+            Statement::NumberLiteral { .. } | Statement::CharLiteral { .. } | Statement::BoolLiteral { .. } | Statement::Path { .. } |
+            Statement::Wildcard { .. }),
+        _ => false
+    });
+    if use_switch {
+        let mut code = format!("switch ({}) {{
+", value.gen_c_code(options)?);
+        for arm in arms {
+            if let Statement::MatchArm { pattern, body, .. } = arm {
+                let label = match pattern.as_ref() {
+                    Statement::Wildcard { .. } => "default".to_string(),
+                    _ => format!("case {}", pattern.gen_c_code(options)?)
+                };
+                code.push_str(&format!("{}: {} break;
+", label, c_arm_body(body, options)?));
+            }
+        }
+        code.push('}');
+        return Ok(code);
+    }
+    let value_type = value_type.as_ref()
+        .ok_or_else(|| CompilerError::new("The type of a match's value was never checked."))?;
+    let mut code = format!("{{
+{} = {};
+", c_declaration(value_type, "__match_value")?, value.gen_c_code(options)?);
+    let mut first = true;
+    for arm in arms {
+        if let Statement::MatchArm { pattern, body, .. } = arm {
+            if !first {
+                code.push_str(" else ");
+            }
+            first = false;
+            match pattern_condition(pattern, "__match_value", options)? {
+                Some(condition) => code.push_str(&format!("if {} {}", condition, c_arm_body(body, options)?)),
+                None => {
+                    code.push_str(&c_arm_body(body, options)?);
+                    break;
+                }
+            }
+        }
+    }
+    code.push_str("
+}");
+    Ok(code)
+}
+
+/// inline assembly that raises an interrupt on the target
+fn c_interrupt(vector: &Statement, target: Target) -> Result<String, CompilerError> {
+    let value = literal_value(vector)
+        .ok_or_else(|| CompilerError::new("Interrupt vectors must be an integer literal."))?;
+    // x86 and ARM encode the vector in the instruction, RISC-V passes it to the handler in a7
+    let (max, asm) = match target {
+        Target::X86 => (0xFF, format!("__asm__ volatile (\"int ${}\")", value)),
+        Target::Arm => (0xFFFFFF, format!("__asm__ volatile (\"svc #{}\")", value)),
+        Target::RiscV => (0x7FF, format!("__asm__ volatile (\"li a7, {}\\n\\tecall\" ::: \"a7\")", value)),
+    };
+    if !(0..=max).contains(&value) {
+        return Err(CompilerError::new(format!("Interrupt vector {:#x} does not fit in the {:?} instruction, \
+                                               which allows up to {:#x}.", value, target, max)));
+    }
+    Ok(asm)
+}
+
+/// converts a type statement to a C type. References are passed as pointers in C
+/// the primitive type the type checker gave an operator's operands, if it gave one
+fn operand_type(value_type: Option<&Statement>) -> Option<Type> {
+    match value_type? {
+        Statement::Type { type_ident, modifiers, .. } if modifiers.is_empty() => match type_ident.as_ref() {
+            Statement::Identifier { ident, .. } => Type::from_name(ident),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// C does arithmetic on integers narrower than an int as an int, so the result is cast back to wrap the
+/// way it does in boulder
+fn c_wrap(operand: &Option<Type>, expr: String) -> String {
+    match operand {
+        Some(t @ (Type::U8 | Type::I8 | Type::U16 | Type::I16)) => format!("(({}){})", c_type_name(&t.to_string()), expr),
+        _ => expr
+    }
+}
+
+fn c_type(type_stmt: &Statement) -> Result<String, CompilerError> {
+    match type_stmt {
+        Statement::Type { type_ident, modifiers, .. } => {
+            let name = match type_ident.as_ref() {
+                Statement::Identifier { ident, .. } => c_type_name(ident),
+                _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
+            };
+            Ok(format!("{}{}", name, "*".repeat(modifiers.len())))
+        }
+        // arrays are wrapped in a struct, see `find_array_types`
+        Statement::ArrayType { array_type, size, modifiers, .. } => {
+            Ok(format!("boulder_array_{}_{}{}", type_tag(array_type)?, array_size(size)?, "*".repeat(modifiers.len())))
+        }
+        Statement::Void => Ok("void".to_string()),
+        _ => Err(CompilerError::new("Expected type expression but AST provided an illegal expression."))
+    }
+}
+
+/// declares `name` with a type, e.g. `uint8_t* buf`
+fn c_declaration(type_stmt: &Statement, name: &str) -> Result<String, CompilerError> {
+    Ok(format!("{} {}", c_type(type_stmt)?, name))
+}
+
 impl Statement {
-    fn gen_c_code(&mut self) -> Result<String, CompilerError> {
+    /// generates the C for a statement. Errors that don't know where they came from yet are given the
+    /// statement's span, so they point at the innermost statement that failed
+    fn gen_c_code(&mut self, options: &CodegenOptions) -> Result<String, CompilerError> {
+        let span = self.span().cloned();
+        self.gen_c_statement(options).map_err(|e| e.at(span.as_ref()))
+    }
+
+    #[allow(clippy::useless_format)]
+    fn gen_c_statement(&mut self, options: &CodegenOptions) -> Result<String, CompilerError> {
         match self {
-            Statement::Program { exprs } => {
-                let mut code = String::from("#include \"stdio.h\"\n");
+            Statement::Program { exprs, .. } => {
+                let mut code = String::from("#include \"stdio.h\"\n#include <stdint.h>\n#include <stdbool.h>\n\
+                                             #include <stdarg.h>\n#include <stdlib.h>\n");
+                // C needs types defined before they are used, so they all go first
+                let mut structs = Vec::new();
+                let mut enums = Vec::new();
+                find_types(exprs, &mut structs, &mut enums);
+                for mut e in enums {
+                    code.push_str(&e.gen_c_code(options)?);
+                    code.push('\n');
+                }
+                // arrays are wrapped in structs, which are defined alongside the structs that hold them
+                let mut types = Vec::new();
+                for expr in exprs.iter() {
+                    find_array_types(expr, &mut types)?;
+                }
+                for mut s in structs {
+                    let (name, holds) = match &s {
+                        Statement::Struct { ident, fields, .. } => {
+                            let mut holds = Vec::new();
+                            for f in fields {
+                                if let Statement::Declaration { type_ident: Some(t), .. } = f {
+                                    holds.extend(held_type(t)?);
+                                }
+                            }
+                            (ident.clone().gen_c_code(options)?, holds)
+                        }
+                        _ => continue
+                    };
+                    types.push(TypeDefinition { name, holds, code: s.gen_c_code(options)? });
+                }
+                for t in order_types(types) {
+                    code.push_str(&t.code);
+                    code.push('\n');
+                }
+                let mut defined = String::new();
+                globals(exprs, &mut defined, options)?;
+                if !defined.is_empty() {
+                    code.push_str(&defined);
+                    code.push('\n');
+                }
+                // and functions can be called before they are defined
+                let mut prototypes = String::new();
+                fn_prototypes(exprs, &mut prototypes, options)?;
+                if !prototypes.is_empty() {
+                    code.push_str(&prototypes);
+                    code.push('\n');
+                }
+                code.push_str(&panic_runtime(has_function(exprs, "panic_hook")));
+                code.push('\n');
+                if !options.release {
+                    code.push_str(&index_runtime());
+                    code.push('\n');
+                }
                 for expr in exprs {
-                    code.push_str(&expr.gen_c_code()?);
+                    if matches!(expr, Statement::Struct { .. } | Statement::Enum { .. } | Statement::Const { .. } |
+                                      Statement::Static { .. }) {
+                        continue;
+                    }
+                    code.push_str(&expr.gen_c_code(options)?);
                     code.push('\n');
                 }
                 Ok(code)
             }
-            Statement::Block { exprs } => {
-                let mut code = String::new();
+            Statement::Block { exprs, .. } => {
+                let mut code = String::from("{\n");
                 for expr in exprs {
-                    code.push_str(&expr.gen_c_code()?);
-                    code.push(';');
+                    match expr {
+                        // a match on its own is a statement, anywhere else it is a value
+                        Statement::Match { value, arms, value_type, .. } => {
+                            code.push_str(&c_match_statement(value, arms, value_type, options)?);
+                        }
+                        _ => {
+                            code.push_str(&expr.gen_c_code(options)?);
+                            code.push(';');
+                        }
+                    }
                     code.push('\n');
                 }
+                code.push('}');
                 Ok(code)
             }
-            Statement::Use { exprs } => {
+            Statement::Use { exprs, .. } => {
                 let mut code = String::new();
                 for expr in exprs {
-                    code.push_str(&expr.gen_c_code()?);
+                    if matches!(expr, Statement::Struct { .. } | Statement::Enum { .. } | Statement::Const { .. } |
+                                      Statement::Static { .. }) {
+                        continue;
+                    }
+                    code.push_str(&expr.gen_c_code(options)?);
                     code.push('\n');
                 }
                 Ok(code)
             }
-            Statement::Fn { ident, params, return_type, body } => {
+            Statement::Fn { ident, params, return_type, body, .. } => {
                 let name = match ident.as_ref().clone() {
-                    Statement::Identifier { ident } => {
+                    Statement::Identifier { ident, .. } => {
                         ident
                     }
                     _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
                 };
 
-                let (type_name, type_mods) = match return_type.as_ref().clone() {
-                    Statement::Type { modifiers, type_ident } => {
-                        let mut mods = Vec::new();
-                        for m in modifiers {
-                            let c = m.clone().gen_c_code()?;
-                            mods.push(c);
-                        }
+                let header = fn_header(&name, params, return_type, options)?;
+                let body = body.gen_c_code(options)?;
 
-                        let type_name = match type_ident.as_ref().clone() {
-                            Statement::Identifier { ident } => {
-                                ident
-                            }
-                            _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
-                        };
-
-                        (type_name, mods)
-                    }
-                    Statement::Void => {
-                        ("void".to_string(), Vec::new())
+                Ok(format!("{} {}\n", header, body))
+            }
+            Statement::Impl { ident, methods, .. } => {
+                let type_name = ident.gen_c_code(options)?;
+                let mut code = String::new();
+                for m in methods {
+                    if let Statement::Fn { ident, params, return_type, body, .. } = m {
+                        let name = mangle_method(&type_name, &ident.gen_c_code(options)?);
+                        let header = fn_header(&name, params, return_type, options)?;
+                        code.push_str(&format!("{} {}\n", header, body.gen_c_code(options)?));
                     }
-                    _ => return Err(CompilerError::new("Expected type expression but AST provided an illegal expression."))
+                }
+                Ok(code)
+            }
+            Statement::MethodCall { receiver, method, params, receiver_type, .. } => {
+                let (type_name, is_pointer) = match receiver_type.as_deref() {
+                    Some(Statement::Type { type_ident, modifiers, .. }) => match type_ident.as_ref() {
+                        Statement::Identifier { ident, .. } => (ident.clone(), !modifiers.is_empty()),
+                        _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
+                    },
+                    _ => return Err(CompilerError::new("The type of a method call's receiver was never checked."))
                 };
-
-                let rt = format!("{}{}", type_name, type_mods.join(""));
-
-                let mut c_params = Vec::new();
+                let receiver = receiver.gen_c_code(options)?;
+                // methods take a pointer to the value they are called on
+                let mut c_params = vec![if is_pointer { receiver } else { format!("&{}", receiver) }];
                 for p in params {
-                    if let Statement::Declaration {
-                        value, ..
-                    } = p {
-                        if value.is_some() {
-                            // todo: handle default values here
-                        }
-                    }
-                    c_params.push(p.gen_c_code()?);
+                    c_params.push(p.gen_c_code(options)?);
                 }
-                let c_params_str = c_params.join(", ");
-
-                let body = body.gen_c_code()?;
-
-                Ok(format!("{} {}({}) {{\n{}}}\n", rt, name, c_params_str, body))
+                Ok(format!("{}({})", mangle_method(&type_name, &method.gen_c_code(options)?), c_params.join(", ")))
             }
-            Statement::FnCall { ident, params } => {
+            Statement::FnCall { ident, params, .. } => {
                 let name = match ident.as_ref().clone() {
-                    Statement::Identifier { ident } => {
+                    Statement::Identifier { ident, .. } => {
                         ident
                     }
                     _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
@@ -96,126 +828,284 @@ impl Statement {
 
                 let mut c_params = Vec::new();
                 for p in params {
-                    c_params.push(p.gen_c_code()?);
+                    c_params.push(p.gen_c_code(options)?);
                 }
                 let c_params_str = c_params.join(", ");
 
                 Ok(format!("{}({})", name, c_params_str))
             }
-            Statement::If { condition, body, else_statement } => {
-                let cond = condition.gen_c_code()?;
-                let body = body.gen_c_code()?;
+            Statement::If { condition, body, else_statement, .. } => {
+                let cond = condition.gen_c_code(options)?;
+                let body = body.gen_c_code(options)?;
                 Ok(if else_statement.is_some() {
-                    let else_body = else_statement.as_mut().unwrap().gen_c_code()?;
+                    let else_body = else_statement.as_mut().unwrap().gen_c_code(options)?;
                     format!("if ({}) {} else {}", cond, body, else_body)
                 } else {
                     format!("if ({}) {}", cond, body)
                 })
             }
-            Statement::Return { value } => {
-                let val = value.gen_c_code()?;
+            Statement::Return { value, .. } => {
+                if matches!(value.as_ref(), Statement::Void) {
+                    return Ok("return".to_string());
+                }
+                if let matched @ Statement::Match { value: matched_value, arms, span, .. } = value.as_ref() {
+                    if !conditional_match(matched_value, arms) {
+                        let give = |value| Statement::Return { value: Box::new(value), span: span.clone() };
+                        return c_match_value(matched, &give, options);
+                    }
+                }
+                let val = value.gen_c_code(options)?;
                 Ok(format!("return {}", val))
             }
-            Statement::Panic { .. } => {
-                todo!()
+            Statement::Panic { value, value_type, span, .. } => {
+                let location = c_format_text(&span.to_string());
+                Ok(match c_panic_value(value, value_type, options)? {
+                    Some((format, value)) => format!("boulder_panic(\"panicked at {}: {}\", {})", location, format, value),
+                    None => format!("boulder_panic(\"panicked at {}\")", location),
+                })
             }
-            Statement::Assert { .. } => {
-                todo!()
+            Statement::Assert { expr, span, .. } => {
+                if options.release {
+                    return Ok(String::new());
+                }
+                Ok(format!("if (!{}) boulder_panic(\"assertion failed at {}\")", expr.gen_c_code(options)?,
+                           c_format_text(&span.to_string())))
             }
-            Statement::Declaration { ident, type_ident, value } => {
+            Statement::Declaration { ident, type_ident, value, .. } => {
                 let ident = match ident.as_ref().clone() {
-                    Statement::Identifier { ident } => {
+                    Statement::Identifier { ident, .. } => {
                         ident
                     }
                     _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
                 };
 
-                let type_ident = type_ident.as_mut().unwrap().gen_c_code()?;
+                let type_ident = match type_ident {
+                    Some(t) => t,
+                    None => return Err(CompilerError::new(format!("The type of `{}` was never inferred.", ident)))
+                };
+                let declaration = c_declaration(type_ident, &ident)?;
 
+                if let Some(looped @ Statement::Loop { .. }) = value.as_deref() {
+                    let target = Statement::Identifier { ident, span: looped.span().cloned().unwrap_or_default() };
+                    return Ok(format!("{};\n{}", declaration, c_loop_value(looped, &target, options)?));
+                }
+                if let Some(matched @ Statement::Match { value, arms, span, .. }) = value.as_deref() {
+                    if !conditional_match(value, arms) {
+                        let target = Statement::Identifier { ident, span: span.clone() };
+                        let give = |value| Statement::Assignment {
+                            ident: Box::new(target.clone()), value: Box::new(value), span: span.clone()
+                        };
+                        return Ok(format!("{};\n{}", declaration, c_match_value(matched, &give, options)?));
+                    }
+                }
                 Ok(if value.is_some() {
-                    let val = value.as_mut().unwrap().gen_c_code()?;
-                    format!("{} {} = {}", type_ident, ident, val)
+                    let val = c_initializer(value.as_mut().unwrap(), options)?;
+                    format!("{} = {}", declaration, val)
                 } else {
-                    format!("{} {}", type_ident, ident)
+                    declaration
                 })
             }
-            Statement::Assignment { ident, value } => {
-                let ident = match ident.as_ref().clone() {
-                    Statement::Identifier { ident } => {
-                        ident
-                    }
+            Statement::Struct { ident, fields, .. } => {
+                let name = match ident.as_ref() {
+                    Statement::Identifier { ident, .. } => ident.clone(),
                     _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
                 };
-
-                let val = value.gen_c_code()?;
-                Ok(format!("{} = {}", ident, val))
+                let mut code = format!("typedef struct {} {{\n", name);
+                for f in fields {
+                    code.push_str(&f.gen_c_code(options)?);
+                    code.push_str(";\n");
+                }
+                code.push_str(&format!("}} {};\n", name));
+                Ok(code)
             }
-            Statement::Postfix { postfix } => {
-                let mut c_code = String::new();
-                let mut operand_stack: Vec<ShuntedStackItem> = Vec::new();
-
-                // values for verifying the types of operators allowed
-                let mut boolean_mode = false;
-                let mut algebra_mode = false;
-
-                for ssi in postfix {
-                    if ssi.is_operand() {
-                        operand_stack.push(ssi);
-                    } else {
-                        let op = ssi.get_operator().unwrap();
-                        if op.precedence().is_none() {
-                            // todo(eric): handle '++' and '--'
-                            return Err(CompilerError::new(format!("Expected expression operator but found invalid operator `{}`.",
-                                        op)));
+            Statement::Interrupt { vector, .. } => c_interrupt(vector, options.target),
+            Statement::Enum { ident, variants, .. } => {
+                let name = ident.gen_c_code(options)?;
+                let mut code = format!("typedef enum {} {{\n", name);
+                for v in variants {
+                    if let Statement::Declaration { ident, value, .. } = v {
+                        let variant = format!("{}__{}", name, ident.gen_c_code(options)?);
+                        match value {
+                            Some(value) => code.push_str(&format!("{} = {},\n", variant, value.gen_c_code(options)?)),
+                            None => code.push_str(&format!("{},\n", variant)),
                         }
-                        if !boolean_mode && !algebra_mode {
-                            if op.is_boolean() {
-                                boolean_mode = true;
-                            } else {
-                                algebra_mode = true;
+                    }
+                }
+                code.push_str(&format!("}} {};\n", name));
+                Ok(code)
+            }
+            Statement::Path { base, member, .. } => {
+                Ok(format!("{}__{}", base.gen_c_code(options)?, member.gen_c_code(options)?))
+            }
+            Statement::Match { value, arms, .. } => {
+                // C has no expression that can hold statements, so a match used as a value becomes
+                // nested conditionals. Declarations, assignments and returns lower any other match to a
+                // statement instead, see `c_match_value`
+                if !conditional_match(value, arms) {
+                    return Err(CompilerError::new("A match used as a value can only match a variable or field, and \
+                                                   have no blocks as arms, unless it is declared, assigned or returned in C."));
+                }
+                let value = value.gen_c_code(options)?;
+                let mut branches = Vec::new();
+                let mut otherwise = None;
+                for arm in arms {
+                    if let Statement::MatchArm { pattern, body, .. } = arm {
+                        match pattern_condition(pattern, &value, options)? {
+                            Some(condition) => branches.push((condition, body.gen_c_code(options)?)),
+                            None => {
+                                otherwise = Some(body.gen_c_code(options)?);
+                                break;
                             }
                         }
-
-                        // todo(eric): handle operators
                     }
                 }
-                
-                Ok(c_code)
+                // the match is exhaustive, so the last arm doesn't need to be checked
+                let mut code = match otherwise {
+                    Some(code) => code,
+                    None => branches.pop().map(|(_, body)| body)
+                        .ok_or_else(|| CompilerError::new("A match used as a value needs at least one arm."))?
+                };
+                for (condition, body) in branches.into_iter().rev() {
+                    code = format!("({} ? {} : {})", condition, body, code);
+                }
+                Ok(code)
             }
-            Statement::PropertyAccess { expr, property } => {
-                let expr = expr.gen_c_code()?;
-                let property = property.gen_c_code()?;
-                Ok(format!("{}.{}", expr, property))
+            Statement::StructLiteral { ident, .. } => {
+                let name = ident.gen_c_code(options)?;
+                // a compound literal, which can be used anywhere an expression can
+                Ok(format!("(({}){})", name, c_initializer(self, options)?))
             }
-            Statement::ArrayAccess { ident, index } => {
-                let ident = match ident.as_ref().clone() {
-                    Statement::Identifier { ident } => {
-                        ident
+            Statement::Assignment { ident, value, .. } => {
+                if matches!(value.as_ref(), Statement::Loop { .. }) {
+                    return c_loop_value(value, ident, options);
+                }
+                if let matched @ Statement::Match { value, arms, span, .. } = value.as_ref() {
+                    if !conditional_match(value, arms) {
+                        let give = |value| Statement::Assignment {
+                            ident: ident.clone(), value: Box::new(value), span: span.clone()
+                        };
+                        return c_match_value(matched, &give, options);
                     }
-                    _ => return Err(CompilerError::new("Expected identifier expression but AST provided an illegal expression."))
-                };
+                }
+                let ident = ident.gen_c_code(options)?;
 
-                let index = index.gen_c_code()?;
-                Ok(format!("{}[{}]", ident, index))
+                let val = value.gen_c_code(options)?;
+                Ok(format!("{} = {}", ident, val))
+            }
+            Statement::CompoundAssignment { op, ident, value, value_type, .. } => {
+                let ident = ident.gen_c_code(options)?;
+                let val = value.gen_c_code(options)?;
+                Ok(match (*op, operand_type(value_type.as_deref())) {
+                    // two u16s are multiplied as ints, which can overflow, so they are multiplied unsigned
+                    (Operator::Mul, Some(Type::U16)) => format!("{} *= (uint32_t){}", ident, val),
+                    _ => format!("{} {}= {}", ident, op.gen_c_code()?, val)
+                })
+            }
+            Statement::Binary { op, left, right, value_type, .. } => {
+                // every operation is wrapped in parentheses, so C evaluates it in the same order as the tree
+                let left = left.gen_c_code(options)?;
+                let right = right.gen_c_code(options)?;
+                let operand = operand_type(value_type.as_deref());
+                let expr = match (*op, &operand) {
+                    // C has no <<< operator, and shifting left is the same for signed and unsigned numbers
+                    (Operator::Shlu, _) => format!("({} << {})", left, right),
+                    // >> on an unsigned value is a logical shift in C, done in the width of the value
+                    (Operator::Shru, Some(t)) => format!("(({})(({})({}) >> {}))", c_type_name(&t.to_string()),
+                                                         c_type_name(&t.unsigned().to_string()), left, right),
+                    (Operator::Shru, None) => format!("((uint64_t)({}) >> {})", left, right),
+                    // two u16s are multiplied as ints, which can overflow, so they are multiplied unsigned
+                    (Operator::Mul, Some(Type::U16)) => format!("((uint32_t){} * {})", left, right),
+                    _ => format!("({} {} {})", left, op.gen_c_code()?, right),
+                };
+                Ok(if op.is_arithmetic() { c_wrap(&operand, expr) } else { expr })
             }
-            Statement::Type { type_ident, modifiers } => {
-                let mut code = type_ident.gen_c_code()?;
-                for m in modifiers {
-                    code.push_str(m.gen_c_code()?.as_str());
+            Statement::Unary { op, expr, postfix, value_type, .. } => {
+                let value = expr.gen_c_code(options)?;
+                if *postfix {
+                    return Ok(format!("({}{})", value, op.as_raw()));
                 }
-                Ok(code)
+                let operand = operand_type(value_type.as_deref());
+                // references are pointers in C, so `&` and `*` are the same as they are in boulder
+                let c_op = match op {
+                    // `!` flips the bits of an integer, which is `~` in C
+                    Operator::Not if operand.as_ref().is_some_and(Type::is_integer) => "~".to_string(),
+                    Operator::Not | Operator::Sub | Operator::And | Operator::Mul | Operator::Inc | Operator::Dec => op.as_raw(),
+                    _ => return Err(CompilerError::new(format!("`{}` can not be used as a unary operator.", op)))
+                };
+                let expr = format!("({}{})", c_op, value);
+                Ok(if matches!(op, Operator::Not | Operator::Sub) { c_wrap(&operand, expr) } else { expr })
+            }
+            Statement::Cast { expr, cast_type, .. } => {
+                Ok(format!("(({}){})", c_type(cast_type)?, expr.gen_c_code(options)?))
+            }
+            Statement::PropertyAccess { expr, property, through_pointer, .. } => {
+                let expr = expr.gen_c_code(options)?;
+                let property = property.gen_c_code(options)?;
+                Ok(format!("{}{}{}", expr, if *through_pointer { "->" } else { "." }, property))
+            }
+            Statement::ArrayAccess { ident, index, length, through_pointer, span } => {
+                let ident = ident.gen_c_code(options)?;
+                let index = index.gen_c_code(options)?;
+                // arrays are wrapped in a struct, pointers to elements are indexed as they are
+                let ident = match length {
+                    Some(_) => format!("{}{}items", ident, if *through_pointer { "->" } else { "." }),
+                    None => ident
+                };
+                // debug builds check indexes into arrays, pointers have no length to check against
+                Ok(match length {
+                    Some(length) if !options.release => format!("{}[boulder_index({}, {}, \"{}\")]", ident, index, length,
+                                                                c_format_text(&span.to_string())),
+                    _ => format!("{}[{}]", ident, index)
+                })
+            }
+            Statement::ArrayLiteral { .. } | Statement::ArrayRepeat { .. } => {
+                Err(CompilerError::new("Array literals can only be used to declare a variable in C."))
+            }
+            Statement::While { condition, body, label, .. } => {
+                let header = format!("while ({})", condition.gen_c_code(options)?);
+                Ok(c_loop(header, c_arm_body(body, options)?, label))
             }
-            Statement::Identifier { ident } => {
+            Statement::Loop { body, label, .. } => Ok(c_loop("while (1)".to_string(), c_arm_body(body, options)?, label)),
+            Statement::For { .. } => c_for(self, options),
+            Statement::Break { label, value, .. } => {
+                if value.is_some() {
+                    return Err(CompilerError::new("A loop can only give a value in C when it is assigned to a variable."));
+                }
+                Ok(match c_label(label) {
+                    Some(name) => format!("goto {}_break", name),
+                    None => "break".to_string()
+                })
+            }
+            Statement::Continue { label, .. } => Ok(match c_label(label) {
+                Some(name) => format!("goto {}_continue", name),
+                None => "continue".to_string()
+            }),
+            Statement::Type { .. } | Statement::ArrayType { .. } => c_type(self),
+            Statement::Identifier { ident, .. } => {
                 Ok(ident.clone())
             }
             Statement::Void => {
                 Ok("void".to_string())
             }
-            Statement::NumberLiteral { value } => {
+            Statement::NumberLiteral { value, .. } => {
+                Ok(c_number(value))
+            }
+            Statement::FloatLiteral { value, .. } => {
+                // C float literals are doubles unless they are given the `f` suffix
+                Ok(if value.single { format!("{}f", value) } else { value.to_string() })
+            }
+            Statement::BoolLiteral { value, .. } => {
                 Ok(value.to_string())
             }
-            Statement::StringLiteral { value } => {
-                Ok(format!("\"{}\"", value))
+            Statement::CharLiteral { value, .. } => {
+                // a C char is a single byte
+                if !value.is_ascii() {
+                    return Err(CompilerError::new(format!("'{}' does not fit in a C char.", value)));
+                }
+                Ok(format!("'{}'", c_escape(*value, '\'')))
+            }
+            Statement::StringLiteral { value, .. } => {
+                Ok(c_string(value))
             }
             _ => {
                 // todo(eric): implement all other patterns to convert to c
@@ -225,6 +1115,6 @@ impl Statement {
     }
 }
 
-pub fn generate_c_code(ast: &mut Statement) -> Result<String, CompilerError> {
-    ast.gen_c_code()
+pub fn generate_c_code(ast: &mut Statement, options: &CodegenOptions) -> Result<String, CompilerError> {
+    ast.gen_c_code(options)
 }
\ No newline at end of file
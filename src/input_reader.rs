@@ -1,12 +1,16 @@
 use crate::CodePos;
 
+/// A cursor over the source code. Nothing is removed from the source as it is read, the cursor
+/// just moves forward, so reading a file takes time proportional to its length.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InputReader {
-    stream: Vec<char>,
+    source: String,
+    /// the position of the next char, including its byte offset into the source
     pos: CodePos,
 }
 
 impl InputReader {
+    #[allow(clippy::unnecessary_unwrap)]
     pub fn new<S: Into<String>>(file: Option<String>, input: S) -> Self {
         let pos = if file.is_some() {
             CodePos::new(file.unwrap(), 1, 1)
@@ -14,7 +18,7 @@ impl InputReader {
             CodePos::default()
         };
         Self {
-            stream: input.into().chars().collect(),
+            source: input.into(),
             pos,
         }
     }
@@ -23,27 +27,42 @@ impl InputReader {
         self.pos.clone()
     }
 
-    pub fn consume(&mut self) -> Option<char> {
-        if self.stream.is_empty() {
-            return None;
-        }
+    /// the byte offset of the next char in the source
+    pub fn offset(&self) -> usize {
+        self.pos.offset
+    }
 
-        let ch = self.stream.remove(0);
-        self.pos.next();
+    /// the source that has not been read yet
+    fn rest(&self) -> &str {
+        &self.source[self.pos.offset..]
+    }
+
+    pub fn consume(&mut self) -> Option<char> {
+        let ch = self.rest().chars().next()?;
+        self.pos.offset += ch.len_utf8();
 
         if ch == '\n' {
             self.pos.newline();
+        } else {
+            self.pos.next();
         }
 
         Some(ch)
     }
 
+    /// consumes chars as long as `keep` returns true for them
+    pub fn consume_while<F: Fn(char) -> bool>(&mut self, keep: F) {
+        while self.peek().is_some_and(&keep) {
+            self.consume();
+        }
+    }
+
     pub fn peek_at(&self, n: usize) -> Option<char> {
-        self.stream.get(n).cloned()
+        self.rest().chars().nth(n)
     }
 
     pub fn peek(&self) -> Option<char> {
-        self.peek_at(0)
+        self.rest().chars().next()
     }
 
-}
\ No newline at end of file
+}
@@ -1,68 +1,1472 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
-use std::intrinsics::unreachable;
 use better_term::{Color, Style};
 
-use crate::statement::Statement;
+use crate::error::{print_error, Error};
+use crate::operator::Operator;
+use crate::Span;
+use crate::statement::{Float, Statement};
+use crate::types::{literal_value, Type};
 
+/// the deepest the call stack can go before the interpreter gives up
+const MAX_CALL_DEPTH: usize = 512;
+/// the most elements an array can have in the interpreter, so a huge array is an error instead of
+/// using up the host's memory
+const MAX_ARRAY_LENGTH: usize = 1 << 28;
+/// where the interpreter's allocations start, far above the low addresses devices are usually mapped at
+const MEMORY_START: u64 = 0x7f00_0000_0000;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RuntimeError {
     pub msg: String,
+    /// the functions that were running when the error happened, innermost first
+    pub trace: Vec<String>,
+    /// the innermost statement that was running when the error happened
+    pub span: Option<Span>,
 }
 
 impl RuntimeError {
     pub fn new<S: Into<String>>(msg: S) -> RuntimeError {
-        RuntimeError { msg: msg.into() }
+        RuntimeError { msg: msg.into(), trace: Vec::new(), span: None }
+    }
+
+    /// gives the error a span if it doesn't have one yet, so the innermost statement is reported
+    fn at(mut self, span: Option<&Span>) -> RuntimeError {
+        if self.span.is_none() {
+            self.span = span.cloned();
+        }
+        self
+    }
+}
+
+/// prints a runtime error with the code that caused it when it is known, followed by the stack trace
+pub fn print_runtime_error(err: RuntimeError) {
+    let span = match &err.span {
+        Some(span) => span.clone(),
+        None => {
+            println!("{}", err);
+            return;
+        }
+    };
+    print_error(Error::new_singular(err.msg, span));
+    if !err.trace.is_empty() {
+        print!("{}stack trace:", Color::BrightBlack);
+        for (i, function) in err.trace.iter().enumerate() {
+            print!("\n  {}: {}", i, function);
+        }
+        println!("{}", Style::reset());
     }
 }
 
 impl Display for RuntimeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}error{}: {}{}",
-                   Style::default().fg(Color::BrightRed).bold(), Color::BrightWhite, self.msg, Style::reset())
+                   Style::default().fg(Color::BrightRed).bold(), Color::BrightWhite, self.msg, Style::reset())?;
+        if !self.trace.is_empty() {
+            write!(f, "\n{}stack trace:", Color::BrightBlack)?;
+            for (i, function) in self.trace.iter().enumerate() {
+                write!(f, "\n  {}: {}", i, function)?;
+            }
+            write!(f, "{}", Style::reset())?;
+        }
+        Ok(())
     }
 }
 
-pub fn interpret(ast: &Statement) -> Result<(), RuntimeError> {
+/// A value that exists while the program is running
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    String(String),
+    Range { start: i64, end: i64, inclusive: bool },
+    /// a struct's fields in the order they were declared. structs are copied when assigned or passed
+    Struct { name: String, fields: Vec<(String, Value)> },
+    /// a variant of an enum along with its discriminant
+    Enum { name: String, variant: String, value: i64 },
+    /// the elements of a fixed size array, which is copied when assigned or passed like a struct
+    Array(Vec<Value>),
+    /// an address in the interpreter's memory and the type of the value there. References are pointers too
+    Pointer { address: u64, pointee: Type },
+    Void,
+}
 
-    let mut functions: HashMap<String, Statement> = HashMap::new();
-    let mut variables: HashMap<String, Statement> = HashMap::new();
+impl Value {
+    /// the name of the value's type, used for error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Integer(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "bool",
+            Value::Char(_) => "char",
+            Value::String(_) => "string",
+            Value::Range { .. } => "range",
+            Value::Struct { .. } => "struct",
+            Value::Enum { .. } => "enum",
+            Value::Array(_) => "array",
+            Value::Pointer { .. } => "pointer",
+            Value::Void => "void",
+        }
+    }
 
-    let mut start_function: Option<Statement> = None;
+    fn as_integer(&self) -> Result<i64, RuntimeError> {
+        match self {
+            Value::Integer(i) => Ok(*i),
+            _ => Err(RuntimeError::new(format!("Expected an integer but found a {}", self.type_name())))
+        }
+    }
 
-    if let Statement::Program { exprs } = ast {
-        for expr in exprs {
-            match expr {
-                Statement::Fn { ident, return_type, params, body } => {
+    /// finds a field of a struct value
+    fn field(&self, field: &str) -> Result<&Value, RuntimeError> {
+        self.field_index(field).map(|i| match self {
+            Value::Struct { fields, .. } => &fields[i].1,
+            _ => unreachable!()
+        })
+    }
+
+    fn field_index(&self, field: &str) -> Result<usize, RuntimeError> {
+        match self {
+            Value::Struct { name, fields } => fields.iter().position(|(n, _)| n == field)
+                .ok_or_else(|| RuntimeError::new(format!("`{}` has no field named `{}`", name, field))),
+            _ => Err(RuntimeError::new(format!("Expected a struct but found a {}", self.type_name())))
+        }
+    }
 
-                    let name = match *ident.clone() {
-                        Statement::Identifier { ident } => ident,
-                        _ => unreachable!()
-                    };
+    fn as_bool(&self) -> Result<bool, RuntimeError> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(RuntimeError::new(format!("Expected a bool but found a {}", self.type_name())))
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Float(v) => write!(f, "{:?}", v),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Range { start, end, inclusive } => {
+                write!(f, "{}{}{}", start, if *inclusive { "..=" } else { ".." }, end)
+            }
+            Value::Struct { name, fields } => {
+                let fields: Vec<String> = fields.iter().map(|(n, v)| format!("{}: {}", n, v)).collect();
+                write!(f, "{} {{ {} }}", name, fields.join(", "))
+            }
+            Value::Enum { name, variant, .. } => write!(f, "{}::{}", name, variant),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(|v| v.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Pointer { address, .. } => write!(f, "{:#x}", address),
+            Value::Void => write!(f, "void"),
+        }
+    }
+}
 
-                    if name.as_str() == "start" {
-                        start_function = Some(expr.clone());
-                    } else {
-                        functions.insert(name, expr.clone());
+/// How control leaves a statement
+#[derive(Debug, Clone, PartialEq)]
+enum Flow {
+    Normal(Value),
+    /// leaves the loop with the label, or the innermost loop if there is none, giving it the value
+    Break(Option<String>, Value),
+    Continue(Option<String>),
+    Return(Value),
+}
+
+/// Where a variable's value is kept. A variable is moved into memory the first time its address is
+/// taken, so pointers to it see it change and it sees changes made through them
+#[derive(Debug, Clone, PartialEq)]
+enum Slot {
+    Value(Value),
+    Memory { address: u64, value_type: Type },
+}
+
+/// where an assignment stores its value
+enum Place {
+    Variable(String),
+    Memory(u64, Type),
+    /// a field or element of the struct or array in another place
+    Part(Box<Place>, usize),
+}
+
+/// The variables visible to the code currently running.
+/// Each block pushes a new scope which is dropped when the block ends.
+#[derive(Debug, Clone, PartialEq)]
+struct Environment {
+    scopes: Vec<HashMap<String, Slot>>,
+}
+
+impl Environment {
+    fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// declares a variable in the innermost scope, shadowing any variable with the same name
+    fn declare<S: Into<String>>(&mut self, name: S, value: Value) {
+        self.scopes.last_mut().unwrap().insert(name.into(), Slot::Value(value));
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.scopes.iter().any(|s| s.contains_key(name))
+    }
+
+    fn get(&self, name: &str) -> Option<&Slot> {
+        self.scopes.iter().rev().find_map(|s| s.get(name))
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Slot> {
+        self.scopes.iter_mut().rev().find_map(|s| s.get_mut(name))
+    }
+}
+
+/// The memory pointers point into. It is kept as bytes in little-endian order like the targets keep it,
+/// so a value can be read back through a pointer to any type, as it can in C. Addresses outside of every
+/// allocation, like a device's registers, hold whatever was last written to them
+struct Memory {
+    /// the bytes of every allocation, by the address it starts at. A byte is None until something is
+    /// written to it. Allocations last until the program ends
+    allocations: BTreeMap<u64, Vec<Option<u8>>>,
+    /// the bytes written to addresses outside of every allocation
+    unallocated: HashMap<u64, u8>,
+    /// the strings stored in memory, which are kept as their index here since they have no address
+    strings: Vec<String>,
+    /// where the next allocation can start
+    next: u64,
+}
+
+impl Memory {
+    fn new() -> Self {
+        Self {
+            allocations: BTreeMap::new(),
+            unallocated: HashMap::new(),
+            strings: Vec::new(),
+            next: MEMORY_START,
+        }
+    }
+
+    /// stores the bytes of a value in a new allocation aligned to `align`, returning its address
+    fn allocate(&mut self, mut bytes: Vec<Option<u8>>, align: u64) -> u64 {
+        let address = self.next.next_multiple_of(align);
+        // every allocation takes at least a byte, so no two of them start at the same address
+        if bytes.is_empty() {
+            bytes.push(None);
+        }
+        self.next = address + bytes.len() as u64;
+        self.allocations.insert(address, bytes);
+        address
+    }
+
+    /// the byte at an address, which is None if it is outside of every allocation and nothing has been
+    /// written to it, or Some(None) if it is inside of one but uninitialized
+    fn byte(&self, address: u64) -> Option<Option<u8>> {
+        match self.allocations.range(..=address).next_back() {
+            Some((start, bytes)) if address - start < bytes.len() as u64 => Some(bytes[(address - start) as usize]),
+            _ => self.unallocated.get(&address).map(|b| Some(*b))
+        }
+    }
+
+    fn read(&self, address: u64, size: u64) -> Result<Vec<u8>, RuntimeError> {
+        (0..size).map(|i| match self.byte(address.wrapping_add(i)) {
+            Some(Some(byte)) => Ok(byte),
+            Some(None) => Err(RuntimeError::new(format!("Read of an uninitialized value at {:#x}", address))),
+            None => Err(RuntimeError::new(format!("Read from {:#x}, which nothing has been written to", address)))
+        }).collect()
+    }
+
+    fn write(&mut self, address: u64, bytes: Vec<Option<u8>>) {
+        for (i, byte) in bytes.into_iter().enumerate() {
+            let address = address.wrapping_add(i as u64);
+            match self.allocations.range_mut(..=address).next_back() {
+                Some((start, stored)) if address - start < stored.len() as u64 => stored[(address - start) as usize] = byte,
+                _ => if let Some(byte) = byte {
+                    self.unallocated.insert(address, byte);
+                }
+            }
+        }
+    }
+
+    /// the index a string is kept at, adding it if it hasn't been stored before
+    fn string(&mut self, string: String) -> u64 {
+        let index = self.strings.iter().position(|s| *s == string).unwrap_or_else(|| {
+            self.strings.push(string);
+            self.strings.len() - 1
+        });
+        index as u64
+    }
+}
+
+/// looks up the types of a struct's fields, which decide where its fields are in memory
+fn struct_fields(field_types: &HashMap<String, Vec<Type>>) -> impl Fn(&str) -> Vec<Type> + '_ {
+    |name| field_types.get(name).cloned().unwrap_or_default()
+}
+
+/// the type a value has when the type checker hasn't said what it is. Integers are `int`s and floats `f64`s
+fn value_type_of(value: &Value) -> Type {
+    match value {
+        Value::Integer(_) => Type::Int,
+        Value::Float(_) => Type::F64,
+        Value::Bool(_) => Type::Bool,
+        Value::Char(_) => Type::Char,
+        Value::String(_) => Type::Str,
+        Value::Range { .. } => Type::Range(Box::new(Type::Int)),
+        Value::Struct { name, .. } => Type::Struct(name.clone()),
+        Value::Enum { name, .. } => Type::Enum(name.clone()),
+        Value::Array(items) => Type::Array(Box::new(items.first().map(value_type_of).unwrap_or(Type::Int)), items.len()),
+        Value::Pointer { pointee, .. } => Type::Pointer(Box::new(pointee.clone())),
+        Value::Void => Type::Void,
+    }
+}
+
+fn ident_name(ident: &Statement) -> Result<String, RuntimeError> {
+    match ident {
+        Statement::Identifier { ident, .. } => Ok(ident.clone()),
+        _ => Err(RuntimeError::new(format!("Expected an identifier but found {}", ident)))
+    }
+}
+
+fn float_value(number: &Float) -> Result<Value, RuntimeError> {
+    let parsed = number.value.parse::<f64>()
+        .map_err(|e| RuntimeError::new(format!("Invalid float literal `{}`: {}", number.value, e)))?;
+    let value = if number.negative { -parsed } else { parsed };
+    Ok(Value::Float(if number.single { value as f32 as f64 } else { value }))
+}
+
+/// applies a unary operator to a value
+fn unary_op(op: Operator, value: Value) -> Result<Value, RuntimeError> {
+    match (op, value) {
+        (Operator::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+        (Operator::Not, Value::Integer(i)) => Ok(Value::Integer(!i)),
+        (Operator::Sub, Value::Integer(i)) => Ok(Value::Integer(i.wrapping_neg())),
+        (Operator::Sub, Value::Float(v)) => Ok(Value::Float(-v)),
+        (op, value) => Err(RuntimeError::new(format!("Operator {} can not be applied to a {}", op, value.type_name())))
+    }
+}
+
+/// converts a value to another number type, or between pointers and integers. Integers wrap to fit
+/// in smaller types, like they do in C
+fn cast(value: Value, target: &Type) -> Result<Value, RuntimeError> {
+    if let Type::Pointer(pointee) = target {
+        let address = match value {
+            Value::Pointer { address, .. } => address,
+            Value::Integer(i) => i as u64,
+            value => return Err(RuntimeError::new(format!("A {} can not be cast to `{}`", value.type_name(), target)))
+        };
+        return Ok(Value::Pointer { address, pointee: *pointee.clone() });
+    }
+    let value = match value {
+        Value::Integer(i) => i,
+        Value::Pointer { address, .. } => address as i64,
+        Value::Char(c) => c as i64,
+        Value::Bool(b) => b as i64,
+        Value::Float(v) => match target {
+            Type::F32 => return Ok(Value::Float(v as f32 as f64)),
+            Type::F64 => return Ok(Value::Float(v)),
+            _ => v as i64
+        },
+        value => return Err(RuntimeError::new(format!("A {} can not be cast to `{}`", value.type_name(), target)))
+    };
+    Ok(match target {
+        Type::U8 => Value::Integer(value as u8 as i64),
+        Type::U16 => Value::Integer(value as u16 as i64),
+        Type::U32 => Value::Integer(value as u32 as i64),
+        Type::I8 => Value::Integer(value as i8 as i64),
+        Type::I16 => Value::Integer(value as i16 as i64),
+        Type::I32 | Type::Int => Value::Integer(value as i32 as i64),
+        Type::F32 => Value::Float(value as f32 as f64),
+        Type::F64 => Value::Float(value as f64),
+        Type::Char => Value::Char(value as u8 as char),
+        _ => Value::Integer(value)
+    })
+}
+
+/// applies a binary operator to two values
+fn binary_op(op: Operator, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    match op {
+        Operator::Eq => return Ok(Value::Bool(left == right)),
+        Operator::Neq => return Ok(Value::Bool(left != right)),
+        Operator::BoolAnd => return Ok(Value::Bool(left.as_bool()? && right.as_bool()?)),
+        Operator::BoolOr => return Ok(Value::Bool(left.as_bool()? || right.as_bool()?)),
+        Operator::Range => return Ok(Value::Range { start: left.as_integer()?, end: right.as_integer()?, inclusive: false }),
+        Operator::IRange => return Ok(Value::Range { start: left.as_integer()?, end: right.as_integer()?, inclusive: true }),
+        _ => {}
+    }
+
+    if let (Value::Char(l), Value::Char(r)) = (&left, &right) {
+        return match op {
+            Operator::Lt => Ok(Value::Bool(l < r)),
+            Operator::Lte => Ok(Value::Bool(l <= r)),
+            Operator::Gt => Ok(Value::Bool(l > r)),
+            Operator::Gte => Ok(Value::Bool(l >= r)),
+            _ => Err(RuntimeError::new(format!("Operator {} can not be applied to chars", op)))
+        };
+    }
+
+    // floats follow IEEE 754, so dividing by zero gives infinity or NaN instead of an error
+    if let (Value::Float(l), Value::Float(r)) = (&left, &right) {
+        return match op {
+            Operator::Add => Ok(Value::Float(l + r)),
+            Operator::Sub => Ok(Value::Float(l - r)),
+            Operator::Mul => Ok(Value::Float(l * r)),
+            Operator::Div => Ok(Value::Float(l / r)),
+            Operator::Lt => Ok(Value::Bool(l < r)),
+            Operator::Lte => Ok(Value::Bool(l <= r)),
+            Operator::Gt => Ok(Value::Bool(l > r)),
+            Operator::Gte => Ok(Value::Bool(l >= r)),
+            _ => Err(RuntimeError::new(format!("Operator {} can not be applied to floats", op)))
+        };
+    }
+
+    if let (Value::Bool(l), Value::Bool(r)) = (&left, &right) {
+        return match op {
+            Operator::And => Ok(Value::Bool(l & r)),
+            Operator::Or => Ok(Value::Bool(l | r)),
+            Operator::Xor => Ok(Value::Bool(l ^ r)),
+            _ => Err(RuntimeError::new(format!("Operator {} can not be applied to bools", op)))
+        };
+    }
+
+    let l = left.as_integer()?;
+    let r = right.as_integer()?;
+    let result = match op {
+        Operator::Add => Value::Integer(l.wrapping_add(r)),
+        Operator::Sub => Value::Integer(l.wrapping_sub(r)),
+        Operator::Mul => Value::Integer(l.wrapping_mul(r)),
+        Operator::Div | Operator::Mod if r == 0 => {
+            return Err(RuntimeError::new("Attempted to divide by zero"));
+        }
+        Operator::Div => Value::Integer(l.wrapping_div(r)),
+        Operator::Mod => Value::Integer(l.wrapping_rem(r)),
+        Operator::Xor => Value::Integer(l ^ r),
+        Operator::And => Value::Integer(l & r),
+        Operator::Or => Value::Integer(l | r),
+        Operator::Shl | Operator::Shlu => Value::Integer(l.wrapping_shl(r as u32)),
+        Operator::Shr => Value::Integer(l.wrapping_shr(r as u32)),
+        Operator::Shru => Value::Integer((l as u64).wrapping_shr(r as u32) as i64),
+        Operator::Lt => Value::Bool(l < r),
+        Operator::Lte => Value::Bool(l <= r),
+        Operator::Gt => Value::Bool(l > r),
+        Operator::Gte => Value::Bool(l >= r),
+        _ => return Err(RuntimeError::new(format!("Expected expression operator but found invalid operator {}", op)))
+    };
+    Ok(result)
+}
+
+/// applies a binary operator to two values of the given type, the way C does it for that type. Integers
+/// wrap to fit, unsigned integers are divided, shifted and compared as unsigned, and f32 results are
+/// rounded to an f32
+fn typed_binary_op(op: Operator, left: Value, right: Value, value_type: &Type) -> Result<Value, RuntimeError> {
+    if let (Value::Integer(l), Value::Integer(r)) = (&left, &right) {
+        // unsigned values are kept as their bits, so a u64 past i64::MAX is negative here
+        let unsigned = value_type.is_integer() && !value_type.is_signed();
+        let (l, r) = (*l as u64, *r as u64);
+        let result = match op {
+            Operator::Div | Operator::Mod if unsigned && r == 0 => {
+                return Err(RuntimeError::new("Attempted to divide by zero"));
+            }
+            Operator::Div if unsigned => Value::Integer((l / r) as i64),
+            Operator::Mod if unsigned => Value::Integer((l % r) as i64),
+            Operator::Shr if unsigned => Value::Integer(l.wrapping_shr(r as u32) as i64),
+            Operator::Lt if unsigned => Value::Bool(l < r),
+            Operator::Lte if unsigned => Value::Bool(l <= r),
+            Operator::Gt if unsigned => Value::Bool(l > r),
+            Operator::Gte if unsigned => Value::Bool(l >= r),
+            // `>>>` shifts zeros in at the top of the value's own width
+            Operator::Shru => {
+                let bits = match cast(Value::Integer(l as i64), &value_type.unsigned())? {
+                    Value::Integer(bits) => bits as u64,
+                    _ => l
+                };
+                cast(Value::Integer(bits.wrapping_shr(r as u32) as i64), value_type)?
+            }
+            _ => binary_op(op, Value::Integer(l as i64), Value::Integer(r as i64))?
+        };
+        return match result {
+            Value::Integer(_) => cast(result, value_type),
+            result => Ok(result)
+        };
+    }
+    match binary_op(op, left, right)? {
+        Value::Float(v) if *value_type == Type::F32 => Ok(Value::Float(v as f32 as f64)),
+        result => Ok(result)
+    }
+}
+
+/// A host function that runs when the program raises an interrupt, given the interrupt's vector
+pub type InterruptHandler = Box<dyn FnMut(i64) -> Result<(), RuntimeError>>;
+
+/// The host functions interrupts are dispatched to. An interrupt with no handler of its own
+/// goes to the fallback, and is an error if there is none
+#[derive(Default)]
+pub struct InterruptTable {
+    pub handlers: HashMap<i64, InterruptHandler>,
+    pub fallback: Option<InterruptHandler>,
+}
+
+impl InterruptTable {
+    fn raise(&mut self, vector: i64) -> Result<(), RuntimeError> {
+        match self.handlers.get_mut(&vector).or(self.fallback.as_mut()) {
+            Some(handler) => handler(vector),
+            None => Err(RuntimeError::new(format!("No handler for interrupt {:#04x}", vector)))
+        }
+    }
+}
+
+struct Interpreter {
+    functions: HashMap<String, Statement>,
+    /// the field names of every struct, in the order they were declared
+    structs: HashMap<String, Vec<String>>,
+    /// the variants of every enum and their discriminants
+    enums: HashMap<String, Vec<(String, i64)>>,
+    /// the methods of every struct, by type and then method name
+    methods: HashMap<String, HashMap<String, Statement>>,
+    /// the types of every struct's fields, in the order they were declared
+    field_types: HashMap<String, Vec<Type>>,
+    /// the values of every const and static, which variables with the same name shadow
+    globals: HashMap<String, Slot>,
+    memory: Memory,
+    interrupts: InterruptTable,
+    call_depth: usize,
+    /// set while the panic hook runs, so a panic inside of it doesn't call it again
+    panicking: bool,
+}
+
+impl Interpreter {
+    fn new(interrupts: InterruptTable) -> Self {
+        Self {
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            methods: HashMap::new(),
+            field_types: HashMap::new(),
+            globals: HashMap::new(),
+            memory: Memory::new(),
+            interrupts,
+            call_depth: 0,
+            panicking: false,
+        }
+    }
+
+    /// collects the functions and structs defined at the top level of the program and in imported files
+    fn register(&mut self, exprs: &Vec<Statement>) -> Result<(), RuntimeError> {
+        for expr in exprs {
+            match expr {
+                Statement::Fn { ident, .. } => {
+                    let name = ident_name(ident)?;
+                    if self.functions.contains_key(&name) {
+                        return Err(RuntimeError::new(format!("Function `{}` is defined more than once", name)));
                     }
+                    self.functions.insert(name, expr.clone());
                 }
+                Statement::Struct { ident, fields, .. } => {
+                    let names = fields.iter().filter_map(|f| match f {
+                        Statement::Declaration { ident, .. } => ident_name(ident).ok(),
+                        _ => None
+                    }).collect();
+                    self.structs.insert(ident_name(ident)?, names);
+                }
+                Statement::Enum { ident, variants, .. } => {
+                    let mut values = Vec::new();
+                    let mut next = 0;
+                    for v in variants {
+                        if let Statement::Declaration { ident, value, .. } = v {
+                            if let Some(value) = value {
+                                next = literal_value(value).ok_or_else(|| RuntimeError::new(
+                                    format!("Invalid discriminant for `{}`", ident)))? as i64;
+                            }
+                            values.push((ident_name(ident)?, next));
+                            next += 1;
+                        }
+                    }
+                    self.enums.insert(ident_name(ident)?, values);
+                }
+                Statement::Impl { ident, methods, .. } => {
+                    let defined = self.methods.entry(ident_name(ident)?).or_default();
+                    for m in methods {
+                        if let Statement::Fn { ident, .. } = m {
+                            defined.insert(ident_name(ident)?, m.clone());
+                        }
+                    }
+                }
+                Statement::Use { exprs, .. } => self.register(exprs)?,
+                Statement::Const { .. } | Statement::Static { .. } | Statement::NOP => {}
                 _ => {
                     return Err(RuntimeError::new(format!("Unimplemented expression: {}", expr)));
                 }
             }
         }
-    } else {
-        return Err(RuntimeError::new(format!("Unexpected AST root statement, expected Statement::Program, got Statement::{}", ast)));
+        Ok(())
     }
 
-    if start_function.is_none() {
-        return Err(RuntimeError::new("No start() function found! Could not interpret the code!"));
+    /// works out the types of every struct's fields, once every struct and enum is known
+    fn init_layouts(&mut self, exprs: &Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Struct { ident, fields, .. } => {
+                    let types = fields.iter().map(|f| match f {
+                        Statement::Declaration { type_ident: Some(t), .. } => {
+                            Type::from_statement(t, &|n| self.user_type(n)).unwrap_or(Type::Void)
+                        }
+                        _ => Type::Void
+                    }).collect();
+                    if let Ok(name) = ident_name(ident) {
+                        self.field_types.insert(name, types);
+                    }
+                }
+                Statement::Use { exprs, .. } => self.init_layouts(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    /// the type a struct or enum's name refers to
+    fn user_type(&self, name: &str) -> Option<Type> {
+        if self.structs.contains_key(name) {
+            Some(Type::Struct(name.to_string()))
+        } else if self.enums.contains_key(name) {
+            Some(Type::Enum(name.to_string()))
+        } else {
+            None
+        }
+    }
+
+    /// evaluates the value of every const and static, in the order they are defined
+    fn init_globals(&mut self, exprs: &Vec<Statement>) -> Result<(), RuntimeError> {
+        for expr in exprs {
+            match expr {
+                Statement::Const { ident, value, .. } | Statement::Static { ident, value, .. } => {
+                    let value = self.eval(value, &mut Environment::new())?;
+                    self.globals.insert(ident_name(ident)?, Slot::Value(value));
+                }
+                Statement::Use { exprs, .. } => self.init_globals(exprs)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, name: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let function = self.functions.get(name).cloned()
+            .ok_or_else(|| RuntimeError::new(format!("Call to undefined function `{}`", name)))?;
+        Ok(self.run_fn(name, function, args)?.0)
+    }
+
+    /// calls a method on the value of `receiver`. methods take `&self`, so any changes the method
+    /// makes to `self` are stored back into the receiver
+    fn call_method(&mut self, receiver: &Statement, method: &Statement, params: &Vec<Statement>,
+                   env: &mut Environment) -> Result<Value, RuntimeError> {
+        let value = self.eval(receiver, env)?;
+        // a method called through a pointer or reference runs on what it points to, which it changes in place
+        let (value, pointer) = match value {
+            Value::Pointer { address, pointee } => (self.read(address, &pointee)?, Some((address, pointee))),
+            value => (value, None)
+        };
+        let method_name = ident_name(method)?;
+        let type_name = match &value {
+            Value::Struct { name, .. } => name.clone(),
+            other => return Err(RuntimeError::new(format!("Can not call `{}` on a {}", method_name, other.type_name())))
+        };
+        let function = self.methods.get(&type_name).and_then(|m| m.get(&method_name)).cloned()
+            .ok_or_else(|| RuntimeError::new(format!("`{}` has no method named `{}`", type_name, method_name)))?;
+        let mut args = vec![value];
+        for p in params {
+            args.push(self.eval(p, env)?);
+        }
+        let name = format!("{}.{}", type_name, method_name);
+        let (result, callee_env) = self.run_fn(&name, function, args)?;
+        if let Some((address, pointee)) = pointer {
+            let receiver_value = self.variable("self", &callee_env)?;
+            self.write(address, &pointee, receiver_value)?;
+        } else if matches!(receiver, Statement::Identifier { .. } | Statement::PropertyAccess { .. }) {
+            let receiver_value = self.variable("self", &callee_env)?;
+            self.assign(receiver, receiver_value, env)?;
+        }
+        Ok(result)
+    }
+
+    /// runs a function with the given arguments, returning what it returned and the
+    /// environment it finished with
+    fn run_fn(&mut self, name: &str, function: Statement, args: Vec<Value>)
+        -> Result<(Value, Environment), RuntimeError> {
+        let (params, body) = match function {
+            Statement::Fn { params, body, .. } => (params, body),
+            _ => unreachable!()
+        };
+
+        if params.len() != args.len() {
+            return Err(RuntimeError::new(format!("Function `{}` takes {} arguments but {} were supplied",
+                                                 name, params.len(), args.len())));
+        }
+
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new(format!("Stack overflow while calling `{}`", name)));
+        }
+
+        // each call gets a fresh environment holding only its parameters
+        let mut env = Environment::new();
+        for (param, arg) in params.iter().zip(args) {
+            match param {
+                Statement::Declaration { ident, .. } => env.declare(ident_name(ident)?, arg),
+                _ => return Err(RuntimeError::new(format!("Invalid parameter in function `{}`", name)))
+            }
+        }
+
+        self.call_depth += 1;
+        let flow = self.exec(&body, &mut env);
+        self.call_depth -= 1;
+
+        let flow = flow.map_err(|mut e| {
+            e.trace.push(name.to_string());
+            e
+        })?;
+        match flow {
+            Flow::Return(value) => Ok((value, env)),
+            Flow::Normal(_) => Ok((Value::Void, env)),
+            Flow::Break(..) | Flow::Continue(_) => {
+                Err(RuntimeError::new(format!("break or continue outside of a loop in function `{}`", name)))
+            }
+        }
     }
 
-    println!("RUNNING START FUNCTION");
+    /// runs a block of statements in a new scope
+    fn exec_block(&mut self, exprs: &Vec<Statement>, env: &mut Environment) -> Result<Flow, RuntimeError> {
+        env.push_scope();
+        let mut result = Ok(Flow::Normal(Value::Void));
+        for expr in exprs {
+            result = self.exec(expr, env);
+            match result {
+                Ok(Flow::Normal(_)) => continue,
+                _ => break,
+            }
+        }
+        env.pop_scope();
+        result
+    }
+
+    /// runs the body of a loop, returning Some if the loop should stop with the given flow. A break or
+    /// continue for a loop outside of this one stops it and is passed on
+    fn exec_loop_body(&mut self, label: &Option<Box<Statement>>, body: &Statement, env: &mut Environment)
+        -> Result<Option<Flow>, RuntimeError> {
+        let label = label.as_deref().map(ident_name).transpose()?;
+        let targets = |target: &Option<String>| target.is_none() || *target == label;
+        match self.exec(body, env)? {
+            Flow::Break(target, value) if targets(&target) => Ok(Some(Flow::Normal(value))),
+            Flow::Normal(_) => Ok(None),
+            Flow::Continue(target) if targets(&target) => Ok(None),
+            flow => Ok(Some(flow)),
+        }
+    }
+
+    fn condition(&mut self, condition: &Statement, env: &mut Environment) -> Result<bool, RuntimeError> {
+        let value = self.eval(condition, env)?;
+        value.as_bool().map_err(|_| RuntimeError::new(
+            format!("Conditions must be a bool, but found a {}", value.type_name())))
+    }
+
+    /// runs a statement. Errors that don't know where they came from yet are given the statement's span
+    fn exec(&mut self, stmt: &Statement, env: &mut Environment) -> Result<Flow, RuntimeError> {
+        self.exec_statement(stmt, env).map_err(|e| e.at(stmt.span()))
+    }
+
+    fn exec_statement(&mut self, stmt: &Statement, env: &mut Environment) -> Result<Flow, RuntimeError> {
+        match stmt {
+            Statement::Block { exprs, .. } => self.exec_block(exprs, env),
+            Statement::Declaration { ident, value, .. } => {
+                let name = ident_name(ident)?;
+                let value = match value {
+                    Some(v) => self.eval(v, env)?,
+                    None => Value::Void,
+                };
+                env.declare(name, value);
+                Ok(Flow::Normal(Value::Void))
+            }
+            Statement::Assignment { ident, value, .. } => {
+                let value = self.eval(value, env)?;
+                self.assign(ident, value, env)?;
+                Ok(Flow::Normal(Value::Void))
+            }
+            // the target is only worked out once, so `a[next()] += 1` only calls `next` once
+            Statement::CompoundAssignment { op, ident, value, value_type, .. } => {
+                let place = self.place(ident, env)?;
+                let current = self.read_place(&place, env)?;
+                let value = self.eval(value, env)?;
+                let result = self.binary_value(*op, current, value, value_type)?;
+                self.write_place(&place, result, env)?;
+                Ok(Flow::Normal(Value::Void))
+            }
+            Statement::If { condition, body, else_statement, .. } => {
+                if self.condition(condition, env)? {
+                    self.exec(body, env)
+                } else if let Some(else_body) = else_statement {
+                    self.exec(else_body, env)
+                } else {
+                    Ok(Flow::Normal(Value::Void))
+                }
+            }
+            Statement::While { condition, body, label, .. } => {
+                while self.condition(condition, env)? {
+                    if let Some(flow) = self.exec_loop_body(label, body, env)? {
+                        return Ok(flow);
+                    }
+                }
+                Ok(Flow::Normal(Value::Void))
+            }
+            Statement::Loop { body, label, .. } => {
+                loop {
+                    if let Some(flow) = self.exec_loop_body(label, body, env)? {
+                        return Ok(flow);
+                    }
+                }
+            }
+            Statement::For { ident, collection, step, body, label, .. } => {
+                let name = ident_name(ident)?;
+                let step = match step {
+                    Some(step) => self.eval(step, env)?.as_integer()?,
+                    None => 1
+                };
+                let step = usize::try_from(step).ok().filter(|s| *s > 0)
+                    .ok_or_else(|| RuntimeError::new(format!("A for loop can't step by {}", step)))?;
+                let values: Box<dyn Iterator<Item = Value>> = match self.eval(collection, env)? {
+                    // an inclusive range can end at the largest value, so it can't be made exclusive with end + 1
+                    Value::Range { start, end, inclusive: true } => Box::new((start..=end).step_by(step).map(Value::Integer)),
+                    Value::Range { start, end, inclusive: false } => Box::new((start..end).step_by(step).map(Value::Integer)),
+                    // the array is copied, so changing it in the loop doesn't change the elements visited
+                    Value::Array(items) => Box::new(items.into_iter().step_by(step)),
+                    // a reference to an array visits a pointer to each element, so the loop can change them
+                    Value::Pointer { address, pointee: Type::Array(element, length) } => {
+                        let size = self.size_of(&element);
+                        Box::new((0..length as u64).step_by(step).map(move |i| Value::Pointer {
+                            address: address + i * size,
+                            pointee: (*element).clone(),
+                        }))
+                    }
+                    other => return Err(RuntimeError::new(format!("Can not iterate over a {}", other.type_name())))
+                };
+                for value in values {
+                    env.push_scope();
+                    env.declare(name.clone(), value);
+                    let flow = self.exec_loop_body(label, body, env);
+                    env.pop_scope();
+                    if let Some(flow) = flow? {
+                        return Ok(flow);
+                    }
+                }
+                Ok(Flow::Normal(Value::Void))
+            }
+            Statement::Match { value, arms, .. } => self.exec_match(value, arms, env),
+            Statement::Panic { value, span, .. } => {
+                let message = match self.eval(value, env)? {
+                    Value::Void => format!("panicked at {}", span),
+                    value => format!("panicked at {}: {}", span, value),
+                };
+                Err(self.panic(message))
+            }
+            Statement::Assert { expr, span, .. } => {
+                if !self.condition(expr, env)? {
+                    return Err(self.panic(format!("assertion failed at {}", span)));
+                }
+                Ok(Flow::Normal(Value::Void))
+            }
+            Statement::Interrupt { vector, .. } => {
+                let vector = self.eval(vector, env)?.as_integer()?;
+                self.interrupts.raise(vector)?;
+                Ok(Flow::Normal(Value::Void))
+            }
+            Statement::Break { label, value, .. } => {
+                let value = match value {
+                    Some(value) => self.eval(value, env)?,
+                    None => Value::Void
+                };
+                Ok(Flow::Break(label.as_deref().map(ident_name).transpose()?, value))
+            }
+            Statement::Continue { label, .. } => Ok(Flow::Continue(label.as_deref().map(ident_name).transpose()?)),
+            Statement::Return { value, .. } => Ok(Flow::Return(self.eval(value, env)?)),
+            Statement::NOP => Ok(Flow::Normal(Value::Void)),
+            _ => Ok(Flow::Normal(self.eval(stmt, env)?)),
+        }
+    }
 
+    /// stops the program, first passing the message to the program's `panic_hook` if it has one
+    fn panic(&mut self, message: String) -> RuntimeError {
+        if !self.panicking && self.functions.contains_key("panic_hook") {
+            self.panicking = true;
+            // the program is stopping either way, so a hook that fails is not reported
+            let _ = self.call("panic_hook", vec![Value::String(message.clone())]);
+        }
+        RuntimeError::new(message)
+    }
 
+    /// checks that an index is inside an array, panicking with where the array was indexed if it isn't
+    fn array_index(&mut self, array: &Value, index: i64, span: &Span) -> Result<usize, RuntimeError> {
+        match array {
+            Value::Array(items) => self.check_index(items.len(), index, span),
+            other => Err(RuntimeError::new(format!("Expected an array but found a {}", other.type_name())))
+        }
+    }
 
-    Ok(())
-}
\ No newline at end of file
+    fn check_index(&mut self, length: usize, index: i64, span: &Span) -> Result<usize, RuntimeError> {
+        match usize::try_from(index).ok().filter(|i| *i < length) {
+            Some(i) => Ok(i),
+            None => Err(self.panic(format!("index out of bounds at {}: the length is {} but the index is {}",
+                                           span, length, index)))
+        }
+    }
+
+    /// returns true if a match pattern matches the value
+    fn matches(&mut self, pattern: &Statement, value: &Value, env: &mut Environment) -> Result<bool, RuntimeError> {
+        // chars are compared by their code so they can be in the same range as integers
+        let as_number = |v: &Value| match v {
+            Value::Char(c) => Some(*c as i64),
+            Value::Integer(i) => Some(*i),
+            _ => None
+        };
+        match pattern {
+            Statement::Wildcard { .. } => Ok(true),
+            Statement::RangePattern { start, end, inclusive, .. } => {
+                let value = as_number(value)
+                    .ok_or_else(|| RuntimeError::new(format!("Can not match a {} against a range", value.type_name())))?;
+                let start = as_number(&self.eval(start, env)?).unwrap_or(i64::MAX);
+                let end = as_number(&self.eval(end, env)?).unwrap_or(i64::MIN);
+                Ok(value >= start && if *inclusive { value <= end } else { value < end })
+            }
+            _ => Ok(self.eval(pattern, env)? == *value)
+        }
+    }
+
+    /// runs the body of the first arm whose pattern matches the value
+    fn exec_match(&mut self, value: &Statement, arms: &Vec<Statement>, env: &mut Environment) -> Result<Flow, RuntimeError> {
+        let value = self.eval(value, env)?;
+        for arm in arms {
+            if let Statement::MatchArm { pattern, body, .. } = arm {
+                if self.matches(pattern, &value, env)? {
+                    return self.exec(body, env);
+                }
+            }
+        }
+        Err(RuntimeError::new(format!("No match arm matched the value {}", value)))
+    }
+
+    /// the value of a variable, or of the const or static it names, read from memory if its address has been taken
+    fn variable(&mut self, name: &str, env: &Environment) -> Result<Value, RuntimeError> {
+        let slot = env.get(name).or_else(|| self.globals.get(name)).cloned()
+            .ok_or_else(|| RuntimeError::new(format!("Use of undeclared variable `{}`", name)))?;
+        match slot {
+            Slot::Value(value) => Ok(value),
+            Slot::Memory { address, value_type } => self.read(address, &value_type)
+        }
+    }
+
+    fn set_variable(&mut self, name: &str, value: Value, env: &mut Environment) -> Result<(), RuntimeError> {
+        let slot = if env.contains(name) { env.get_mut(name) } else { self.globals.get_mut(name) }
+            .ok_or_else(|| RuntimeError::new(format!("Assignment to undeclared variable `{}`", name)))?;
+        match slot {
+            Slot::Value(v) => *v = value,
+            Slot::Memory { address, value_type } => {
+                let (address, value_type) = (*address, value_type.clone());
+                self.write(address, &value_type, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, address: u64, t: &Type) -> Result<Value, RuntimeError> {
+        let bytes = self.memory.read(address, self.size_of(t))?;
+        self.decode(&bytes, t)
+    }
+
+    fn write(&mut self, address: u64, t: &Type, value: Value) -> Result<(), RuntimeError> {
+        let bytes = self.encode(value, t)?;
+        self.memory.write(address, bytes);
+        Ok(())
+    }
+
+    /// copies a value into memory of its own, returning its address
+    fn allocate(&mut self, value: Value, t: &Type) -> Result<u64, RuntimeError> {
+        let bytes = self.encode(value, t)?;
+        let (_, align) = t.layout(&struct_fields(&self.field_types));
+        Ok(self.memory.allocate(bytes, align))
+    }
+
+    /// the bytes a value is kept as in memory when it is stored as a `t`, or None for the bytes of
+    /// an uninitialized value
+    fn encode(&mut self, value: Value, t: &Type) -> Result<Vec<Option<u8>>, RuntimeError> {
+        let size = self.size_of(t) as usize;
+        let bits = match (value, t) {
+            (Value::Void, _) => return Ok(vec![None; size]),
+            (Value::Array(items), Type::Array(element, _)) => {
+                let mut bytes = Vec::with_capacity(size);
+                for item in items {
+                    bytes.extend(self.encode(item, element)?);
+                }
+                return Ok(bytes);
+            }
+            (Value::Struct { fields: values, .. }, Type::Struct(name)) => {
+                let types = struct_fields(&self.field_types)(name);
+                let (offsets, ..) = Type::struct_layout(&types, &struct_fields(&self.field_types));
+                // padding is zeroed, so the whole struct can be read through a pointer to something else
+                let mut bytes = vec![Some(0); size];
+                for ((offset, field_type), (_, value)) in offsets.into_iter().zip(&types).zip(values) {
+                    let offset = offset as usize;
+                    let field = self.encode(value, field_type)?;
+                    bytes.splice(offset..offset + field.len(), field);
+                }
+                return Ok(bytes);
+            }
+            (Value::Range { start, end, inclusive }, _) => {
+                let end = if inclusive { end.wrapping_add(1) } else { end };
+                return Ok(start.to_le_bytes().into_iter().chain(end.to_le_bytes()).map(Some).collect());
+            }
+            (Value::Float(v), Type::F32) => (v as f32).to_bits() as u64,
+            (Value::Float(v), _) => v.to_bits(),
+            (Value::String(s), _) => self.memory.string(s),
+            (Value::Integer(i), _) => i as u64,
+            (Value::Char(c), _) => c as u64,
+            (Value::Bool(b), _) => b as u64,
+            (Value::Enum { value, .. }, _) => value as u64,
+            (Value::Pointer { address, .. }, _) => address,
+            (value, t) => return Err(RuntimeError::new(format!("A {} can not be stored as a `{}`", value.type_name(), t)))
+        };
+        Ok(bits.to_le_bytes().into_iter().take(size).map(Some).collect())
+    }
+
+    /// the value a `t` kept as these bytes in memory has
+    fn decode(&self, bytes: &[u8], t: &Type) -> Result<Value, RuntimeError> {
+        let mut word = [0; 8];
+        let length = bytes.len().min(8);
+        word[..length].copy_from_slice(&bytes[..length]);
+        let bits = u64::from_le_bytes(word);
+        Ok(match t {
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::I64 => Value::Integer(bits as i64),
+            Type::I8 => Value::Integer(bits as i8 as i64),
+            Type::I16 => Value::Integer(bits as i16 as i64),
+            Type::I32 | Type::Int | Type::UntypedInt => Value::Integer(bits as i32 as i64),
+            Type::F32 => Value::Float(f32::from_bits(bits as u32) as f64),
+            Type::F64 | Type::UntypedFloat => Value::Float(f64::from_bits(bits)),
+            Type::Bool => Value::Bool(bits != 0),
+            Type::Char => Value::Char(bits as u8 as char),
+            Type::Void => Value::Void,
+            Type::Str => self.memory.strings.get(bits as usize).cloned().map(Value::String)
+                .ok_or_else(|| RuntimeError::new("Read of a `str` that was never stored"))?,
+            Type::Pointer(pointee) | Type::Reference(pointee) => Value::Pointer { address: bits, pointee: *pointee.clone() },
+            Type::Range(_) => {
+                let end = i64::from_le_bytes(bytes[8..16].try_into().unwrap());
+                Value::Range { start: bits as i64, end, inclusive: false }
+            }
+            Type::Enum(name) => {
+                let value = bits as i32 as i64;
+                let variant = self.enums.get(name).and_then(|variants| variants.iter().find(|(_, v)| *v == value))
+                    .ok_or_else(|| RuntimeError::new(format!("`{}` has no variant with the discriminant {}", name, value)))?;
+                Value::Enum { name: name.clone(), variant: variant.0.clone(), value }
+            }
+            Type::Array(element, length) => {
+                let size = self.size_of(element) as usize;
+                let items = (0..*length).map(|i| self.decode(&bytes[i * size..(i + 1) * size], element));
+                Value::Array(items.collect::<Result<_, _>>()?)
+            }
+            Type::Struct(name) => {
+                let fields = struct_fields(&self.field_types);
+                let types = fields(name);
+                let (offsets, ..) = Type::struct_layout(&types, &fields);
+                let names = self.structs.get(name).cloned().unwrap_or_default();
+                let mut values = Vec::with_capacity(types.len());
+                for ((field, field_type), offset) in names.into_iter().zip(&types).zip(offsets) {
+                    let start = offset as usize;
+                    let end = start + self.size_of(field_type) as usize;
+                    values.push((field, self.decode(&bytes[start..end], field_type)?));
+                }
+                Value::Struct { name: name.clone(), fields: values }
+            }
+        })
+    }
+
+    fn size_of(&self, t: &Type) -> u64 {
+        t.layout(&struct_fields(&self.field_types)).0
+    }
+
+    /// the address of a variable, field, array element or dereferenced pointer holding a `place_type`.
+    /// A variable is moved into memory the first time its address is taken, and any other value is
+    /// copied into memory of its own
+    fn address_of(&mut self, place: &Statement, place_type: &Type, env: &mut Environment) -> Result<u64, RuntimeError> {
+        match place {
+            Statement::Identifier { ident, .. } => {
+                let slot = if env.contains(ident) { env.get_mut(ident) } else { self.globals.get_mut(ident) }
+                    .ok_or_else(|| RuntimeError::new(format!("Use of undeclared variable `{}`", ident)))?;
+                let value = match slot {
+                    Slot::Memory { address, .. } => return Ok(*address),
+                    Slot::Value(value) => value.clone()
+                };
+                let address = self.allocate(value, place_type)?;
+                let slot = if env.contains(ident) { env.get_mut(ident) } else { self.globals.get_mut(ident) };
+                *slot.unwrap() = Slot::Memory { address, value_type: place_type.clone() };
+                Ok(address)
+            }
+            Statement::PropertyAccess { expr, property, .. } => {
+                let base = self.eval(expr, env)?;
+                Ok(self.field_address(base, expr, property, env)?.0)
+            }
+            Statement::ArrayAccess { ident, index, span, .. } => {
+                let base = self.eval(ident, env)?;
+                let index = self.eval(index, env)?.as_integer()?;
+                if let Value::Pointer { address, pointee } = base {
+                    return Ok(self.pointer_element(address, pointee, index, span)?.0);
+                }
+                let index = self.array_index(&base, index, span)?;
+                let length = match base {
+                    Value::Array(items) => items.len(),
+                    _ => unreachable!()
+                };
+                let start = self.address_of(ident, &Type::Array(Box::new(place_type.clone()), length), env)?;
+                Ok(start + index as u64 * self.size_of(place_type))
+            }
+            Statement::Unary { op: Operator::Mul, expr, .. } => match self.eval(expr, env)? {
+                Value::Pointer { address, .. } => Ok(address),
+                other => Err(RuntimeError::new(format!("Expected a pointer but found a {}", other.type_name())))
+            },
+            _ => {
+                let value = self.eval(place, env)?;
+                self.allocate(value, place_type)
+            }
+        }
+    }
+
+    /// where a field of a struct, or of the struct a pointer points to, is in memory and its type.
+    /// `base` is the value of `base_expr`
+    fn field_address(&mut self, base: Value, base_expr: &Statement, property: &Statement, env: &mut Environment)
+        -> Result<(u64, Type), RuntimeError> {
+        let (address, name) = match base {
+            Value::Pointer { address, pointee: Type::Struct(name) } => (address, name),
+            Value::Struct { name, .. } => (self.address_of(base_expr, &Type::Struct(name.clone()), env)?, name),
+            other => return Err(RuntimeError::new(format!("Expected a struct but found a {}", other.type_name())))
+        };
+        let field = ident_name(property)?;
+        let index = self.structs.get(&name).and_then(|fields| fields.iter().position(|f| *f == field))
+            .ok_or_else(|| RuntimeError::new(format!("`{}` has no field named `{}`", name, field)))?;
+        let fields = struct_fields(&self.field_types);
+        let types = fields(&name);
+        let (offsets, ..) = Type::struct_layout(&types, &fields);
+        Ok((address + offsets[index], types[index].clone()))
+    }
+
+    /// where element `index` of what a pointer points to is in memory and its type. Pointers to arrays
+    /// are bounds checked, any other pointer is indexed without a check like it is in C
+    fn pointer_element(&mut self, address: u64, pointee: Type, index: i64, span: &Span)
+        -> Result<(u64, Type), RuntimeError> {
+        let element = match pointee {
+            Type::Array(element, length) => {
+                self.check_index(length, index, span)?;
+                *element
+            }
+            t => t
+        };
+        let offset = (index as u64).wrapping_mul(self.size_of(&element));
+        Ok((address.wrapping_add(offset), element))
+    }
+
+    /// stores a value in a variable, a field or element of one, or where a pointer points
+    fn assign(&mut self, target: &Statement, value: Value, env: &mut Environment) -> Result<(), RuntimeError> {
+        let place = self.place(target, env)?;
+        self.write_place(&place, value, env)
+    }
+
+    /// works out where an assignment to `target` stores its value, evaluating any index or pointer in it
+    fn place(&mut self, target: &Statement, env: &mut Environment) -> Result<Place, RuntimeError> {
+        match target {
+            Statement::Identifier { ident, .. } => Ok(Place::Variable(ident.clone())),
+            Statement::Unary { op: Operator::Mul, expr, .. } => match self.eval(expr, env)? {
+                Value::Pointer { address, pointee } => Ok(Place::Memory(address, pointee)),
+                other => Err(RuntimeError::new(format!("Expected a pointer but found a {}", other.type_name())))
+            },
+            Statement::PropertyAccess { expr, property, .. } => {
+                let base_place = self.place(expr, env)?;
+                let base = self.read_place(&base_place, env)?;
+                if let Value::Pointer { .. } = base {
+                    let (address, field_type) = self.field_address(base, expr, property, env)?;
+                    return Ok(Place::Memory(address, field_type));
+                }
+                let index = base.field_index(&ident_name(property)?)?;
+                Ok(Place::Part(Box::new(base_place), index))
+            }
+            Statement::ArrayAccess { ident, index, span, .. } => {
+                let base_place = self.place(ident, env)?;
+                let index = self.eval(index, env)?.as_integer()?;
+                let base = self.read_place(&base_place, env)?;
+                if let Value::Pointer { address, pointee } = base {
+                    let (address, element) = self.pointer_element(address, pointee, index, span)?;
+                    return Ok(Place::Memory(address, element));
+                }
+                let index = self.array_index(&base, index, span)?;
+                Ok(Place::Part(Box::new(base_place), index))
+            }
+            _ => Err(RuntimeError::new(format!("Can not assign to a {} expression", target)))
+        }
+    }
+
+    fn read_place(&mut self, place: &Place, env: &mut Environment) -> Result<Value, RuntimeError> {
+        match place {
+            Place::Variable(name) => self.variable(name, env),
+            Place::Memory(address, t) => self.read(*address, t),
+            Place::Part(base, index) => match self.read_place(base, env)? {
+                Value::Struct { mut fields, .. } => Ok(fields.swap_remove(*index).1),
+                Value::Array(mut items) => Ok(items.swap_remove(*index)),
+                other => Err(RuntimeError::new(format!("A {} has no fields or elements", other.type_name())))
+            }
+        }
+    }
+
+    fn write_place(&mut self, place: &Place, value: Value, env: &mut Environment) -> Result<(), RuntimeError> {
+        match place {
+            Place::Variable(name) => self.set_variable(name, value, env),
+            Place::Memory(address, t) => self.write(*address, t, value),
+            // structs and arrays are values, so the part is changed on a copy which is then stored back
+            Place::Part(base, index) => {
+                let mut whole = self.read_place(base, env)?;
+                match &mut whole {
+                    Value::Struct { fields, .. } => fields[*index].1 = value,
+                    Value::Array(items) => items[*index] = value,
+                    other => return Err(RuntimeError::new(format!("A {} has no fields or elements", other.type_name())))
+                }
+                self.write_place(base, whole, env)
+            }
+        }
+    }
+
+    fn eval_struct_literal(&mut self, ident: &Statement, fields: &Vec<Statement>, env: &mut Environment)
+        -> Result<Value, RuntimeError> {
+        let name = ident_name(ident)?;
+        let declared = self.structs.get(&name).cloned()
+            .ok_or_else(|| RuntimeError::new(format!("No struct named `{}` exists", name)))?;
+        // fields are evaluated in the order they are written, then stored in the order they were declared
+        let mut values = HashMap::new();
+        for f in fields {
+            if let Statement::Assignment { ident, value, .. } = f {
+                values.insert(ident_name(ident)?, self.eval(value, env)?);
+            }
+        }
+        let mut ordered = Vec::new();
+        for field in declared {
+            let value = values.remove(&field)
+                .ok_or_else(|| RuntimeError::new(format!("`{}` is missing the field `{}`", name, field)))?;
+            ordered.push((field, value));
+        }
+        Ok(Value::Struct { name, fields: ordered })
+    }
+
+    /// the type the type checker gave an operator's operands, if it gave one
+    fn operand_type(&self, value_type: &Option<Box<Statement>>) -> Result<Option<Type>, RuntimeError> {
+        value_type.as_ref()
+            .map(|t| Type::from_statement(t, &|n| self.user_type(n)).map_err(|e| RuntimeError::new(e.msg)))
+            .transpose()
+    }
+
+    /// evaluates `left op right` in `value_type`. `&&` and `||` only evaluate their right side when they need
+    /// it, like they do in C
+    fn eval_binary(&mut self, op: Operator, left: &Statement, right: &Statement, value_type: &Option<Box<Statement>>,
+                   env: &mut Environment) -> Result<Value, RuntimeError> {
+        let left = self.eval(left, env)?;
+        match op {
+            Operator::BoolAnd if !left.as_bool()? => Ok(Value::Bool(false)),
+            Operator::BoolOr if left.as_bool()? => Ok(Value::Bool(true)),
+            _ => {
+                let right = self.eval(right, env)?;
+                self.binary_value(op, left, right, value_type)
+            }
+        }
+    }
+
+    /// applies a binary operator to two values that have already been evaluated
+    fn binary_value(&mut self, op: Operator, left: Value, right: Value, value_type: &Option<Box<Statement>>)
+        -> Result<Value, RuntimeError> {
+        match (op, left, right) {
+            // adding to a pointer moves it by whole elements, like it does in C
+            (Operator::Add | Operator::Sub, Value::Pointer { address, pointee }, Value::Integer(n)) |
+            (Operator::Add, Value::Integer(n), Value::Pointer { address, pointee }) => {
+                let offset = n.wrapping_mul(self.size_of(&pointee) as i64);
+                let offset = if op == Operator::Sub { offset.wrapping_neg() } else { offset };
+                Ok(Value::Pointer { address: address.wrapping_add_signed(offset), pointee })
+            }
+            // pointers are compared by address, whatever they point to
+            (_, Value::Pointer { address: l, .. }, Value::Pointer { address: r, .. }) => Ok(Value::Bool(match op {
+                Operator::Eq => l == r,
+                Operator::Neq => l != r,
+                Operator::Lt => l < r,
+                Operator::Lte => l <= r,
+                Operator::Gt => l > r,
+                Operator::Gte => l >= r,
+                _ => return Err(RuntimeError::new(format!("Operator {} can not be applied to pointers", op)))
+            })),
+            (_, left, right) => match self.operand_type(value_type)? {
+                Some(t) => typed_binary_op(op, left, right, &t),
+                None => binary_op(op, left, right)
+            }
+        }
+    }
+
+    /// evaluates `op expr`. `value_type` is the type of the value, which results wrap to fit and `&` takes
+    /// the address of. It is worked out from the value when the type checker hasn't given it
+    fn eval_unary(&mut self, op: Operator, expr: &Statement, postfix: bool, value_type: &Option<Box<Statement>>,
+                  env: &mut Environment) -> Result<Value, RuntimeError> {
+        let operand = self.operand_type(value_type)?;
+        let wrap = |value: Value| match &operand {
+            Some(t) if t.is_integer() => cast(value, t),
+            _ => Ok(value)
+        };
+        match op {
+            // `x++` gives the value from before it changed, and `++x` the value after
+            Operator::Inc | Operator::Dec => {
+                let place = self.place(expr, env)?;
+                let old = self.read_place(&place, env)?.as_integer()?;
+                let new = wrap(Value::Integer(if op == Operator::Inc { old.wrapping_add(1) } else { old.wrapping_sub(1) }))?;
+                self.write_place(&place, new.clone(), env)?;
+                Ok(if postfix { Value::Integer(old) } else { new })
+            }
+            Operator::And => {
+                let pointee = match operand {
+                    Some(t) => t,
+                    None => value_type_of(&self.eval(expr, env)?)
+                };
+                let address = self.address_of(expr, &pointee, env)?;
+                Ok(Value::Pointer { address, pointee })
+            }
+            Operator::Mul => match self.eval(expr, env)? {
+                Value::Pointer { address, pointee } => self.read(address, &pointee),
+                other => Err(RuntimeError::new(format!("Expected a pointer but found a {}", other.type_name())))
+            },
+            _ => {
+                let value = self.eval(expr, env)?;
+                wrap(unary_op(op, value)?)
+            }
+        }
+    }
+
+    /// evaluates an expression. Errors that don't know where they came from yet are given its span
+    fn eval(&mut self, stmt: &Statement, env: &mut Environment) -> Result<Value, RuntimeError> {
+        self.eval_expr(stmt, env).map_err(|e| e.at(stmt.span()))
+    }
+
+    fn eval_expr(&mut self, stmt: &Statement, env: &mut Environment) -> Result<Value, RuntimeError> {
+        match stmt {
+            // the type checker makes sure literals fit in their type, anything past i64 wraps like it does in C
+            Statement::NumberLiteral { value, .. } => Ok(Value::Integer(value.signed_value() as i64)),
+            Statement::FloatLiteral { value, .. } => float_value(value),
+            Statement::BoolLiteral { value, .. } => Ok(Value::Bool(*value)),
+            Statement::CharLiteral { value, .. } => Ok(Value::Char(*value)),
+            Statement::StringLiteral { value, .. } => Ok(Value::String(value.clone())),
+            Statement::Void => Ok(Value::Void),
+            Statement::Identifier { ident, .. } => {
+                let value = self.variable(ident, env)?;
+                if value == Value::Void {
+                    return Err(RuntimeError::new(format!("Use of uninitialized variable `{}`", ident)));
+                }
+                Ok(value)
+            }
+            Statement::Binary { op, left, right, value_type, .. } => self.eval_binary(*op, left, right, value_type, env),
+            Statement::Unary { op, expr, postfix, value_type, .. } => self.eval_unary(*op, expr, *postfix, value_type, env),
+            Statement::Cast { expr, cast_type, .. } => {
+                let value = self.eval(expr, env)?;
+                let target = Type::from_statement(cast_type, &|n| self.user_type(n))
+                    .map_err(|e| RuntimeError::new(format!("Invalid cast: {}", e.msg)))?;
+                cast(value, &target)
+            }
+            Statement::FnCall { ident, params, .. } => {
+                let name = ident_name(ident)?;
+                let mut args = Vec::new();
+                for p in params {
+                    args.push(self.eval(p, env)?);
+                }
+                self.call(&name, args)
+            }
+            Statement::StructLiteral { ident, fields, .. } => self.eval_struct_literal(ident, fields, env),
+            Statement::MethodCall { receiver, method, params, .. } => self.call_method(receiver, method, params, env),
+            Statement::PropertyAccess { expr, property, .. } => {
+                let base = self.eval(expr, env)?;
+                if let Value::Pointer { .. } = base {
+                    let (address, field_type) = self.field_address(base, expr, property, env)?;
+                    return self.read(address, &field_type);
+                }
+                Ok(base.field(&ident_name(property)?)?.clone())
+            }
+            Statement::ArrayAccess { ident, index, span, .. } => {
+                let base = self.eval(ident, env)?;
+                let index = self.eval(index, env)?.as_integer()?;
+                if let Value::Pointer { address, pointee } = base {
+                    let (address, element) = self.pointer_element(address, pointee, index, span)?;
+                    return self.read(address, &element);
+                }
+                let index = self.array_index(&base, index, span)?;
+                match base {
+                    Value::Array(mut items) => Ok(items.swap_remove(index)),
+                    _ => unreachable!()
+                }
+            }
+            Statement::ArrayLiteral { items, .. } => {
+                let mut values = Vec::new();
+                for i in items {
+                    values.push(self.eval(i, env)?);
+                }
+                Ok(Value::Array(values))
+            }
+            Statement::ArrayRepeat { value, size, .. } => {
+                let value = self.eval(value, env)?;
+                let size = self.eval(size, env)?.as_integer()?;
+                let too_big = || RuntimeError::new(format!("An array can't have {} elements", size));
+                let size = usize::try_from(size).ok().filter(|s| *s <= MAX_ARRAY_LENGTH).ok_or_else(too_big)?;
+                // running out of memory is an error rather than aborting the interpreter
+                let mut items = Vec::new();
+                items.try_reserve_exact(size).map_err(|_| too_big())?;
+                items.resize(size, value);
+                Ok(Value::Array(items))
+            }
+            Statement::Path { base, member, .. } => {
+                let (name, variant) = (ident_name(base)?, ident_name(member)?);
+                let value = self.enums.get(&name)
+                    .and_then(|variants| variants.iter().find(|(v, _)| *v == variant))
+                    .map(|(_, value)| *value)
+                    .ok_or_else(|| RuntimeError::new(format!("`{}::{}` does not exist", name, variant)))?;
+                Ok(Value::Enum { name, variant, value })
+            }
+            Statement::Block { .. } | Statement::Declaration { .. } | Statement::Assignment { .. } |
+            Statement::CompoundAssignment { .. } | Statement::If { .. } | Statement::While { .. } |
+            Statement::Loop { .. } | Statement::For { .. } | Statement::Match { .. } | Statement::NOP => {
+                match self.exec(stmt, env)? {
+                    Flow::Normal(v) => Ok(v),
+                    _ => Err(RuntimeError::new(format!("Unexpected control flow in {} expression", stmt)))
+                }
+            }
+            _ => Err(RuntimeError::new(format!("Unimplemented expression: {}", stmt)))
+        }
+    }
+}
+
+/// Runs the program's start() function, returning the value it returned.
+/// Interrupts the program raises are handled by the host functions in `interrupts`
+pub fn interpret(ast: &Statement, interrupts: InterruptTable) -> Result<Value, RuntimeError> {
+    let mut interpreter = Interpreter::new(interrupts);
+
+    if let Statement::Program { exprs, .. } = ast {
+        interpreter.register(exprs)?;
+        interpreter.init_layouts(exprs);
+        interpreter.init_globals(exprs)?;
+    } else {
+        return Err(RuntimeError::new(format!("Unexpected AST root statement, expected Statement::Program, got Statement::{}", ast)));
+    }
+
+    if !interpreter.functions.contains_key("start") {
+        return Err(RuntimeError::new("No start() function found! Could not interpret the code!"));
+    }
+
+    interpreter.call("start", Vec::new())
+}
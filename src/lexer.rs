@@ -2,9 +2,7 @@ use crate::error::Error;
 use crate::input_reader::InputReader;
 use crate::operator::Operator;
 use crate::token::{Token, TokenList, TokenType};
-
-// todo(eric): This will lex 10,000 lines of code in about 4 seconds, so it has a ton of room to
-//  be optimized.
+use crate::types::Type;
 
 /// returns the next identifier to be processed by the lexer.
 fn next_ident(input: &mut InputReader) -> String {
@@ -20,75 +18,243 @@ fn next_ident(input: &mut InputReader) -> String {
     ident
 }
 
-/// returns the next hex literal
-fn lex_hex_lit(input: &mut InputReader) -> Result<Token, Error> {
+/// returns true if the `'` at the start of the input begins a loop label such as `'outer`, rather
+/// than a character literal. A name followed by another `'` is a character literal that is too long
+fn is_label(input: &InputReader) -> bool {
+    if !input.peek_at(1).is_some_and(|c| c.is_alphabetic() || c == '_') {
+        return false;
+    }
+    let mut n = 2;
+    while input.peek_at(n).is_some_and(|c| c.is_alphanumeric() || c == '_') {
+        n += 1;
+    }
+    input.peek_at(n) != Some('\'')
+}
+
+/// the name of a base, for errors
+fn radix_name(radix: u32) -> &'static str {
+    match radix {
+        2 => "binary",
+        8 => "octal",
+        16 => "hex",
+        _ => "number",
+    }
+}
+
+/// lexes the suffix that can follow an integer literal, such as the `u8` in `10u8`, adding it to the
+/// literal. Anything else directly after the digits is an error
+fn lex_suffix(input: &mut InputReader, literal: &mut String, radix: u32) -> Result<(), Error> {
+    let pos = input.pos();
+    match input.peek() {
+        // the digits that are valid have already been lexed
+        Some(c) if c.is_ascii_digit() => {
+            Err(Error::new("Invalid digit", format!("'{}' is not a {} digit", c, radix_name(radix)), pos))
+        }
+        Some(c) if c.is_alphanumeric() || c == '_' => {
+            let suffix = next_ident(input);
+            if !Type::from_name(&suffix).is_some_and(|t| t.is_integer()) {
+                return Err(Error::new("Invalid suffix", format!("`{}` is not an integer type", suffix), pos));
+            }
+            literal.push_str(&suffix);
+            Ok(())
+        }
+        _ => Ok(())
+    }
+}
+
+/// lexes a hex, octal or binary literal, which starts with `0x`, `0o` or `0b`.
+/// `_` can separate the digits and is ignored
+fn lex_radix_lit(input: &mut InputReader, radix: u32, token_type: TokenType) -> Result<Token, Error> {
     let start = input.pos();
     input.consume(); // remove the 0
-    input.consume(); // remove the x
-    let mut hex_lit = String::new();
+    input.consume(); // remove the x, o or b
+    let mut literal = String::new();
     while let Some(c) = input.peek() {
-        if !(c.is_numeric() || (c >= (97 as char) && c <= (102 as char)) || (c >= (65 as char) && c <= (70 as char))) {
+        if c == '_' {
+            input.consume();
+            continue;
+        }
+        if !c.is_digit(radix) {
             break;
         }
         input.consume();
-        hex_lit.push(c);
+        literal.push(c);
+    }
+    if literal.is_empty() {
+        return Err(Error::new("Missing digits", format!("expected {} digits", radix_name(radix)), input.pos()));
     }
-    Ok(Token::new_lit(TokenType::HexLit, hex_lit, start, input.pos()))
+    lex_suffix(input, &mut literal, radix)?;
+    Ok(Token::new_lit(token_type, literal, start, input.pos()))
 }
 
-fn lex_bin_lit(input: &mut InputReader) -> Result<Token, Error> {
+/// decodes an escape sequence starting at a '\\', erroring at the exact character that is invalid
+fn lex_escape(input: &mut InputReader) -> Result<char, Error> {
     let start = input.pos();
-    input.consume(); // remove the 0
-    input.consume(); // remove the b
-    let mut bin_lit = String::new();
-    while let Some(c) = input.peek() {
-        if !(c == '1' || c == '0') {
-            break;
+    input.consume(); // remove the '\\'
+    let pos = input.pos();
+    let c = input.consume().ok_or_else(|| Error::new("Unfinished escape sequence", "found end of file", start.clone()))?;
+    Ok(match c {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '0' => '\0',
+        '\\' => '\\',
+        '"' => '"',
+        '\'' => '\'',
+        'x' => {
+            // exactly two hex digits, limited to ASCII so the value is one byte in C
+            let mut value = 0;
+            for _ in 0..2 {
+                let pos = input.pos();
+                let digit = input.peek().and_then(|d| d.to_digit(16))
+                    .ok_or_else(|| Error::new("Invalid escape sequence", "\\x must be followed by two hex digits", pos))?;
+                input.consume();
+                value = value * 16 + digit;
+            }
+            if value > 0x7F {
+                return Err(Error::new("Invalid escape sequence",
+                                      "\\x can only be up to \\x7F, use \\u{...} for other characters", pos));
+            }
+            char::from_u32(value).unwrap()
+        }
+        'u' => {
+            let brace = input.pos();
+            if input.consume() != Some('{') {
+                return Err(Error::new("Invalid escape sequence", "expected '{' after \\u", brace));
+            }
+            let first_digit = input.pos();
+            let mut value: u32 = 0;
+            let mut digits = 0;
+            loop {
+                let pos = input.pos();
+                match input.consume() {
+                    Some('}') if digits > 0 => break,
+                    Some(d) if d.is_ascii_hexdigit() && digits < 6 => {
+                        value = value * 16 + d.to_digit(16).unwrap();
+                        digits += 1;
+                    }
+                    Some(d) if d.is_ascii_hexdigit() => {
+                        return Err(Error::new("Invalid escape sequence", "\\u{...} can have at most 6 hex digits", pos));
+                    }
+                    Some(d) => {
+                        return Err(Error::new("Invalid escape sequence", format!("expected a hex digit but found '{}'", d), pos));
+                    }
+                    None => return Err(Error::new("Unfinished escape sequence", "found end of file", pos)),
+                }
+            }
+            char::from_u32(value).ok_or_else(|| Error::new("Invalid escape sequence",
+                                                           format!("{:X} is not a unicode character", value), first_digit))?
         }
+        c => return Err(Error::new("Invalid escape sequence", format!("unknown escape '\\{}'", c), pos)),
+    })
+}
+
+/// lexes a string literal, decoding its escape sequences
+fn lex_string(input: &mut InputReader) -> Result<Token, Error> {
+    let start = input.pos();
+    input.consume(); // remove the opening '"'
+    let mut strlit = String::new();
+    loop {
+        match input.peek() {
+            Some('"') => {
+                input.consume();
+                break;
+            }
+            Some('\\') => strlit.push(lex_escape(input)?),
+            Some(c) => {
+                input.consume();
+                strlit.push(c);
+            }
+            None => return Err(Error::new(
+                "String literal with no close",
+                "Reached EOF before finding closing '\"'", start)),
+        }
+    }
+    Ok(Token::new_lit(TokenType::StringLit, strlit, start, input.pos()))
+}
+
+/// lexes a raw string, `r"..."` or `r#"..."#`, where nothing is escaped. Any number of '#' can be
+/// used so the string can contain `"#`
+fn lex_raw_string(input: &mut InputReader) -> Result<Token, Error> {
+    let start = input.pos();
+    input.consume(); // remove the 'r'
+    let mut hashes = 0;
+    while input.peek() == Some('#') {
         input.consume();
-        bin_lit.push(c);
+        hashes += 1;
     }
-    Ok(Token::new_lit(TokenType::BinLit, bin_lit, start, input.pos()))
+    let quote = input.pos();
+    if input.consume() != Some('"') {
+        return Err(Error::new("Invalid raw string", "expected '\"' after the '#'s", quote));
+    }
+    let mut strlit = String::new();
+    loop {
+        match input.consume() {
+            Some('"') if (0..hashes).all(|i| input.peek_at(i) == Some('#')) => {
+                for _ in 0..hashes {
+                    input.consume();
+                }
+                break;
+            }
+            Some(c) => strlit.push(c),
+            None => return Err(Error::new(
+                "String literal with no close",
+                format!("Reached EOF before finding closing '\"{}'", "#".repeat(hashes)), start)),
+        }
+    }
+    Ok(Token::new_lit(TokenType::StringLit, strlit, start, input.pos()))
 }
 
-/// returns the next numeric literal
-fn next_numeric(input: &mut InputReader) -> Result<String, Error> {
-    let start = input.pos(); // used for error handling
+/// returns the next numeric literal and whether it is a float. A '.' is only part of the number if a
+/// digit follows it, so `0..10` and `1.max` still lex as a range and a property access
+fn next_numeric(input: &mut InputReader) -> (String, bool) {
     let mut num = String::new(); // the number that will be returned
-    //let mut decimal = false; // if the number is a decimal
+    let mut float = false; // if the number has a decimal point or exponent
     while let Some(c) = input.peek() {
-        if c.is_numeric() { // if the character is a number
+        if c.is_ascii_digit() { // if the character is a number
             input.consume();
             num.push(c); // add the number to the string
-        } else if c == '.' { // if the character is a decimal
-            // if there is no next character,
-            let next_peek = input.peek_at(1);
-            if next_peek.is_none() {
-                return Err(Error::new(
-                    "Unexpected token at EOF", format!("Found {}", c), start));
+        } else if c == '_' { // separators are only for readability
+            input.consume();
+        } else if c == '.' && !float && input.peek_at(1).is_some_and(|n| n.is_ascii_digit()) {
+            input.consume();
+            num.push(c);
+            float = true;
+        } else if c == 'e' || c == 'E' {
+            // an exponent, which can have a sign
+            let sign = matches!(input.peek_at(1), Some('+' | '-'));
+            let digit_at = if sign { 2 } else { 1 };
+            if !input.peek_at(digit_at).is_some_and(|n| n.is_ascii_digit()) {
+                break;
             }
-            // handle decimal numbers
-            let next = next_peek.unwrap();
-            if next.is_numeric() {
-                return Err(Error::new(
-                    "Unexpected Token", format!("Decimals/floating point numbers are not yet supported!"), input.pos()));
-                // todo(eric): handle decimal numbers
-                // if decimal {
-                //     return Err(Error::new(
-                //         "Extra Decimal Point", format!("The number literal is already a decimal!"), start));
-                // }
-                // decimal = true;
-                // input.consume(); // consume the .
-                // num.push(c);
-                // continue; // continue until the number is complete
-            } else {
-                break; // the . is a property accessor, so break and return the number
+            for _ in 0..digit_at {
+                num.push(input.consume().unwrap());
+            }
+            while let Some(d) = input.peek().filter(|d| d.is_ascii_digit()) {
+                input.consume();
+                num.push(d);
             }
-        } else { // todo(eric): Potentially handle type flags here for numeric literals (i.e. 10u8)
+            float = true;
             break;
+        } else {
+            break;
+        }
+    }
+    (num, float)
+}
+
+/// lexes a number or float literal. Numbers can have a suffix giving their type
+fn lex_numeric(input: &mut InputReader) -> Result<Token, Error> {
+    let start = input.pos();
+    let (mut num, float) = next_numeric(input);
+    if float {
+        if let Some(c) = input.peek().filter(|c| c.is_alphanumeric() || *c == '_') {
+            return Err(Error::new("Invalid suffix", format!("float literals can not have a suffix, found '{}'", c), input.pos()));
         }
+        return Ok(Token::new_lit(TokenType::FloatLit, num, start, input.pos()));
     }
-    Ok(num)
+    lex_suffix(input, &mut num, 10)?;
+    Ok(Token::new_lit(TokenType::NumberLit, num, start, input.pos()))
 }
 
 /// lex the current operator and determines if it is a multi-character operator or the default 1 character
@@ -110,13 +276,15 @@ fn lex_op(input: &mut InputReader, norm: Operator, assign: Operator) -> Operator
     lex_op_other(input, assign, '=').unwrap_or(norm)
 }
 
+#[allow(clippy::needless_return, clippy::useless_format)]
 pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
     let next = input.peek().unwrap();
     let start = input.pos();
     match next {
         // general tokens
-        ' ' | '\n' | '\t' => {
-            input.consume();
+        // a run of whitespace is one token, which still shows whether it crossed a newline
+        ' ' | '\n' | '\t' | '\r' => {
+            input.consume_while(|c| matches!(c, ' ' | '\n' | '\t' | '\r'));
             Ok(Token::new(TokenType::Whitespace, start, input.pos()))
         }
         ';' => {
@@ -185,45 +353,38 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
             }
             Ok(Token::new(TokenType::Dot, start, input.pos()))
         }
-        '"' => { // process string literals
-            // todo(eric): handle escape sequences and string formatting
+        '"' => lex_string(input),
+        // a quote before a name that isn't closed right after it is a loop label, like `'outer`
+        '\'' if is_label(input) => {
             input.consume();
-            let mut strlit = String::new();
-            while let Some(c) = input.peek() {
-                if c == '"' {
-                    input.consume();
-                    break;
-                } else {
-                    input.consume();
-                    strlit.push(c);
-                    if input.peek().is_none() {
-                        return Err(Error::new(
-                            "String literal with no close",
-                            format!("Reached EOF before finding closing '\"'"), start));
-                    }
-                }
-            }
-            Ok(Token::new_lit(TokenType::StringLit, strlit, start, input.pos()))
+            let name = next_ident(input);
+            Ok(Token::new_lit(TokenType::Label, name, start, input.pos()))
         }
         '\'' => {
             input.consume();
-            if let None = input.peek() {
-                return Err(Error::new(
+            let charlit = match input.peek() {
+                None => return Err(Error::new(
                     "Character literal with no close",
-                    format!("Reached EOF before finding closing '\''"), start));
-            }
-            let charlit = input.consume().unwrap();
-            // todo(eric): handle escape characters
-            if let None = input.peek() {
+                    "Reached EOF before finding closing '\''", start)),
+                Some('\'') => return Err(Error::new("Empty character literal",
+                                                   "expected a character before the closing '\''", input.pos())),
+                Some('\\') => lex_escape(input)?,
+                Some(c) => {
+                    input.consume();
+                    c
+                }
+            };
+            if input.peek().is_none() {
                 return Err(Error::new(
                     "Character literal with no close",
                     format!("Reached EOF before finding closing '\''"), start));
             }
+            let pos = input.pos();
             let next = input.consume().unwrap();
             if next != '\'' {
                 return Err(Error::new(
                     "Character literal with no close",
-                    format!("Found '{}' instead of closing '\''", next), start));
+                    format!("Found '{}' instead of closing '\''", next), pos));
             }
             return Ok(Token::new_lit(TokenType::CharLit, charlit.to_string(), start, input.pos()));
         }
@@ -257,15 +418,8 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
             // disreguard comments
             if let Some(next) = input.peek() {
                 if next == '/' {
-                    input.consume();
-                    while let Some(c) = input.peek() {
-                        if c == '\n' {
-                            input.consume();
-                            break;
-                        } else {
-                            input.consume();
-                        }
-                    }
+                    input.consume_while(|c| c != '\n');
+                    input.consume(); // the newline ends the comment
                     return Ok(Token::new(TokenType::Whitespace, start, input.pos()));
                 }
                 if next == '*' {
@@ -335,7 +489,7 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
                 return Ok(Token::new_op(slu, start, input.pos()));
             }
             // < or <=
-            Ok(Token::new_op(lex_op(input, Operator::Gt, Operator::Gte),
+            Ok(Token::new_op(lex_op(input, Operator::Lt, Operator::Lte),
                              start, input.pos()))
         }
         '>' => { // >, >=, >>, >>>, >>=
@@ -350,7 +504,7 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
                 return Ok(Token::new_op(sru, start, input.pos()));
             }
             // > or >=
-            Ok(Token::new_op(lex_op(input, Operator::Lt, Operator::Lte),
+            Ok(Token::new_op(lex_op(input, Operator::Gt, Operator::Gte),
                              start, input.pos()))
         }
         '!' => { // !, !=
@@ -358,20 +512,17 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
             Ok(Token::new_op(lex_op(input, Operator::Not, Operator::Neq),
                              start, input.pos()))
         }
-        // binary and hex literals
+        // binary, octal and hex literals
         '0' => {
-            if let Some(next) = input.peek_at(1) {
-                if next == 'x' {
-                    // get the number
-                    return lex_hex_lit(input);
-                }
-                if next == 'b' {
-                    // get the number
-                    return lex_bin_lit(input);
-                }
+            match input.peek_at(1) {
+                Some('x') => lex_radix_lit(input, 16, TokenType::HexLit),
+                Some('o') => lex_radix_lit(input, 8, TokenType::OctLit),
+                Some('b') => lex_radix_lit(input, 2, TokenType::BinLit),
+                _ => lex_numeric(input)
             }
-            return Ok(Token::new_lit(TokenType::NumberLit, next_numeric(input)?, start, input.pos()));
         }
+        // raw strings
+        'r' if matches!(input.peek_at(1), Some('"' | '#')) => lex_raw_string(input),
         // identifiers and numbers
         _ => {
             if next.is_alphabetic() || next == '_' { // identifiers
@@ -380,16 +531,25 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
                     // check for keywords
                     "fn" => Ok(Token::new(TokenType::Fn, start, input.pos())),
                     "let" => Ok(Token::new(TokenType::Let, start, input.pos())),
+                    "const" => Ok(Token::new(TokenType::Const, start, input.pos())),
+                    "static" => Ok(Token::new(TokenType::Static, start, input.pos())),
+                    "mut" => Ok(Token::new(TokenType::Mut, start, input.pos())),
+                    "pub" => Ok(Token::new(TokenType::Pub, start, input.pos())),
                     "if" => Ok(Token::new(TokenType::If, start, input.pos())),
                     "else" => Ok(Token::new(TokenType::Else, start, input.pos())),
                     "while" => Ok(Token::new(TokenType::While, start, input.pos())),
                     "for" => Ok(Token::new(TokenType::For, start, input.pos())),
                     "loop" => Ok(Token::new(TokenType::Loop, start, input.pos())),
+                    "break" => Ok(Token::new(TokenType::Break, start, input.pos())),
+                    "continue" => Ok(Token::new(TokenType::Continue, start, input.pos())),
                     "return" => Ok(Token::new(TokenType::Return, start, input.pos())),
                     "match" => Ok(Token::new(TokenType::Match, start, input.pos())),
                     "struct" => Ok(Token::new(TokenType::Struct, start, input.pos())),
+                    "impl" => Ok(Token::new(TokenType::Impl, start, input.pos())),
+                    "enum" => Ok(Token::new(TokenType::Enum, start, input.pos())),
                     "assert" => Ok(Token::new(TokenType::Assert, start, input.pos())),
                     "in" => Ok(Token::new(TokenType::In, start, input.pos())),
+                    "as" => Ok(Token::new(TokenType::As, start, input.pos())),
                     "use" => Ok(Token::new(TokenType::Use, start, input.pos())),
                     "true" => Ok(Token::new(TokenType::BoolTrue, start, input.pos())),
                     "false" => Ok(Token::new(TokenType::BoolFalse, start, input.pos())),
@@ -397,7 +557,7 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
                 }
             }
             return if next.is_numeric() { // handle numeric literals
-                Ok(Token::new_lit(TokenType::NumberLit, next_numeric(input)?, start, input.pos()))
+                lex_numeric(input)
             } else {
                 Err(Error::new(
                     "Unexpected character",
@@ -407,12 +567,67 @@ pub fn next_token(input: &mut InputReader) -> Result<Token, Error> {
     }
 }
 
-pub fn lex(input: &mut InputReader) -> Result<TokenList, Error> {
+/// skips the rest of a token that failed to lex, so lexing can continue after it without the
+/// rest of a broken literal being read as more tokens
+fn recover(input: &mut InputReader, first: char, start: usize) {
+    match first {
+        // the rest of a string, up to its closing quote
+        '"' => {
+            while let Some(c) = input.consume() {
+                match c {
+                    '\\' => {
+                        input.consume();
+                    }
+                    '"' => break,
+                    _ => {}
+                }
+            }
+        }
+        // char literals can't span lines, so a missing quote only skips to the end of the line
+        '\'' => {
+            input.consume_while(|c| c != '\'' && c != '\n');
+            if input.peek() == Some('\'') {
+                input.consume();
+            }
+        }
+        // the rest of a number with an invalid digit or suffix
+        c if c.is_ascii_digit() => input.consume_while(|c| c.is_alphanumeric() || c == '_'),
+        _ => {}
+    }
+    // always move past at least the character that caused the error
+    if input.offset() == start {
+        input.consume();
+    }
+}
+
+/// lexes the whole input. Errors don't stop the lexer, so every one in the file is returned
+pub fn lex(input: &mut InputReader) -> Result<TokenList, Vec<Error>> {
     let mut tokens: Vec<Token> = Vec::new();
+    let mut errors = Vec::new();
 
-    while let Some(_) = input.peek() {
+    while let Some(first) = input.peek() {
+        let start = input.offset();
         // Process the next token
-        tokens.push(next_token(input)?); // push the next token into the list
+        let token = match next_token(input) {
+            Ok(token) => token,
+            Err(e) => {
+                errors.push(e);
+                recover(input, first, start);
+                continue;
+            }
+        };
+        // whitespace next to a comment is merged with it, so the parser never sees two in a row
+        if token.token_type == TokenType::Whitespace {
+            if let Some(last) = tokens.last_mut().filter(|t| t.token_type == TokenType::Whitespace) {
+                last.end = token.end;
+                continue;
+            }
+        }
+        tokens.push(token); // push the next token into the list
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
     }
 
     // add an EOF token for the parser
@@ -420,4 +635,4 @@ pub fn lex(input: &mut InputReader) -> Result<TokenList, Error> {
 
     // return the list of tokens in a TokenList wrapper for use in the parser
     Ok(TokenList::new(tokens))
-}
\ No newline at end of file
+}
@@ -1,5 +1,3 @@
-#![feature(core_intrinsics)]
-
 mod argument_parser;
 pub mod input_reader;
 pub mod lexer;
@@ -7,28 +5,35 @@ pub mod token;
 pub mod operator;
 pub mod error;
 mod parser;
+mod module;
 pub mod statement;
 mod gen_c;
 mod interpreter;
+mod checker;
+mod types;
+mod type_checker;
 
 use std::{env, fs};
 use std::fmt::Display;
-use std::fs::{File, OpenOptions};
+use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use better_term::{Color, flush_styles};
 use cli_tree::TreeNode;
 use crate::argument_parser::{Argument, parse_args};
-use crate::error::{Error, print_error};
-use crate::gen_c::generate_c_code;
+use crate::checker::check_program;
+use crate::error::{DEFAULT_ERROR_LIMIT, Error, print_compiler_error, print_errors};
+use crate::gen_c::{generate_c_code, CodegenOptions, Target};
 use crate::input_reader::InputReader;
-use crate::interpreter::interpret;
+use crate::interpreter::{interpret, print_runtime_error, InterruptTable};
 use crate::lexer::lex;
+use crate::module::Modules;
 use crate::parser::parse;
 use crate::token::TokenList;
+use crate::type_checker::type_check;
 
 fn round(value: f64, place: usize) -> f64 {
-    let round_by = 10.0f64.powi(place as i32) as f64;
+    let round_by = 10.0f64.powi(place as i32);
     (value * round_by).round() / round_by
 }
 
@@ -44,15 +49,907 @@ fn time_taken<T, F: FnMut() -> T>(mut f: F) -> (T, f64) {
 
 #[cfg(test)]
 mod tests {
-    use crate::{CodePos, InputReader, time_taken, TokenList};
+    use crate::{time_taken, InputReader};
+    use crate::checker::check_program;
+    use crate::gen_c::{generate_c_code, CodegenOptions};
+    use crate::interpreter::{interpret, InterruptTable, Value};
     use crate::lexer::lex;
-    use crate::token::{Token, TokenType};
+    use crate::module::Modules;
+    use crate::parser::parse;
+    use crate::token::TokenType;
+    use crate::type_checker::type_check;
+
+    fn run(code: &str) -> Value {
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let ast = parse(&mut tokens).unwrap();
+        interpret(&ast, InterruptTable::default()).unwrap()
+    }
 
     #[test]
     fn le_testing_zone() {
 
     }
 
+    #[test]
+    fn interpret_start() {
+        let code = "fn fib(n: int) -> int {\n\
+                        if n < 2 {\n return n\n }\n\
+                        return fib(n - 1) + fib(n - 2)\n\
+                    }\n\
+                    fn start() -> int {\n\
+                        let total: int = 0\n\
+                        let i: int = 0\n\
+                        while i < 10 {\n i += 1\n total = total + i\n }\n\
+                        for j in 0..=4 {\n total += j\n }\n\
+                        return total + fib(10) - (0x10 >>> 2)\n\
+                    }";
+        assert_eq!(run(code), Value::Integer(55 + 10 + 55 - 4));
+
+        // the target of `op=` is only evaluated once
+        let code = "fn next(n: *int) -> int {\n *n += 1\n return *n\n }\n\
+                    fn start() -> int {\n let a = [10, 20, 30]\n let i = 0\n a[next(&i)] += 5\n return a[1] * 10 + i\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(251));
+        let c = generate_c_code(&mut ast, &CodegenOptions { release: true, ..CodegenOptions::default() }).unwrap();
+        assert!(c.contains("(*n) += 1;") && c.contains("a.items[next((&i))] += 5;"), "{}", c);
+    }
+
+    #[test]
+    fn check_undefined() {
+        let code = "fn add(a: int, b: int) -> int {\n return a + b\n }\n\
+                    fn start() {\n let x: int = add(1)\n return add(x, y)\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors = check_program(&mut ast).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].core_msg.as_deref(), Some("Wrong number of arguments"));
+        assert_eq!((errors[0].pos.line, errors[0].pos.ch), (5, 15));
+        assert_eq!(errors[1].core_msg.as_deref(), Some("Undefined identifier"));
+        assert_eq!((errors[1].pos.line, errors[1].pos.ch), (6, 16));
+    }
+
+    #[test]
+    fn type_mismatch() {
+        let code = "fn inc(n: u8) -> u8 {\n return n + 1\n }\n\
+                    fn start() {\n let a: u8 = inc(true)\n let b: bool = a\n if a {\n }\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors = type_check(&mut ast, true).unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|e| e.pos.line).collect();
+        assert_eq!(lines, vec![5, 6, 7]);
+        assert!(errors.iter().all(|e| e.core_msg.as_deref() == Some("Mismatched types")));
+
+        let code = "fn h() -> int {\n }\nfn g(x: bool) -> int {\n if x {\n return 1\n }\n }\n\
+                    fn both(x: bool) -> int {\n if x {\n return 1\n } else {\n ? x\n }\n }\n\
+                    fn spin() -> int {\n 'outer: loop {\n while true {\n break\n }\n }\n }\n\
+                    fn leave() -> int {\n 'outer: loop {\n while true {\n break 'outer\n }\n }\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors = type_check(&mut ast, true).unwrap_err();
+        let found: Vec<(&str, usize)> = errors.iter().map(|e| (e.core_msg.as_deref().unwrap(), e.pos.line)).collect();
+        assert_eq!(found, [("Missing return", 1), ("Missing return", 3), ("Missing return", 22)]);
+    }
+
+    #[test]
+    fn infer_declarations() {
+        let code = "fn half(n: u16) -> u16 {\n return n / 2\n }\n\
+                    fn start() {\n let a = 0b0101\n let b: u8 = 2\n let c = b + 1\n let d = a < 3\n\
+                    let e = half(8)\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["int a = 0b101;", "uint8_t c = ((uint8_t)(b + 1));", "bool d = (a < 3);", "uint16_t e = half(8);"] {
+            assert!(c.contains(line), "{}", c);
+        }
+    }
+
+    #[test]
+    fn structs() {
+        let code = "fn width(r: Rect) -> int {\n return r.max.x - r.min.x\n }\n\
+                    struct Point { x: int, y: int }\n\
+                    struct Rect { min: Point, max: Point }\n\
+                    fn start() -> int {\n let p = Point { x: 1, y: 2 }\n let q = p\n q.x = 5\n\
+                    let r = Rect { min: p, max: q }\n r.max.x += 1\n return width(r) * 10 + p.x\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(51));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        assert!(c.find("} Point;").unwrap() < c.find("} Rect;").unwrap(), "{}", c);
+        assert!(c.contains("Point p = { .x = 1, .y = 2 };"), "{}", c);
+
+        let code = "struct A { b: B }\nstruct B { a: [A; 2], next: *B }\nstruct C { c: C }\nstruct D { a: *D, b: B }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors = check_program(&mut ast).unwrap_err();
+        let found: Vec<(&str, usize)> = errors.iter().map(|e| (e.core_msg.as_deref().unwrap(), e.pos.line)).collect();
+        assert_eq!(found, [("Recursive struct", 1), ("Recursive struct", 3)]);
+        assert!(errors[0].msg.starts_with("`A` holds itself by value (`A` -> `B` -> `A`)"), "{}", errors[0].msg);
+    }
+
+    #[test]
+    fn methods() {
+        let code = "struct Counter { count: int }\n\
+                    fn start() -> int {\n let c = Counter { count: 1 }\n c.add(2)\n let pc = &c\n pc.add(4)\n\
+                        return pc.get() * 10 + c.get()\n }\n\
+                    impl Counter {\n\
+                        fn get(&self) -> int {\n return self.count\n }\n\
+                        fn add(&self, n: int) {\n self.count = self.get() + n\n }\n\
+                    }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(77));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["void Counter__add(Counter* self, int n);", "Counter__add(&c, 2);", "Counter__add(pc, 4);",
+                     "self->count = (Counter__get(self) + n);"] {
+            assert!(c.contains(line), "{}", c);
+        }
+    }
+
+    #[test]
+    fn enums_and_match() {
+        let code = "enum Color { Red, Green = 4, Blue }\n\
+                    fn score(c: Color) -> int {\n return match c {\n Color::Red => 1\n Color::Green => 2\n Color::Blue => 3\n }\n }\n\
+                    fn start() -> int {\n let total = score(Color::Blue)\n let n: u8 = 200\n\
+                    match n {\n 0..=9 => total = total + 10\n 10..100 => total = total + 20,\n _ => {\n total = total + 30\n }\n }\n\
+                    match Color::Green {\n Color::Green => total = total * 2\n _ => total = 0\n }\n return total\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(66));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["typedef enum Color {\nColor__Red,\nColor__Green = 4,\nColor__Blue,\n} Color;",
+                     "((c == Color__Red) ? 1 : ((c == Color__Green) ? 2 : 3))",
+                     "if (__match_value >= 0 && __match_value <= 9) total = (total + 10);",
+                     "switch (Color__Green) {\ncase Color__Green: total = (total * 2); break;"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        // a match on a call or with a block arm can't be a conditional, so it is stored or returned by each arm
+        let code = "fn next(n: int) -> int {\n return n + 1\n }\n\
+                    fn pick(n: int) -> int {\n return match next(n) {\n 1 => 10\n 2 => {\n let y = 7\n y * 3\n }\n _ => 99\n }\n }\n\
+                    fn start() -> int {\n let a = match next(0) {\n 0..5 => 4\n _ => 5\n }\n\
+                        a = match a {\n 4 => {\n a * 2\n }\n _ => 0\n }\n return pick(0) * 10000 + pick(1) * 100 + pick(5) + a\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(102199 + 8));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["switch (next(n)) {\ncase 1: return 10; break;\ncase 2: {\nint y = 7;\nreturn (y * 3);\n} break;\ndefault: return 99; break;\n}",
+                     "int a;\n{\nint __match_value = next(0);\nif (__match_value >= 0 && __match_value < 5) a = 4; else a = 5;\n}",
+                     "switch (a) {\ncase 4: {\na = (a * 2);\n} break;\ndefault: a = 0; break;\n}"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "fn start() {\n let n: u8 = 3\n match n {\n 0..200 => n = 1\n 201..=255 => n = 2\n }\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors = type_check(&mut ast, true).unwrap_err();
+        assert_eq!(errors[0].core_msg.as_deref(), Some("Non-exhaustive match"));
+        assert_eq!(errors[0].msg, "`200` is not matched");
+    }
+
+    #[test]
+    fn interrupts() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+        use crate::gen_c::Target;
+
+        let code = "fn start() {\n @0x80\n for i in 0..2 {\n @3\n }\n @0b101\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+
+        let raised = Rc::new(RefCell::new(Vec::new()));
+        let log = raised.clone();
+        let mut interrupts = InterruptTable {
+            fallback: Some(Box::new(move |vector| {
+                log.borrow_mut().push(vector.to_string());
+                Ok(())
+            })),
+            ..Default::default()
+        };
+        let log = raised.clone();
+        interrupts.handlers.insert(0x80, Box::new(move |_| {
+            log.borrow_mut().push("syscall".to_string());
+            Ok(())
+        }));
+        interpret(&ast, interrupts).unwrap();
+        assert_eq!(*raised.borrow(), vec!["syscall", "3", "3", "5"]);
+        assert!(interpret(&ast, InterruptTable::default()).is_err());
+
+        for (target, asm) in [(Target::X86, "__asm__ volatile (\"int $128\");"),
+                              (Target::Arm, "__asm__ volatile (\"svc #5\");"),
+                              (Target::RiscV, "__asm__ volatile (\"li a7, 5\\n\\tecall\" ::: \"a7\");")] {
+            let c = generate_c_code(&mut ast, &CodegenOptions { target, ..Default::default() }).unwrap();
+            assert!(c.contains(asm), "{}", c);
+        }
+    }
+
+    #[test]
+    fn panics() {
+        let code = "fn check(n: int) {\n assert n < 10\n if n == 5 {\n ? n * 2\n }\n }\n\
+                    fn panic_hook(message: string) {\n @0x01\n }\n\
+                    fn start() {\n check(1)\n check(5)\n }";
+        let mut tokens = lex(&mut InputReader::new(Some("main.rock".to_string()), code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        // the hook raises an interrupt, so it can be seen here
+        let hooked = std::rc::Rc::new(std::cell::Cell::new(false));
+        let seen = hooked.clone();
+        let interrupts = InterruptTable {
+            fallback: Some(Box::new(move |_| {
+                seen.set(true);
+                Ok(())
+            })),
+            ..Default::default()
+        };
+        let err = interpret(&ast, interrupts).unwrap_err();
+        assert_eq!(err.msg, "panicked at main.rock:4:2: 10");
+        assert_eq!(err.trace, vec!["check", "start"]);
+        assert!(hooked.get());
+
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["if (!(n < 10)) boulder_panic(\"assertion failed at main.rock:2:2\");",
+                     "boulder_panic(\"panicked at main.rock:4:2: %lld\", (long long)((n * 2)));",
+                     "panic_hook(message);\nabort();"] {
+            assert!(c.contains(line), "{}", c);
+        }
+        let c = generate_c_code(&mut ast, &CodegenOptions { release: true, ..Default::default() }).unwrap();
+        assert!(!c.contains("assertion failed"), "{}", c);
+
+        let code = "fn start() {\n let x: u8 = 3\n assert x == 4\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap_err().msg, "assertion failed at code:3:2");
+    }
+
+    #[test]
+    fn spans() {
+        use crate::statement::Statement;
+
+        let code = "fn start() {\n let x: u8 = 3\n let y: bool = x + 1\n x = x / (x - 3)\n @0x1FF\n }";
+        let source = |span: &crate::Span| &code[span.start.offset..span.end];
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let body = match &ast {
+            Statement::Program { exprs, .. } => match &exprs[0] {
+                Statement::Fn { body, span, .. } => {
+                    assert_eq!(source(span), code);
+                    body
+                }
+                _ => panic!("expected a function")
+            },
+            _ => unreachable!()
+        };
+        let exprs = match body.as_ref() {
+            Statement::Block { exprs, .. } => exprs,
+            _ => panic!("expected a block")
+        };
+        assert_eq!(source(exprs[0].span().unwrap()), "let x: u8 = 3");
+        assert_eq!(exprs[2].span().unwrap().start.line, 4);
+
+        let errors = type_check(&mut ast.clone(), true).unwrap_err();
+        assert_eq!(errors[0].core_msg.as_deref(), Some("Mismatched types"));
+        assert_eq!(&code[errors[0].pos.offset..errors[0].end], "x + 1");
+
+        // without the mismatched declaration, it runs until it divides by zero
+        if let Statement::Program { exprs, .. } = &mut ast {
+            if let Statement::Fn { body, .. } = &mut exprs[0] {
+                if let Statement::Block { exprs, .. } = body.as_mut() {
+                    exprs.remove(1);
+                }
+            }
+        }
+        type_check(&mut ast, true).unwrap();
+        let err = interpret(&ast, InterruptTable::default()).unwrap_err();
+        assert_eq!(err.msg, "Attempted to divide by zero");
+        assert_eq!(source(err.span.as_ref().unwrap()), "x / (x - 3)");
+
+        let err = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap_err();
+        assert_eq!(source(err.span.as_ref().unwrap()), "@0x1FF");
+    }
+
+    #[test]
+    fn error_recovery() {
+        let code = "fn add(a: int, b: int) -> int {\n let x = a + + b\n let y: = 5\n return x\n }\n\
+                    struct S {\n a int\n }\n\
+                    fn start() {\n let z = add(1, 2)\n if z == 3 {\n let q = ]\n }\n z = 4;\n let w = ) }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let errors = parse(&mut tokens).unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|e| e.pos.line).collect();
+        assert_eq!(lines, vec![2, 3, 7, 12, 15], "{:?}", errors);
+
+        // a block missing its '}' ends at the next function, which is still parsed
+        let code = "fn a() {\n let x = 1\n fn b() {\n let y = )\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let errors = parse(&mut tokens).unwrap_err();
+        let found: Vec<(&str, usize)> = errors.iter().map(|e| (e.core_msg.as_deref().unwrap(), e.pos.line)).collect();
+        assert_eq!(found, vec![("Expected closing '}'", 3), ("Expected an expression", 4)]);
+
+        let code = "let s = \"bad \\q\" $ 'ab' 0b102 # 3";
+        let errors = lex(&mut InputReader::new(None, code)).unwrap_err();
+        let columns: Vec<usize> = errors.iter().map(|e| e.pos.ch).collect();
+        assert_eq!(columns, vec![15, 18, 22, 29, 31], "{:?}", errors);
+    }
+
+    #[test]
+    fn escapes() {
+        let code = "fn start() -> string {\n let q = '\\''\n let n = '\\n'\n if q == '\\x27' && n != 'a' {\n \
+                    return \"a\\\"b\\tc\\\\\\u{1F600}\\0\" \n }\n return r#\"raw \\n \"quoted\"\"#\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::String("a\"b\tc\\\u{1F600}\0".to_string()));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["char q = '\\'';", "char n = '\\n';", "return \"a\\\"b\\tc\\\\\u{1F600}\\000\";",
+                     "return \"raw \\\\n \\\"quoted\\\"\";"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        for (code, ch) in [("\"ab\\qc\"", 5), ("'\\x4G'", 5), ("\"\\u{11FFFF}\"", 5), ("'ab'", 3)] {
+            let err = &lex(&mut InputReader::new(None, code)).unwrap_err()[0];
+            assert_eq!((err.pos.line, err.pos.ch), (1, ch), "{}", code);
+        }
+    }
+
+    #[test]
+    fn floats() {
+        let tokens = lex(&mut InputReader::new(None, "1.5 2e-3 0..3 p.x")).unwrap();
+        let types: Vec<TokenType> = tokens.tokens.iter().map(|t| t.token_type.clone())
+            .filter(|t| *t != TokenType::Whitespace).collect();
+        assert_eq!(&types[..7], &[TokenType::FloatLit, TokenType::FloatLit, TokenType::NumberLit,
+            TokenType::Operator, TokenType::NumberLit, TokenType::Ident, TokenType::Dot]);
+
+        let code = "fn half(x: f32) -> f32 {\n return x / 2.0\n }\n\
+                    fn start() -> f64 {\n let h: f32 = half(3.0)\n let d = 1.5e1\n if h < 2.0 {\n return d - 0.25\n }\n return -d\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Float(14.75));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["float half(float x)", "double start()", "double d = 1.5e1;"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors = type_check(&mut ast, false).unwrap_err();
+        assert_eq!((errors[0].pos.line, errors[0].pos.ch), (1, 12));
+
+        // f32 math is rounded to an f32 after every operation, so adding 1 to 2^24 does nothing
+        let code = "fn start() -> bool {\n let a: f32 = 16777216.0\n return a + 1.0 == a\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Bool(true));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        assert!(c.contains("float a = 16777216.0f;") && c.contains("return ((a + 1.0f) == a);"), "{}", c);
+    }
+
+    #[test]
+    fn integer_literals() {
+        let code = "fn start() -> u64 {\n let a = 0xFF_u16\n let b: u8 = 0b1010_1010\n let c = 0o17\n\
+                    let d = 1_000_000u64\n return d + 0o17 + c + b + a\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        assert!(type_check(&mut ast, true).is_err());
+        let code = code.replace(" + c + b + a", "");
+        let mut tokens = lex(&mut InputReader::new(None, &code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(1_000_015));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["uint16_t a = ((uint16_t)0xFF);", "uint8_t b = 0b10101010;", "int c = 017;",
+                     "uint64_t d = ((uint64_t)1000000);"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "fn f(n: u16) {\n }\n\
+                    fn start() {\n let x: u8 = 300\n let y = 5000000000\n let z = 256u8\n f(-1)\n let w = x + 0x100\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors = type_check(&mut ast, true).unwrap_err();
+        let lines: Vec<usize> = errors.iter().map(|e| e.pos.line).collect();
+        assert_eq!(lines, vec![4, 5, 6, 7, 8]);
+        assert!(errors.iter().all(|e| e.core_msg.as_deref() == Some("Literal out of range")), "{:?}", errors);
+
+        for (code, ch) in [("0b102", 5), ("10q", 3), ("0x", 3), ("1.5u8", 4)] {
+            let err = &lex(&mut InputReader::new(None, code)).unwrap_err()[0];
+            assert_eq!((err.pos.line, err.pos.ch), (1, ch), "{}", code);
+        }
+    }
+
+    #[test]
+    fn integer_overflow() {
+        // each line wraps the way the same code does in C, which the interpreter has to match
+        let code = "fn start() -> bool {\n let a: u8 = 250\n a += 10\n let b: u8 = 0\n b -= 1\n\
+                    let c: u16 = 65535\n c = c * c\n let d: u32 = 0\n d--\n let e = 18446744073709551615u64 > 1u64\n\
+                    let f: i8 = 127\n f++\n let g: i16 = -32768\n g = -g\n let h: int = 2147483647\n h += 1\n\
+                    let i: i64 = 9223372036854775807\n i += 1\n let j: u8 = 7\n\
+                    return a == 4 && b == 255 && c == 1 && d == 4294967295 && e && f == -128 && g == -32768 &&\
+                    h == -2147483648 && i < 0 && (j + 250) < 5 && d / 2 == 2147483647 && !j == 248\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Bool(true));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["a += 10;", "c = ((uint16_t)((uint32_t)c * c));", "g = ((int16_t)(-g));",
+                     "h += 1;", "(((uint8_t)(j + 250)) < 5)", "(((uint8_t)(~j)) == 248)"] {
+            assert!(c.contains(line), "{}", c);
+        }
+    }
+
+    #[test]
+    fn lex_benchmark() {
+        // 100,000 lines of code, which should lex in well under a second even in debug builds
+        let function = "// adds up the numbers below n\n\
+                        fn sum(n: u32) -> u32 {\n    let total: u32 = 0x0\n    let i = 0\n\
+                        \x20   while i < n {\n        total += i; i += 1\n    }\n    return total\n}\n\n";
+        let code = function.repeat(100_000 / function.lines().count());
+        assert!(code.lines().count() >= 100_000);
+        let (tokens, millis) = time_taken(|| lex(&mut InputReader::new(None, code.as_str())));
+        let tokens = tokens.unwrap();
+        assert!(millis < 1000.0, "lexing took {}ms", millis);
+
+        // whitespace runs and comments are one token, and the last line's position is still known
+        assert!(tokens.tokens.windows(2).all(|w| w[0].token_type != TokenType::Whitespace ||
+            w[1].token_type != TokenType::Whitespace));
+        let eof = tokens.eof();
+        assert_eq!((eof.line, eof.offset), (code.lines().count() + 1, code.len()));
+    }
+
+    #[test]
+    fn gen_c_postfix() {
+        let code = "fn f(a: int, b: int) -> bool {\n return !(a == b) && (-a < b >>> 1)\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        assert!(c.contains("return ((!(a == b)) && ((-a) < ((uint64_t)(b) >> 1)))"), "{}", c);
+
+        // `>>>` shifts in the width of the value it shifts
+        let code = "fn start() -> int {\n let n = -8\n return n >>> 1\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(0x7FFFFFFC));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        assert!(c.contains("return ((int)((uint32_t)(n) >> 1));"), "{}", c);
+    }
+
+    #[test]
+    fn pratt_precedence() {
+        let code = "fn double(n: int) -> int {\n return n * 2\n }\n\
+                    fn start() -> int {\n\
+                        let i = 5\n\
+                        let a = 2 + 3 * 4 - 10 / 5 - 1\n\
+                        let b = 1 << 2 + 1\n\
+                        let c = -double(i) + (300 as u8) as int\n\
+                        let d = i++\n d += ++i\n\
+                        if 1 + 1 == 2 && !(i < 7) {\n return a * 1000 + b * 100 + c + d\n }\n\
+                        return 0\n\
+                    }";
+        assert_eq!(run(code), Value::Integer(11 * 1000 + 8 * 100 + 34 + 12));
+
+        let code = "fn f(a: int, b: int, c: bool) -> u8 {\n if a < b + 1 == c {\n return -a as u8\n }\n return 0\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        assert!(c.contains("if (((a < (b + 1)) == c))"), "{}", c);
+        assert!(c.contains("return ((uint8_t)(-a))"), "{}", c);
+
+        // C doesn't order a change made by `++` or `--` against another use of the same place
+        let code = "struct P { x: int, y: int }\n\
+                    fn start() {\n let i = 0\n let p = P { x: 0, y: 0 }\n let a: [int; 2] = [0, 0]\n\
+                        let b = i++ + i\n let c = p.x++ + p.y++\n a[i] = i++\n let d = i > 0 && i++ > 1\n p.x = p.x++\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| format!("{} {}", e.core_msg.unwrap(), e.pos)).collect();
+        assert_eq!(errors, ["Unsequenced change code:6:9", "Unsequenced change code:8:9", "Unsequenced change code:10:8"]);
+    }
+
+    #[test]
+    fn default_args() {
+        let code = "fn pick(a: int, b: u8 = 2, c: bool = true) -> int {\n if c {\n return a + b as int\n }\n return a\n }\n\
+                    fn start() -> int {\n return pick(10) * 100 + pick(1, c = false) * 10 + pick(b = 7, a = 0)\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(1200 + 10 + 7));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["int pick(int a, uint8_t b, bool c)", "pick(10, 2, true)", "pick(1, 2, false)", "pick(0, 7, true)"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "fn f(a: int, b: int = 1) {\n }\n\
+                    fn start() {\n f()\n f(1, 2, 3)\n f(b = 2)\n f(1, a = 2)\n f(1, c = 2)\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Wrong number of arguments", "Wrong number of arguments", "Missing arguments",
+                            "Duplicate argument", "Unknown argument"]);
+
+        // a default value refers to the items where the function is defined, and is read when the function is called
+        let code = "static mut N: int = 3\nfn f(b: int = N) -> int {\n return b\n }\n\
+                    fn start() -> int {\n N = 4\n return f()\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(4));
+        let code = "static mut N: int = 3\nfn f(b: int = N) -> int {\n return b\n }\n\
+                    fn start() -> int {\n let N = 5\n return f() + f(1)\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Hidden default value"]);
+
+        // methods take named arguments and default values too
+        let code = "struct V { n: int }\nimpl V {\n fn bump(&self, k: int = 1, times: int = 1) {\n self.n += k * times\n }\n }\n\
+                    fn start() -> int {\n let v = V { n: 0 }\n v.bump()\n v.bump(10)\n v.bump(times = 3)\n v.bump(k = 100, times = 2)\n\
+                        return v.n\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(1 + 10 + 3 + 200));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["V__bump(&v, 1, 1);", "V__bump(&v, 10, 1);", "V__bump(&v, 1, 3);", "V__bump(&v, 100, 2);"] {
+            assert!(c.contains(line), "{}", c);
+        }
+        let code = "struct V { n: int }\nimpl V {\n fn bump(&self, k: int) {\n }\n }\n\
+                    fn start() {\n let v = V { n: 0 }\n v.bump()\n v.bump(1, 2)\n v.bump(j = 1)\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        let errors: Vec<String> = type_check(&mut ast, true).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Wrong number of arguments", "Wrong number of arguments", "Unknown argument", "Missing arguments"]);
+    }
+
+    #[test]
+    fn globals() {
+        let code = "const SIZE: u8 = 4 * 2\nconst MASK: u8 = (1 << SIZE - 5) - 1 as u8\nstatic mut COUNT: int = SIZE as int\n\
+                    fn start() -> int {\n COUNT += 1\n return COUNT * 100 + MASK as int\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(907));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["static const uint8_t SIZE = ((uint8_t)8);", "static const uint8_t MASK = ((uint8_t)7);",
+                     "static int COUNT = ((int)8);",
+                     "return ((COUNT * 100) + ((int)((uint8_t)7)))"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        // arrays and structs can be globals, and a const holding one is used by name
+        let code = "const N: int = 3\nstruct Point { x: int, y: u8 }\nconst TABLE: [u8; 4] = [1, 2, 4, 8]\n\
+                    static mut BUF: [int; N] = [0; N]\nconst ORIGIN: Point = Point { x: N * 2, y: 7 }\n\
+                    fn start() -> int {\n BUF[1] = 5\n return TABLE[3] as int + BUF[1] * 10 + ORIGIN.x * 100 + ORIGIN.y as int * 1000\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(7658));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["static const boulder_array_u8_4 TABLE = {{((uint8_t)1), ((uint8_t)2), ((uint8_t)4), ((uint8_t)8)}};",
+                     "static boulder_array_int_3 BUF = {0};", "static const Point ORIGIN = { .x = 6, .y = 7 };",
+                     "TABLE.items[boulder_index(3, 4, \"code:8:9\")]"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "const A: u8 = B + 1\nconst B: u8 = A\nconst C: u8 = 256\nstatic D: int = 1\n\
+                    fn start() {\n let x = 1\n D = 2\n C += 1\n }\nconst E: int = start()\n\
+                    const F: [int; 2] = [1, start()]";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Recursive constant", "Constant out of range", "Assignment to an immutable static",
+                            "Assignment to a constant", "Not a constant", "Not a constant"]);
+        let code = "const G: [u8; 2] = [1, 300]";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        let errors: Vec<String> = type_check(&mut ast, true).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Literal out of range"]);
+
+        // a constant is worked out in its operands' type like it is at runtime, so it can't overflow in between
+        let code = "const A: u8 = 16\nconst B: u8 = A * A / 2\nconst C: u8 = (200 + 100) / 2\nconst D: int = A as int * A as int\n\
+                    const E: u8 = !0\nconst F: i8 = -1 >>> 4\nconst G: u8 = 1 - A";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| format!("{} {}", e.core_msg.unwrap(), e.pos)).collect();
+        assert_eq!(errors, ["Constant out of range code:2:15", "Constant out of range code:3:16", "Constant out of range code:7:15"]);
+        let code = "const A: u8 = 16\nconst D: int = A as int * A as int\nconst E: u8 = !0\nconst F: i8 = -1 >>> 4\n\
+                    fn start() -> int {\n return D * 1000 + E as int + F as int\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(256 * 1000 + 255 + 15));
+    }
+
+    #[test]
+    fn modules() {
+        let dir = std::env::temp_dir().join("boulder_modules_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let files = [
+            ("uart.rock", "pub fn init() -> int {\n return helper() + 1\n }\nfn helper() -> int {\n return LEVEL\n }\n\
+                           pub const LEVEL: int = 4"),
+            ("util.rock", "use \"uart.rock\"\npub fn twice() -> int {\n return init() * 2\n }"),
+            ("spi.rock", "pub fn init() -> int {\n return helper() * 2\n }\nfn helper() -> int {\n return 3\n }\n\
+                          pub struct Config { speed: int }"),
+            ("main.rock", "use \"uart.rock\" as uart\nuse \"util.rock\"\nuse \"spi.rock\" as spi\n\
+                           fn helper() -> int {\n return 7000\n }\nfn speed(c: spi::Config) -> int {\n return c.speed\n }\n\
+                           fn start() -> int {\n return uart::init() * 100 + twice() + uart::LEVEL + spi::init() * 1000 + helper() \
+                               + speed(spi::Config { speed: 8 }) * 10000\n }"),
+            ("ambiguous.rock", "use \"uart.rock\"\nuse \"spi.rock\"\nfn start() -> int {\n return init()\n }"),
+            ("private.rock", "use \"uart.rock\" as uart\nfn start() {\n uart::helper()\n helper()\n uart::missing()\n init()\n }"),
+            ("a.rock", "use \"b.rock\""),
+            ("b.rock", "use \"a.rock\""),
+        ];
+        for (name, code) in files {
+            std::fs::write(dir.join(name), code).unwrap();
+        }
+        let parse_file = |name: &str| {
+            let path = dir.join(name).to_str().unwrap().to_string();
+            let code = std::fs::read_to_string(&path).unwrap();
+            let mut tokens = lex(&mut InputReader::new(Some(path), code)).unwrap();
+            parse(&mut tokens)
+        };
+
+        let mut ast = parse_file("main.rock").unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(514 + 6000 + 7000 + 80000));
+        // each file has its own namespace, so items in different files can have the same name
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["int uart_init() {\nreturn (uart_helper() + 1);", "int spi_init() {\nreturn (spi_helper() * 2);",
+                     "int helper() {", "return (uart_init() * 2);", "int speed(Config c)"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let mut ast = parse_file("private.rock").unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Private item", "Undefined function", "Unknown item", "Undefined function"]);
+        let mut ast = parse_file("ambiguous.rock").unwrap();
+        let errors = check_program(&mut ast).unwrap_err();
+        assert_eq!(errors[0].core_msg.as_deref(), Some("Ambiguous item"));
+
+        let errors = parse_file("a.rock").unwrap_err();
+        assert_eq!(errors[0].core_msg.as_deref(), Some("Import cycle"));
+        assert!(errors[0].msg.contains("b.rock:1:5 uses"), "{}", errors[0].msg);
+    }
+
+    #[test]
+    fn search_paths() {
+        let dir = std::env::temp_dir().join("boulder_search_paths_test");
+        let lib = dir.join("lib");
+        std::fs::create_dir_all(&lib).unwrap();
+        std::fs::write(lib.join("answer.rock"), "pub fn answer() -> int {\n return 42\n }").unwrap();
+        let parse_code = |code: &str| {
+            let path = dir.join("main.rock").to_str().unwrap().to_string();
+            let mut tokens = lex(&mut InputReader::new(Some(path), code)).unwrap();
+            tokens.modules = Modules::new(vec![lib.clone()]);
+            parse(&mut tokens)
+        };
+
+        let mut ast = parse_code("use \"answer.rock\"\nuse std \"print.rock\"\nfn start() -> int {\n println(\"hi\")\n \
+                                  return answer()\n }").unwrap();
+        check_program(&mut ast).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(42));
+
+        let errors = parse_code("use std \"answer.rock\"").unwrap_err();
+        assert_eq!(errors[0].core_msg.as_deref(), Some("File not found"));
+        assert!(!errors[0].msg.contains(lib.to_str().unwrap()), "{}", errors[0].msg);
+        let errors = parse_code("use \"missing.rock\"").unwrap_err();
+        let searched = [dir.join("missing.rock"), lib.join("missing.rock")];
+        assert!(searched.iter().all(|p| errors[0].msg.contains(p.to_str().unwrap())), "{}", errors[0].msg);
+    }
+
+    #[test]
+    fn loop_labels() {
+        let code = "fn start() -> int {\n let i = 0\n let found = 0\n\
+                    'outer: while i < 10 {\n i += 1\n let j = 0\n\
+                        while true {\n j += 1\n if j > i {\n continue 'outer\n }\n if i * j == 12 {\n found = i * 10 + j\n break 'outer\n }\n }\n }\n\
+                    let n = 0\n let first = loop {\n n += 1\n if n * n > 50 {\n break n\n }\n }\n\
+                    return found * 100 + first\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(4300 + 8));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["goto outer_continue", "goto outer_break", "outer_break:", "int first;\nwhile (1)", "first = n;\nbreak;"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "fn start() {\n break\n 'a: loop {\n continue 'b\n }\n while true {\n break 1\n }\n\
+                    'a: loop {\n 'a: loop {\n }\n }\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Break outside of a loop", "Undefined label", "Break with a value", "Duplicate label"]);
+
+        // a loop that gives a value can't be left without one, or the value would be uninitialized
+        let code = "fn start() -> int {\n let i = 0\n let x = loop {\n i += 1\n if i == 1 {\n break\n }\n break 5\n }\n\
+                        let y = 'a: loop {\n loop {\n break 'a\n }\n break 'a 2\n }\n return x + y\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        let errors: Vec<String> = type_check(&mut ast, true).unwrap_err().into_iter()
+            .map(|e| format!("{} {}", e.core_msg.unwrap(), e.pos)).collect();
+        assert_eq!(errors, ["Missing break value code:6:2", "Missing break value code:12:2"]);
+    }
+
+    #[test]
+    fn for_loops() {
+        let code = "const STEP: int = 3\n\
+                    fn start() -> int {\n let n: u8 = 4\n let total = 0\n\
+                        for i in 0..n {\n total += i as int\n }\n\
+                        for i in 1..=10 step STEP {\n total += i * 10\n }\n\
+                        'rows: for i in 0..5 step 2 {\n for j in 0..5 {\n if j > i {\n continue 'rows\n }\n total += 100\n }\n }\n\
+                        return total\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(6 + 220 + 900));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["uint8_t __i_end = n;", "bool __i_more = __i_next <= __i_end;", "int __i_step = 3;",
+                     "__i_more = (uint32_t)__i_end - (uint32_t)__i_next > (uint32_t)__i_step;", "goto rows_continue"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        // the range is worked out once, an inclusive range can end at the largest value, and changing the
+        // variable doesn't change how many times the loop runs
+        let code = "fn start() -> int {\n let total = 0\n let big: i64 = 9223372036854775806\n\
+                        for i in big..=9223372036854775807 {\n total += 1\n }\n\
+                        for i in 0..3 {\n i = 10\n total += 10\n }\n\
+                        let k = 3\n for i in 0..k {\n k += 1\n total += 100\n }\n return total\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(332));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["int64_t __i_end = 9223372036854775807;", "int i = __i_next;",
+                     "__i_more = (uint64_t)__i_end - (uint64_t)__i_next >= (uint64_t)1;\nif (__i_more) {\n__i_next += 1;\n}"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "fn start() {\n let n = 2\n for i in 0..10 step 0 {\n }\n for i in 0..10 step n {\n }\n\
+                    for i in 0..10 step true {\n }\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Invalid step", "Not a constant", "Invalid step"]);
+    }
+
+    #[test]
+    fn arrays() {
+        let code = "const N: int = 2\nstruct Grid { cells: [u8; N * 2] }\n\
+                    fn start() -> int {\n let a = [1, 2, 3]\n let b: [u8; N * 2] = [7; 4]\n let z = [0; 16]\n\
+                        let g = Grid { cells: [1, 2, 3, 4] }\n a[1] = 5\n b[3] += 1\n g.cells[0] = 9\n\
+                        let total = 0\n for x in a {\n total += x\n }\n for x in b step 2 {\n total += x as int * 10\n }\n\
+                        return total * 100 + a[2] + z[15] + g.cells[0] as int\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(149 * 100 + 3 + 9));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["typedef struct boulder_array_u8_4 {\nuint8_t items[4];\n} boulder_array_u8_4;", "boulder_array_u8_4 cells;",
+                     "boulder_array_int_3 a = {{1, 2, 3}};", "boulder_array_u8_4 b = {{7, 7, 7, 7}};", "boulder_array_int_16 z = {0};",
+                     "a.items[boulder_index(1, 3, \"code:8:2\")] = 5;",
+                     "for (size_t __x_index = 0; __x_index < 4; __x_index += 2)"] {
+            assert!(c.contains(line), "{}", c);
+        }
+        let c = generate_c_code(&mut ast, &CodegenOptions { release: true, ..CodegenOptions::default() }).unwrap();
+        assert!(c.contains("a.items[1] = 5;") && !c.contains("boulder_index"), "{}", c);
+
+        // arrays are values: assigning, passing and returning one copies it, and a reference to one shares it
+        let code = "fn bump(a: [int; 3]) -> [int; 3] {\n a[0] = 100\n return a\n }\n\
+                    fn sum(a: &[int; 3]) -> int {\n let total = 0\n for x in a {\n total += *x\n }\n return total\n }\n\
+                    fn start() -> int {\n let a = [1, 2, 3]\n let b = a\n b[1] = 50\n let c = bump(a)\n\
+                        let r = &a\n r[2] = 30\n return sum(&a) * 1000 + b[1] * 10 + c[0] * 100 + a[0]\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(33 * 1000 + 500 + 10000 + 1));
+        let c = generate_c_code(&mut ast, &CodegenOptions::default()).unwrap();
+        for line in ["boulder_array_int_3 bump(boulder_array_int_3 a)", "boulder_array_int_3 b = a;",
+                     "r->items[boulder_index(2, 3, \"code:18:2\")] = 30;", "int* x = &a->items[__x_index];",
+                     "for (size_t __x_index = 0; __x_index < 3; __x_index += 1)"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "fn start() -> int {\n let a = [1, 2, 3]\n let i = 3\n return a[i]\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let ast = parse(&mut tokens).unwrap();
+        let error = interpret(&ast, InterruptTable::default()).unwrap_err();
+        assert!(error.msg.starts_with("index out of bounds at code:4:9: the length is 3 but the index is 3"), "{}", error.msg);
+        let code = "fn start() {\n let a = [0; 4294967296]\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let ast = parse(&mut tokens).unwrap();
+        let error = interpret(&ast, InterruptTable::default()).unwrap_err();
+        assert_eq!(error.msg, "An array can't have 4294967296 elements");
+
+        let code = "fn start() {\n let a: [int; 0] = []\n let b = [1, true]\n let c = [0; -1]\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = check_program(&mut ast).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Invalid array size", "Invalid array size"]);
+        let code = "fn start() {\n let a = []\n let b = [1, true]\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = type_check(&mut ast, true).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Empty array", "Mismatched types"]);
+    }
+
+    #[test]
+    fn pointers() {
+        let code = "struct Point { x: u8, y: int }\nfn bump(p: *int) {\n *p = *p + 1\n }\n\
+                    fn start() -> int {\n let x = 41\n bump(&x)\n let a: [int; 4] = [1, 2, 3, 4]\n let p: *int = &a[0]\n\
+                        let q = p + 2\n *q = 30\n *(q - 1) += 10\n for v in &a {\n *v *= 2\n }\n assert p + 1 == q - 1\n\
+                        let pt = Point { x: 1, y: 2 }\n let pp = &pt\n pp.y = 7\n let addr = &pt.y as u64\n\
+                        assert addr - (pp as u64) == 4\n let back = addr as *int\n *back += 1\n\
+                        let r = 0x4000 as *u32\n *r = 5\n\
+                        return x * 100000 + (a[0] + a[1] + a[2] + a[3]) * 100 + pt.y * 10 + *r as int\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(42 * 100000 + 94 * 100 + 8 * 10 + 5));
+        let c = generate_c_code(&mut ast, &CodegenOptions { release: true, ..CodegenOptions::default() }).unwrap();
+        for line in ["(*p) = ((*p) + 1);", "bump((&x));", "int* q = (p + 2);", "pp->y = 7;",
+                     "uint32_t* r = ((uint32_t*)0x4000);"] {
+            assert!(c.contains(line), "{}", c);
+        }
+
+        let code = "fn start() {\n let x = 1\n let p = &x\n let a = 1.5 as *int\n let b = p as bool\n\
+                        let c = &(x + 1)\n let d = p * 2\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        let errors: Vec<String> = type_check(&mut ast, true).unwrap_err().into_iter()
+            .map(|e| e.core_msg.unwrap()).collect();
+        assert_eq!(errors, ["Invalid cast", "Invalid cast", "Invalid operand", "Invalid operands"]);
+
+        // memory is little-endian bytes, so a value can be read and written through a pointer to another type
+        let code = "fn start() -> int {\n let x: int = 0x1234\n let b = &x as *u8\n let lo = *b\n *(b + 1) = 0x56\n\
+                        let bytes: [u8; 2] = [0x34, 0x12]\n let h = &bytes as *u16\n *h += 1\n\
+                        return lo as int * 1000000 + x - 0x5634 + (*h as int) - 0x1235 + bytes[0] as int\n }";
+        let mut tokens = lex(&mut InputReader::new(None, code)).unwrap();
+        let mut ast = parse(&mut tokens).unwrap();
+        check_program(&mut ast).unwrap();
+        type_check(&mut ast, true).unwrap();
+        assert_eq!(interpret(&ast, InterruptTable::default()).unwrap(), Value::Integer(52 * 1000000 + 53));
+    }
+
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -60,6 +957,8 @@ pub struct CodePos {
     pub file: Option<String>,
     pub line: usize,
     pub ch: usize,
+    /// the number of bytes before this position in the file
+    pub offset: usize,
 }
 
 impl CodePos {
@@ -68,6 +967,7 @@ impl CodePos {
             file: Some(file),
             line,
             ch,
+            offset: 0,
         }
     }
 
@@ -83,11 +983,12 @@ impl CodePos {
 
 impl Default for CodePos {
     fn default() -> Self {
-        CodePos { file: None, line: 1, ch: 1 }
+        CodePos { file: None, line: 1, ch: 1, offset: 0 }
     }
 }
 
 impl Display for CodePos {
+    #[allow(clippy::unnecessary_unwrap)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.file.is_some() {
             write!(f, "{}:{}:{}", self.file.as_ref().unwrap(), self.line, self.ch)
@@ -97,6 +998,40 @@ impl Display for CodePos {
     }
 }
 
+/// A range of source code, such as a token or a statement. It starts at `start`, which knows its
+/// file, line and column, and ends just before the byte at `end`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: CodePos,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: CodePos, end: &CodePos) -> Span {
+        let end = end.offset.max(start.offset);
+        Span { start, end }
+    }
+
+    /// the span from the start of this span to the end of `other`
+    pub fn to(&self, other: &Span) -> Span {
+        Span { start: self.start.clone(), end: other.end.max(self.start.offset) }
+    }
+}
+
+/// a position on its own is a span that covers nothing
+impl From<CodePos> for Span {
+    fn from(pos: CodePos) -> Self {
+        let end = pos.offset;
+        Span { start: pos, end }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
 pub fn validate_file(file: &str) -> Result<(), String> {
     let path = Path::new(&file);
     if !path.exists() {
@@ -133,9 +1068,15 @@ fn print_help() {
         {t}{c}-c{ob}, {c}--color   {ob}[{o}true{ob}|{o}false{ob}] {c2}Set the output to be colored or not\n\
         {t}{c}-d{ob}, {c}--debug   {c2}Compile in debug mode\n\
         {t}{c}-r{ob}, {c}--release {c2}Compile in release mode\n\
+        {t}{c}-t{ob}, {c}--target  {ob}[{o}x86{ob}|{o}arm{ob}|{o}riscv{ob}] {c2}The processor to compile interrupts for\n\
+        {t}{c}--no-fpu         {c2}The target has no floating point unit, so floats are an error\n\
+        {t}{c}-e{ob}, {c}--error-limit {ob}[{o}count{ob}] {c2}The most errors to show, 0 shows every error (defaults to 20)\n\
+        {t}{c}-I{ob}, {c}--include {ob}[{o}directory{ob}] {c2}Also look for used files in the directory, after the using file's own\n\
         {t}{c}-q{ob}, {c}--quiet   {c2}No output, just compile (this will still show errors)\n\
-        {t}{c}-i{ob}, {c}--verbose {c2}All the output that is possible will appear.",
-        Color::BrightWhite, Color::BrightWhite, Color::BrightWhite, c = Color::White, c2 = Color::BrightWhite,
+        {t}{c}-i{ob}, {c}--verbose {c2}All the output that is possible will appear.\n\
+        {t}Used files are looked for next to the file using them, then in each {c}-I{c2} directory in order, then in each\n\
+        {t}directory in {c}BOULDER_PATH{c2}, then in the standard library. {c}use std \"file\"{c2} only looks in the standard library.",
+        Color::BrightWhite, Color::BrightWhite, Color::BrightWhite, Color::BrightWhite, c = Color::White, c2 = Color::BrightWhite,
         ob = Color::BrightBlack, o = Color::BrightWhite, t = "  "
     );
     flush_styles();
@@ -145,8 +1086,9 @@ fn print_version() {
     println!("{}", env!("CARGO_PKG_VERSION"));
 }
 
+#[allow(clippy::unnecessary_unwrap)]
 pub fn read_file<P: AsRef<Path>>(path: P) -> String {
-    let input_file_result = fs::read_to_string(&path.as_ref());
+    let input_file_result = fs::read_to_string(path.as_ref());
     if input_file_result.is_err() {
         println!("{}Failed to read {}: {}", Color::Red, path.as_ref().to_str().unwrap(), input_file_result.unwrap_err());
         return String::new();
@@ -154,16 +1096,20 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> String {
     input_file_result.unwrap()
 }
 
+#[allow(clippy::unnecessary_unwrap, clippy::needless_late_init)]
 fn main() {
     // parse arguments
     let mut args = env::args().collect::<Vec<String>>();
     args.remove(0);
 
     let input_file: &Path;
-    let mut color = true;
     let mut release = false;
+    let mut fpu = true;
     let mut quiet = false;
     let mut verbose = false;
+    let mut error_limit = DEFAULT_ERROR_LIMIT;
+    let mut include = Vec::new();
+    let mut options = CodegenOptions::default();
 
     // if the arguments are empty, there is nothing to do
     if args.is_empty() {
@@ -181,11 +1127,11 @@ fn main() {
     }
 
     // get the mode defined
-    let mode_in = &args.get(0).unwrap().clone();
+    let mode_in = &args.first().unwrap().clone();
     args.remove(0);
 
     // get the input file
-    let input_file_in = &args.get(0).unwrap().clone();
+    let input_file_in = &args.first().unwrap().clone();
     args.remove(0);
 
     // make sure its not a help command or a version command, and if it is, print the help or version
@@ -241,9 +1187,7 @@ fn main() {
                     print_version();
                     return;
                 }
-                Argument::Color(b) => {
-                    color = b;
-                }
+                Argument::Color => {}
                 Argument::Quiet => {
                     quiet = true;
                 }
@@ -261,11 +1205,37 @@ fn main() {
                 Argument::Release => {
                     release = true;
                 }
+                Argument::NoFpu => {
+                    fpu = false;
+                }
+                Argument::ErrorLimit(limit) => {
+                    match limit.parse() {
+                        Ok(limit) => error_limit = limit,
+                        Err(_) => {
+                            println!("{}Invalid error limit {}! Must be a number, or 0 for no limit!", Color::Red, limit);
+                            flush_styles();
+                            return;
+                        }
+                    }
+                }
+                Argument::Include(dir) => {
+                    include.push(PathBuf::from(dir));
+                }
+                Argument::Target(t) => {
+                    match Target::from_name(&t) {
+                        Some(target) => options.target = target,
+                        None => {
+                            println!("{}Unknown target {}! Can be x86, arm or riscv!", Color::Red, t);
+                            flush_styles();
+                            return;
+                        }
+                    }
+                }
             }
         }
     }
 
-    let code = read_file(&input_file);
+    let code = read_file(input_file);
 
     if verbose {
         println!("Lexing the code...");
@@ -277,7 +1247,7 @@ fn main() {
     // tokenize the input through the lexer
     let (mut tokens, lex_time) = time_taken(|| lex(&mut input_reader));
     if tokens.is_err() {
-        print_error(tokens.unwrap_err());
+        print_errors(tokens.unwrap_err(), error_limit);
         return;
     }
 
@@ -293,9 +1263,10 @@ fn main() {
     }
 
     // parse the tokens into an AST
-    let (mut ast, parse_time) = time_taken(|| parse(&mut tokens.as_mut().unwrap()));
+    tokens.as_mut().unwrap().modules = Modules::new(include);
+    let (mut ast, parse_time) = time_taken(|| parse(tokens.as_mut().unwrap()));
     if ast.is_err() {
-        print_error(ast.unwrap_err());
+        print_errors(ast.unwrap_err(), error_limit);
         return;
     }
 
@@ -309,6 +1280,25 @@ fn main() {
         // println!("AST:\n{}", ast.unwrap());
         let tree_node: TreeNode = ast.as_mut().unwrap().as_treenode();
         println!("{}", tree_node);
+        println!("Checking the program...");
+    }
+
+    // find semantic and type errors before running or compiling the code
+    let (checked, check_time) = time_taken(|| {
+        check_program(ast.as_mut().unwrap()).and_then(|_| type_check(ast.as_mut().unwrap(), fpu))
+    });
+    if let Err(errors) = checked {
+        print_errors(errors, error_limit);
+        return;
+    }
+
+    if verbose {
+        let check_display_time = if check_time > 500.0 {
+            format!("{}s", round(check_time / 1000.0, 3))
+        } else {
+            format!("{}ms", check_time)
+        };
+        println!("Checking done. Took {}.", check_display_time);
     }
 
     if mode == 0 { // interpretation mode
@@ -316,10 +1306,22 @@ fn main() {
             println!("Interpreting code...");
         }
         // interpret the ast
-        let (res, interpret_time) = time_taken(|| interpret(&mut ast.as_mut().unwrap()));
+        // the host has no interrupts of its own, so they are only reported
+        let (res, interpret_time) = time_taken(|| {
+            let interrupts = InterruptTable {
+                fallback: Some(Box::new(move |vector| {
+                    if !quiet {
+                        println!("Raised interrupt {:#04x}", vector);
+                    }
+                    Ok(())
+                })),
+                ..Default::default()
+            };
+            interpret(ast.as_ref().unwrap(), interrupts)
+        });
 
         if res.is_err() {
-            println!("{}", res.unwrap_err());
+            print_runtime_error(res.unwrap_err());
             return;
         }
 
@@ -330,6 +1332,7 @@ fn main() {
                 format!("{}ms", interpret_time)
             };
             println!("Ran the code. Took {}.", parse_display_time);
+            println!("start() returned {}", res.unwrap());
         }
 
     } else { // compilation mode
@@ -337,12 +1340,13 @@ fn main() {
             println!("Generating code...");
         }
         // compile to a string
-        let (res, compile_time) = time_taken(|| generate_c_code(&mut ast.as_mut().unwrap()));
+        options.release = release;
+        let (res, compile_time) = time_taken(|| generate_c_code(ast.as_mut().unwrap(), &options));
 
         // writing the string to a file
 
         if res.is_err() {
-            println!("{}", res.unwrap_err());
+            print_compiler_error(res.unwrap_err());
             return;
         }
 
@@ -350,6 +1354,7 @@ fn main() {
             .write(true)
             .append(false)
             .create(true)
+            .truncate(true)
             .open(output_file_path)
             .expect("Failed to open output file for writing.");
 
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::{env, fs};
+use std::path::{Path, PathBuf};
+use crate::CodePos;
+
+/// What happened when a file was used
+pub enum Import {
+    /// the file hasn't been loaded before, so it needs to be parsed
+    New,
+    /// the file was already loaded, from the path given
+    Loaded(String),
+}
+
+/// The files a program is made of. Each file is loaded once no matter how many files use it, and
+/// the files being parsed are tracked so a file that ends up using itself can be reported
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Modules {
+    /// the path every file was loaded from, by its canonical path
+    loaded: HashMap<PathBuf, String>,
+    /// the files being parsed, from the program's file to the innermost `use`, along with where they were used
+    importing: Vec<(PathBuf, Option<CodePos>)>,
+    /// the directories from `-I` and then `BOULDER_PATH`, searched after the directory of the file using a file
+    search: Vec<PathBuf>,
+    /// the directories the standard library can be in, searched last
+    std: Vec<PathBuf>,
+}
+
+/// the canonical path of a file, which is the same however the file is named
+fn canonical(path: &str) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path))
+}
+
+/// where the standard library can be: a `std` directory next to the compiler, then the one in the
+/// source tree the compiler was built from
+fn std_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+    if let Some(dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(|dir| dir.join("std"))) {
+        roots.push(dir);
+    }
+    roots.push(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/std")));
+    roots
+}
+
+impl Modules {
+    /// files that aren't next to the file using them are searched for in `include`, then in each
+    /// directory in the `BOULDER_PATH` environment variable, then in the standard library
+    pub fn new(include: Vec<PathBuf>) -> Modules {
+        let mut search = include;
+        if let Some(paths) = env::var_os("BOULDER_PATH") {
+            search.extend(env::split_paths(&paths));
+        }
+        Modules { search, std: std_roots(), ..Modules::default() }
+    }
+
+    /// finds the file a `use` in `from` names. `use std` only searches the standard library. Returns
+    /// every path that was tried if none of them exist
+    pub fn find(&self, name: &str, from: Option<&str>, std: bool) -> Result<String, Vec<String>> {
+        let mut dirs = Vec::new();
+        if !std {
+            dirs.push(from.and_then(|f| Path::new(f).parent()).map(Path::to_path_buf).unwrap_or_default());
+            dirs.extend(self.search.iter().cloned());
+        }
+        dirs.extend(self.std.iter().cloned());
+        let tried: Vec<String> = dirs.iter().map(|dir| dir.join(name).to_string_lossy().to_string()).collect();
+        tried.iter().find(|path| Path::new(path).is_file()).cloned().ok_or(tried)
+    }
+
+    /// marks the program's own file as being parsed, so files it uses can't use it again
+    pub fn enter_root(&mut self, path: &str) {
+        let canonical = canonical(path);
+        self.loaded.insert(canonical.clone(), path.to_string());
+        self.importing.push((canonical, None));
+    }
+
+    /// starts loading a file used at `from`. Returns the chain of uses that leads back to the file
+    /// if it is already being parsed
+    pub fn enter(&mut self, path: &str, from: &CodePos) -> Result<Import, String> {
+        let canonical = canonical(path);
+        if let Some(index) = self.importing.iter().position(|(p, _)| *p == canonical) {
+            let mut chain: Vec<String> = self.importing[index + 1..].iter()
+                .filter_map(|(p, pos)| pos.as_ref().map(|pos| format!("{} uses `{}`", pos, self.loaded[p])))
+                .collect();
+            chain.push(format!("{} uses `{}`", from, self.loaded[&canonical]));
+            return Err(format!("`{}` uses itself: {}", self.loaded[&canonical], chain.join(", then ")));
+        }
+        if let Some(loaded) = self.loaded.get(&canonical) {
+            return Ok(Import::Loaded(loaded.clone()));
+        }
+        self.loaded.insert(canonical.clone(), path.to_string());
+        self.importing.push((canonical, Some(from.clone())));
+        Ok(Import::New)
+    }
+
+    /// finishes parsing the file that was entered last
+    pub fn leave(&mut self) {
+        self.importing.pop();
+    }
+}
@@ -3,44 +3,44 @@ use cli_tree::TreeNode;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Operator {
-    Add,       // +   (binary)         0
-    Sub,       // -   (binary & unary) 0
-    Mul,       // *   (binary)         1
-    Div,       // /   (binary)         1
-    Mod,       // %   (binary)         1
-    Xor,       // ^   (binary)         2
-    And,       // &   (binary)         2
-    Or,        // |   (binary)         2
-    Not,       // !   (binary & unary) 2
-    Assign,    // =   (binary)         -
-    Eq,        // ==  (binary)         2
-    Neq,       // !=  (binary)         2
-    Lt,        // <   (binary)         0
-    Lte,       // <=  (binary)         1
-    Gt,        // >   (binary)         0
-    Gte,       // >=  (binary)         1
-    BoolAnd,   // &&  (binary)         1
-    BoolOr,    // ||  (binary)         1
-    MulAssign, // *=  (binary)         -
-    DivAssign, // /=  (binary)         -
-    ModAssign, // %=  (binary)         -
-    AddAssign, // +=  (binary)         -
-    SubAssign, // -=  (binary)         -
-    XorAssign, // ^=  (binary)         -
-    AndAssign, // &=  (binary)         -
-    OrAssign,  // |=  (binary)         -
-    Shl,       // <<  (binary)         2
-    Shr,       // >>  (binary)         2
-    ShlAssign, // <<= (binary)         -
-    ShrAssign, // >>= (binary)         -
-    Shlu,      // <<< (binary)         2
-    Shru,      // >>> (binary)         2
-    Move,      // ->  (binary)         -
-    Inc,       // ++  (unary)          0
-    Dec,       // --  (unary)          0
-    Right,     // =>  (binary)         -
-    Range,     // ..  (binary)         -
-    IRange,    // ..= (binary)         -
+    Add,       // +    (binary)            10
+    Sub,       // -    (binary & unary)    10
+    Mul,       // *    (binary & unary)    11
+    Div,       // /    (binary)            11
+    Mod,       // %    (binary)            11
+    Xor,       // ^    (binary)            5
+    And,       // &    (binary & unary)    6
+    Or,        // |    (binary)            4
+    Not,       // !    (unary)             -
+    Assign,    // =    (binary)            -
+    Eq,        // ==   (binary)            7
+    Neq,       // !=   (binary)            7
+    Lt,        // <    (binary)            8
+    Lte,       // <=   (binary)            8
+    Gt,        // >    (binary)            8
+    Gte,       // >=   (binary)            8
+    BoolAnd,   // &&   (binary)            3
+    BoolOr,    // ||   (binary)            2
+    MulAssign, // *=   (binary)            -
+    DivAssign, // /=   (binary)            -
+    ModAssign, // %=   (binary)            -
+    AddAssign, // +=   (binary)            -
+    SubAssign, // -=   (binary)            -
+    XorAssign, // ^=   (binary)            -
+    AndAssign, // &=   (binary)            -
+    OrAssign,  // |=   (binary)            -
+    Shl,       // <<   (binary)            9
+    Shr,       // >>   (binary)            9
+    ShlAssign, // <<=  (binary)            -
+    ShrAssign, // >>=  (binary)            -
+    Shlu,      // <<<  (binary)            9
+    Shru,      // >>>  (binary)            9
+    Move,      // ->   (binary)            -
+    Inc,       // ++   (unary)             -
+    Dec,       // --   (unary)             -
+    Right,     // =>   (binary)            -
+    Range,     // ..   (binary)            1
+    IRange,    // ..=  (binary)            1
 }
 
 impl Operator {
@@ -48,25 +48,60 @@ impl Operator {
         TreeNode::new(self.to_string())
     }
 
-    // returns a number from 0 to 2, with higher numbers being higher precedence
+    /// how tightly a binary operator binds, following C, with higher numbers binding tighter. Every
+    /// binary operator is left associative. Ranges bind the loosest, so `0..n + 1` is `0..(n + 1)`
     pub fn precedence(&self) -> Option<u8> {
+        Some(match self {
+            Operator::Range | Operator::IRange => 1,
+            Operator::BoolOr => 2,
+            Operator::BoolAnd => 3,
+            Operator::Or => 4,
+            Operator::Xor => 5,
+            Operator::And => 6,
+            Operator::Eq | Operator::Neq => 7,
+            Operator::Lt | Operator::Lte | Operator::Gt | Operator::Gte => 8,
+            Operator::Shl | Operator::Shr | Operator::Shlu | Operator::Shru => 9,
+            Operator::Add | Operator::Sub => 10,
+            Operator::Mul | Operator::Div | Operator::Mod => 11,
+            _ => return None
+        })
+    }
+
+    /// returns true for the operators that can come before a value, such as `-x` or `*p`
+    pub fn is_prefix(&self) -> bool {
+        matches!(self, Operator::Sub | Operator::Not | Operator::And | Operator::Mul | Operator::Inc | Operator::Dec)
+    }
+
+    /// returns the operator applied by an assignment operator (i.e. `+=` applies `+`)
+    pub fn assign_op(&self) -> Option<Operator> {
         match self {
-            Operator::Add | Operator::Sub | Operator::Gt | Operator::Lt => Some(0),
-            Operator::Mul | Operator::Div | Operator::Mod |
-            Operator::Gte | Operator::Lte | Operator::BoolAnd | Operator::BoolOr => Some(1),
-            Operator::Xor | Operator::And | Operator::Or | Operator::Not |
-            Operator::Shl | Operator::Shr | Operator::Shlu | Operator::Shru |
-            Operator::Eq  | Operator::Neq => Some(2),
+            Operator::AddAssign => Some(Operator::Add),
+            Operator::SubAssign => Some(Operator::Sub),
+            Operator::MulAssign => Some(Operator::Mul),
+            Operator::DivAssign => Some(Operator::Div),
+            Operator::ModAssign => Some(Operator::Mod),
+            Operator::XorAssign => Some(Operator::Xor),
+            Operator::AndAssign => Some(Operator::And),
+            Operator::OrAssign => Some(Operator::Or),
+            Operator::ShlAssign => Some(Operator::Shl),
+            Operator::ShrAssign => Some(Operator::Shr),
             _ => None
         }
     }
 
+    pub fn is_assignment(&self) -> bool {
+        *self == Operator::Assign || self.assign_op().is_some()
+    }
+
+    /// returns true for the binary operators that compute a number from two numbers
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(self, Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Mod | Operator::Xor |
+                       Operator::And | Operator::Or | Operator::Shl | Operator::Shr | Operator::Shlu | Operator::Shru)
+    }
+
     pub fn is_boolean(&self) -> bool {
-        match self {
-            Operator::Eq | Operator::Neq | Operator::Lt | Operator::Gt | Operator::Lte |
-            Operator::Gte | Operator::BoolAnd | Operator::BoolOr => true,
-            _ => false
-        }
+        matches!(self, Operator::Eq | Operator::Neq | Operator::Lt | Operator::Gt | Operator::Lte |
+                       Operator::Gte | Operator::BoolAnd | Operator::BoolOr)
     }
 
     pub fn as_raw(&self) -> String {
@@ -74,7 +109,7 @@ impl Operator {
             Operator::Add => String::from("+"),
             Operator::Sub => String::from("-"),
             Operator::Mul => String::from("*"),
-            Operator::Div => String::from("+"),
+            Operator::Div => String::from("/"),
             Operator::Mod => String::from("%"),
             Operator::Xor => String::from("^"),
             Operator::And => String::from("&"),
@@ -158,8 +193,8 @@ impl Display for Operator {
     }
 }
 
-impl Into<TreeNode> for Operator {
-    fn into(self) -> TreeNode {
-        TreeNode::new(self.to_string())
+impl From<Operator> for TreeNode {
+    fn from(op: Operator) -> TreeNode {
+        TreeNode::new(op.to_string())
     }
 }
\ No newline at end of file
@@ -1,11 +1,51 @@
-use std::path::Path;
-use crate::statement::{Number, ShuntedStack, ShuntedStackItem, Statement};
-use crate::{Error, InputReader, lex, read_file, TokenList, validate_boulder_file};
+use crate::statement::{Float, Number, Statement};
+use crate::{CodePos, Error, InputReader, lex, read_file, Span, TokenList, validate_boulder_file};
+use crate::module::Import;
 use crate::operator::Operator;
 use crate::token::{Token, TokenType};
 
+/// skips the tokens after an error to where parsing can start again: past the next `;`, or up to the
+/// next `fn`, `use`, `pub`, `const` or `static`. Inside of a block it also stops at a new line or the `}` closing the block,
+/// outside of one the `}` is skipped too. Returns an error node covering what was skipped
+fn synchronize(tokens: &mut TokenList, start: CodePos, in_block: bool) -> Statement {
+    // how many blocks deep into the skipped code the tokens are
+    let mut depth = 0;
+    while let Some(token) = tokens.peek() {
+        match token.token_type {
+            TokenType::EOF => break,
+            TokenType::Fn | TokenType::Use | TokenType::Pub | TokenType::Const | TokenType::Static if depth == 0 => break,
+            TokenType::Whitespace if in_block && depth == 0 && token.end.line > token.start.line => break,
+            TokenType::CloseBracket if depth == 0 => {
+                if !in_block {
+                    tokens.consume();
+                }
+                break;
+            }
+            TokenType::NOP if depth == 0 => {
+                tokens.consume();
+                break;
+            }
+            TokenType::OpenBracket => depth += 1,
+            TokenType::CloseBracket => depth -= 1,
+            _ => {}
+        }
+        tokens.consume();
+    }
+    Statement::Error { span: tokens.span_from(start) }
+}
+
+/// records an error and skips past the code that caused it. `remaining` is how many tokens were left
+/// before the failed statement, so the token it failed on is skipped if nothing else was
+fn recover(tokens: &mut TokenList, error: Error, start: CodePos, remaining: usize, in_block: bool) -> Statement {
+    tokens.errors.push(error);
+    if tokens.tokens.len() == remaining && !tokens.next_is(TokenType::EOF) {
+        tokens.consume();
+    }
+    synchronize(tokens, start, in_block)
+}
+
 fn parse_block(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // remove the '{' token
+    let start = tokens.consume().unwrap().start; // remove the '{' token
     let mut expressions = Vec::new();
     // while the next token is not the '}' token, parse the next expression
     while let Some(token) = tokens.peek() {
@@ -18,21 +58,38 @@ fn parse_block(tokens: &mut TokenList) -> Result<Statement, Error> {
             tokens.consume(); // remove it
             break; // break out of the loop
         }
-        expressions.push(parse_statement(tokens)?);
+        // a block missing its '}' ends where the next item starts, so the item is still parsed
+        if matches!(token.token_type, TokenType::EOF | TokenType::Fn | TokenType::Use | TokenType::Pub) {
+            let found = match token.token_type {
+                TokenType::EOF => "found end of file".to_string(),
+                other => format!("found: {}", other)
+            };
+            tokens.errors.push(Error::new("Expected closing '}'", found, token.start));
+            break;
+        }
+        let statement_start = token.start.clone();
+        let remaining = tokens.tokens.len();
+        match parse_statement(tokens) {
+            Ok(statement) => expressions.push(statement),
+            Err(e) => expressions.push(recover(tokens, e, statement_start, remaining, true)),
+        }
     }
-    Ok(Statement::Block { exprs: expressions })
+    Ok(Statement::Block { exprs: expressions, span: tokens.span_from(start) })
 }
 
-fn parse_array_dec(tokens: &mut TokenList, mods: Vec<Statement>) -> Result<Statement, Error> {
-    tokens.expect(TokenType::OpenBracket)?; // expect the '[' token
+#[allow(clippy::needless_return)]
+fn parse_array_dec(tokens: &mut TokenList, mods: Vec<Statement>, start: CodePos) -> Result<Statement, Error> {
+    tokens.expect(TokenType::OpenBrace)?; // expect the '[' token
     let array_type = Box::new(get_type(tokens, false)?); // get the type of the array with its modifiers
     tokens.expect(TokenType::NOP)?; // consume the ';' token
-    let size = Box::new(parse_global(tokens)?); // parse the size of the array
-    tokens.expect(TokenType::CloseBracket)?; // consume the ']' token
+    // the size is a constant expression, which the checker evaluates
+    let size = Box::new(parse_binary(tokens, 0, true)?);
+    tokens.expect(TokenType::CloseBrace)?; // consume the ']' token
     return Ok(Statement::ArrayType {
         array_type,
         size,
         modifiers: mods,
+        span: tokens.span_from(start),
     });
 }
 
@@ -40,6 +97,7 @@ fn get_type(tokens: &mut TokenList, expect_colon: bool) -> Result<Statement, Err
     if expect_colon {
         tokens.expect(TokenType::Colon)?;
     }
+    let start = tokens.next_start();
     let mut modifiers: Vec<Statement> = Vec::new();
     while let Some(op) = tokens.optional_expect(TokenType::Operator)? {
         match op.op.unwrap() {
@@ -51,51 +109,78 @@ fn get_type(tokens: &mut TokenList, expect_colon: bool) -> Result<Statement, Err
             }
         }
     }
-    if let Some(_) = tokens.optional_expect(TokenType::OpenBracket)? {
-        return parse_array_dec(tokens, modifiers);
+    if tokens.next_is(TokenType::OpenBrace) {
+        return parse_array_dec(tokens, modifiers, start);
+    }
+    // the type, which is `alias::Type` for a type from a file used with an alias
+    let type_token = tokens.expect(TokenType::Ident)?;
+    let mut type_ident = Box::new(identifier(type_token));
+    // the whitespace after a type isn't removed, since it can end the statement
+    while tokens.peek().is_some_and(|t| t.token_type == TokenType::DoubleColon) {
+        type_ident = Box::new(parse_path(tokens, *type_ident)?);
     }
-    // the type
-    let type_ident = Box::new(Statement::Identifier{
-        ident: tokens.expect(TokenType::Ident)?.value.unwrap()
-    });
     Ok(Statement::Type {
-        type_ident, modifiers
+        type_ident, modifiers,
+        span: tokens.span_from(start)
     })
 }
 
-fn define_params(tokens: &mut TokenList) -> Result<Vec<Statement>, Error> {
+/// parses `&self` at the start of a method's parameters, which becomes `self: &Type`
+fn parse_self_param(tokens: &mut TokenList, impl_type: Option<&Statement>) -> Result<Option<Statement>, Error> {
+    let is_self = |t: Option<Token>| t.map(|t| t.value.as_deref() == Some("self")).unwrap_or(false);
+    let reference = tokens.next_is_op(Operator::And) && is_self(tokens.second_after_ws());
+    let by_value = tokens.next_is(TokenType::Ident) && is_self(tokens.peek());
+    if !reference && !by_value {
+        return Ok(None);
+    }
+    let start = tokens.peek_loc().unwrap();
+    if !reference {
+        return Err(Error::new("Expected `&self`", "methods take their value by reference", start));
+    }
+    if impl_type.is_none() {
+        return Err(Error::new("Unexpected `self`", "only methods in an impl block can take `&self`", start));
+    }
+    tokens.consume(); // remove the '&'
+    let ident = tokens.expect(TokenType::Ident)?;
+    let span = tokens.span_from(start);
+    Ok(Some(Statement::Declaration {
+        ident: Box::new(identifier(ident)),
+        type_ident: Some(Box::new(Statement::Type {
+            modifiers: vec![Statement::Reference],
+            type_ident: Box::new(impl_type.unwrap().clone()),
+            span: span.clone()
+        })),
+        value: None,
+        span
+    }))
+}
+
+fn define_params(tokens: &mut TokenList, impl_type: Option<&Statement>) -> Result<Vec<Statement>, Error> {
     // the vector to hold the parameters
     let mut params = Vec::new();
     tokens.expect(TokenType::OpenParen)?; // expect an open paren
+    if let Some(self_param) = parse_self_param(tokens, impl_type)? {
+        params.push(self_param);
+        if tokens.optional_expect(TokenType::Comma)?.is_none() {
+            tokens.expect(TokenType::CloseParen)?;
+            return Ok(params);
+        }
+    }
     // while there is a next identifier, parse the next parameter
     while let Some(ident) = tokens.optional_expect(TokenType::Ident)? {
         // expect a type identifier
         let param_type = get_type(tokens, true)?;
-        // if there is a default value, push the full declaration as a parameter
-        // todo(eric): when this is implemented, there is no need to check if tokens is empty and store next_loc
-        //  so it can be removed
-        tokens.optional_whitespace();
-        if tokens.is_empty() {
-            return Err(Error::new("Expected parameter or closing ')'", "Found end of file", tokens.eof()));
-        }
-        let next_loc = tokens.peek_loc().unwrap();
+        // a default value is used by calls that leave the parameter out
+        let mut default_value = None;
         if tokens.optional_op(Operator::Assign)?.is_some() {
-            // todo(eric): handle default values (removed because the compiler doesn't support them, this code works)
-            return Err(Error::new("Invalid Assignment", "Default parameter values are not yet supported!", next_loc));
-            // let default_value = parse_statement(tokens)?;
-            // params.push(Statement::Declaration {
-            //     ident: Box::new(Statement::Identifier { ident: ident.value.unwrap() }),
-            //     type_ident: Some(Box::new(param_type)),
-            //     value: Some(Box::new(default_value))
-            // });
-        } else {
-            // otherwise push the ident and type as the param
-            params.push(Statement::Declaration {
-                ident: Box::new(Statement::Identifier { ident: ident.value.unwrap() }),
-                type_ident: Some(Box::new(param_type)),
-                value: None
-            });
+            default_value = Some(Box::new(parse_statement(tokens)?));
         }
+        params.push(Statement::Declaration {
+            span: tokens.span_from(ident.start.clone()),
+            ident: Box::new(identifier(ident)),
+            type_ident: Some(Box::new(param_type)),
+            value: default_value
+        });
         // if the next token is not a comma, break out of the loop
         if tokens.optional_expect(TokenType::Comma)?.is_none() {
             break;
@@ -107,11 +192,12 @@ fn define_params(tokens: &mut TokenList) -> Result<Vec<Statement>, Error> {
     Ok(params)
 }
 
-fn parse_fn(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // remove fn
+/// parses a function, or a method when it is inside of an impl block for `impl_type`
+fn parse_fn(tokens: &mut TokenList, impl_type: Option<&Statement>) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // remove fn
     tokens.expect_whitespace()?; // separator between fn and name
     let name = tokens.expect(TokenType::Ident)?; // the identifier of the function
-    let params = define_params(tokens)?;
+    let params = define_params(tokens, impl_type)?;
     // optional return type
     let mut rt = Statement::Void;
     if tokens.optional_op(Operator::Move)?.is_some() {
@@ -120,64 +206,249 @@ fn parse_fn(tokens: &mut TokenList) -> Result<Statement, Error> {
     // parse the body of the expression
     let body = parse_statement(tokens)?;
     Ok(Statement::Fn {
-        ident: Box::new(Statement::Identifier { ident: name.value.unwrap() }),
+        ident: Box::new(identifier(name)),
         params,
         return_type: Box::new(rt),
-        body: Box::new(body)
+        body: Box::new(body),
+        public: false,
+        span: tokens.span_from(start)
     })
 }
 
 fn parse_declaration(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // remove the let token
+    let start = tokens.consume().unwrap().start; // remove the let token
     tokens.expect_whitespace()?; // separator between let and name
     let ident = tokens.expect(TokenType::Ident)?; // the identifier of the declaration
     // if there is a type, parse it
+    // without a type, the type checker infers it from the value
     let mut def_type: Option<Box<Statement>> = None;
     if tokens.optional_expect(TokenType::Colon)?.is_some() {
         def_type = Some(Box::new(get_type(tokens, false)?));
-    } else {
-        // TODO: inferred types!
-        return Err(Error::new("Inferred types are not yet implemented!", "Variable types must be defined!",
-                       tokens.peek().unwrap().start))?;
     }
     // if there is a default value, parse it
     let mut def_value: Option<Box<Statement>> = None;
     if tokens.optional_op(Operator::Assign)?.is_some() {
         def_value = Some(Box::new(parse_statement(tokens)?));
+    } else if def_type.is_none() {
+        return Err(Error::new("Missing type", "Variables without a type must be given a value to infer it from",
+                              ident.start));
     }
     // return the declaration
     Ok(Statement::Declaration {
-        ident: Box::new(Statement::Identifier { ident: ident.value.unwrap() }),
+        ident: Box::new(identifier(ident)),
         type_ident: def_type,
-        value: def_value
+        value: def_value,
+        span: tokens.span_from(start)
     })
 }
 
+/// `const NAME: type = value`, `static NAME: type = value` or `static mut NAME: type = value`
+fn parse_global_var(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let keyword = tokens.consume().unwrap(); // remove the const or static token
+    tokens.expect_whitespace()?; // separator between the keyword and name
+    let mutable = keyword.token_type == TokenType::Static && tokens.optional_expect(TokenType::Mut)?.is_some();
+    let name = tokens.expect(TokenType::Ident)?;
+    // globals are visible before their value is checked, so their type can't be inferred
+    if !tokens.next_is(TokenType::Colon) {
+        let msg = format!("`{}` needs a type, globals can't infer one from their value", name.value.as_deref().unwrap());
+        return Err(Error::new("Missing type", msg, name.span()));
+    }
+    let type_ident = Box::new(get_type(tokens, true)?);
+    tokens.expect_op(Operator::Assign)?;
+    let value = Box::new(parse_statement(tokens)?);
+    let ident = Box::new(identifier(name));
+    let span = tokens.span_from(keyword.start);
+    if keyword.token_type == TokenType::Const {
+        return Ok(Statement::Const { ident, type_ident, value, public: false, span });
+    }
+    Ok(Statement::Static { ident, type_ident, value, mutable, public: false, span })
+}
+
+fn parse_struct(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // remove the struct token
+    tokens.expect_whitespace()?; // separator between struct and name
+    let name = tokens.expect(TokenType::Ident)?; // the identifier of the struct
+    tokens.expect(TokenType::OpenBracket)?;
+    let mut fields = Vec::new();
+    // fields are separated by commas, with an optional trailing comma
+    while let Some(field) = tokens.optional_expect(TokenType::Ident)? {
+        let field_type = get_type(tokens, true)?;
+        fields.push(Statement::Declaration {
+            span: tokens.span_from(field.start.clone()),
+            ident: Box::new(identifier(field)),
+            type_ident: Some(Box::new(field_type)),
+            value: None
+        });
+        if tokens.optional_expect(TokenType::Comma)?.is_none() {
+            break;
+        }
+    }
+    tokens.expect(TokenType::CloseBracket)?;
+    // C does not allow empty structs
+    if fields.is_empty() {
+        return Err(Error::new("Empty struct", "structs must have at least one field", name.start));
+    }
+    Ok(Statement::Struct {
+        ident: Box::new(identifier(name)),
+        fields,
+        public: false,
+        span: tokens.span_from(start)
+    })
+}
+
+/// parses a literal used in a pattern or enum discriminant, such as `5`, `-1`, `0x10`, `'a'` or `true`
+fn parse_literal(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.next_start();
+    let negative = tokens.next_is_op(Operator::Sub);
+    if negative {
+        tokens.consume();
+    }
+    let token = tokens.consume().ok_or_else(|| Error::new("Expected a literal", "found end of file", tokens.eof()))?;
+    let span = tokens.span_from(start);
+    match token.token_type {
+        TokenType::NumberLit | TokenType::HexLit | TokenType::OctLit | TokenType::BinLit => number_literal(token, negative, span),
+        TokenType::CharLit if !negative => number_literal(token, false, span),
+        TokenType::BoolTrue if !negative => Ok(Statement::BoolLiteral { value: true, span }),
+        TokenType::BoolFalse if !negative => Ok(Statement::BoolLiteral { value: false, span }),
+        _ => Err(Error::new("Expected a literal", format!("found: {}", token.token_type), token.start))
+    }
+}
+
+fn parse_enum(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // remove the enum token
+    tokens.expect_whitespace()?; // separator between enum and name
+    let name = tokens.expect(TokenType::Ident)?; // the identifier of the enum
+    tokens.expect(TokenType::OpenBracket)?;
+    let mut variants = Vec::new();
+    // variants are separated by commas, with an optional trailing comma
+    while let Some(variant) = tokens.optional_expect(TokenType::Ident)? {
+        let mut discriminant = None;
+        if tokens.next_is_op(Operator::Assign) {
+            tokens.consume();
+            discriminant = Some(Box::new(parse_literal(tokens)?));
+        }
+        variants.push(Statement::Declaration {
+            span: tokens.span_from(variant.start.clone()),
+            ident: Box::new(identifier(variant)),
+            type_ident: None,
+            value: discriminant
+        });
+        if tokens.optional_expect(TokenType::Comma)?.is_none() {
+            break;
+        }
+    }
+    tokens.expect(TokenType::CloseBracket)?;
+    // C does not allow empty enums
+    if variants.is_empty() {
+        return Err(Error::new("Empty enum", "enums must have at least one variant", name.start));
+    }
+    Ok(Statement::Enum {
+        ident: Box::new(identifier(name)),
+        variants,
+        public: false,
+        span: tokens.span_from(start)
+    })
+}
+
+fn parse_impl(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // remove the impl token
+    tokens.expect_whitespace()?; // separator between impl and the type
+    let type_token = tokens.expect(TokenType::Ident)?; // the type the methods belong to
+    let ident = identifier(type_token);
+    tokens.expect(TokenType::OpenBracket)?;
+    let mut methods = Vec::new();
+    while !tokens.next_is(TokenType::CloseBracket) {
+        if !tokens.next_is(TokenType::Fn) {
+            let found = tokens.peek().ok_or_else(|| Error::new("Expected a method or closing '}'",
+                                                                "found end of file", tokens.eof()))?;
+            return Err(Error::new("Expected a method or closing '}'",
+                                  format!("found: {}", found.token_type), found.start));
+        }
+        let method = parse_fn(tokens, Some(&ident))?;
+        if let Statement::Fn { ident: name, params, .. } = &method {
+            let takes_self = params.first().map(|p| match p {
+                Statement::Declaration { ident, .. } => matches!(ident.as_ref(),
+                    Statement::Identifier { ident, .. } if ident == "self"),
+                _ => false
+            }).unwrap_or(false);
+            if !takes_self {
+                if let Statement::Identifier { span, .. } = name.as_ref() {
+                    return Err(Error::new("Expected `&self`",
+                                          "methods must take `&self` as their first parameter", span.clone()));
+                }
+            }
+        }
+        methods.push(method);
+    }
+    tokens.consume(); // remove the '}'
+    Ok(Statement::Impl {
+        ident: Box::new(ident),
+        methods,
+        span: tokens.span_from(start)
+    })
+}
+
+#[allow(clippy::unnecessary_unwrap)]
 fn parse_use(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // remove the use token
+    let start = tokens.consume().unwrap().start; // remove the use token
     tokens.expect_whitespace()?; // separator between use and file
-    let file = tokens.expect(TokenType::StringLit)?; // the file to import
-    // ensure the file is valid and is a .rock file
-    let mut file_path = file.value.as_ref().unwrap().clone();
-    if file.start.file.is_some() {
-        let dir = file.start.file.as_ref().unwrap().clone();
-        let path = Path::new(&dir);
-        let parent = path.parent().unwrap();
-        file_path = format!("{}/{}", parent.to_str().unwrap(), file_path);
+    // `use std "file"` only looks in the standard library
+    let std = tokens.peek().is_some_and(|t| t.token_type == TokenType::Ident && t.value.as_deref() == Some("std"));
+    if std {
+        tokens.consume();
+        tokens.expect_whitespace()?;
     }
+    let file = tokens.expect(TokenType::StringLit)?; // the file to import
+    let name = file.value.as_deref().unwrap();
+    let file_path = match tokens.modules.find(name, file.start.file.as_deref(), std) {
+        Ok(path) => path,
+        Err(tried) => {
+            let msg = format!("`{}` was not found, looked for:\n{}", name, tried.iter()
+                .map(|path| format!("    {}", path)).collect::<Vec<String>>().join("\n"));
+            return Err(Error::new("File not found", msg, file.span()));
+        }
+    };
+    // ensure the file is a .rock file
     let valid = validate_boulder_file(file_path.clone());
     if valid.is_err() {
         return Err(Error::new("Invalid boulder file import", valid.unwrap_err(), file.start));
     }
+    // `as name` lets the file's items be used as `name::item`
+    let mut alias = None;
+    if tokens.next_after_ws(TokenType::As) {
+        tokens.optional_whitespace();
+        tokens.consume(); // remove the as token
+        alias = Some(Box::new(identifier(tokens.expect(TokenType::Ident)?)));
+    }
+    let span = tokens.span_from(start);
+    // a file that was already loaded is only parsed once, later uses just refer to it
+    match tokens.modules.enter(&file_path, &file.start) {
+        Ok(Import::New) => {}
+        Ok(Import::Loaded(path)) => return Ok(Statement::Use { exprs: Vec::new(), path, alias, span }),
+        Err(chain) => return Err(Error::new("Import cycle", chain, file.span())),
+    }
     // lex the file
-    let mut ir = InputReader::new(Some(file_path.clone()), read_file(file_path));
-    let mut file_tokens = lex(&mut ir)?;
-    // return the file's AST in an expression
-    Ok(Statement::Use { exprs: parse_file(&mut file_tokens)? })
+    let mut ir = InputReader::new(Some(file_path.clone()), read_file(&file_path));
+    let mut file_tokens = match lex(&mut ir) {
+        Ok(file_tokens) => file_tokens,
+        Err(errors) => {
+            // the errors are reported with the rest, the file just has nothing in it
+            tokens.errors.extend(errors);
+            tokens.modules.leave();
+            return Ok(Statement::Use { exprs: Vec::new(), path: file_path, alias, span });
+        }
+    };
+    // return the file's AST in an expression, keeping its errors to report with the rest
+    file_tokens.modules = std::mem::take(&mut tokens.modules);
+    let exprs = parse_file(&mut file_tokens);
+    tokens.modules = std::mem::take(&mut file_tokens.modules);
+    tokens.modules.leave();
+    tokens.errors.append(&mut file_tokens.errors);
+    Ok(Statement::Use { exprs, path: file_path, alias, span })
 }
 
 fn parse_if(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // consume the if token
+    let start = tokens.consume().unwrap().start; // consume the if token
     let condition = parse_statement(tokens)?;
     let body = parse_statement(tokens)?;
     let mut else_body = None;
@@ -188,58 +459,150 @@ fn parse_if(tokens: &mut TokenList) -> Result<Statement, Error> {
     Ok(Statement::If {
         condition: Box::new(condition),
         body: Box::new(body),
-        else_statement: else_body
+        else_statement: else_body,
+        span: tokens.span_from(start)
     })
 }
 
 fn parse_while(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // consume the while token
+    let start = tokens.consume().unwrap().start; // consume the while token
     let condition = parse_statement(tokens)?;
     let body = parse_statement(tokens)?;
     Ok(Statement::While {
         condition: Box::new(condition),
-        body: Box::new(body)
+        body: Box::new(body),
+        label: None,
+        span: tokens.span_from(start)
     })
 }
 
 fn parse_loop(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // consume the loop token
+    let start = tokens.consume().unwrap().start; // consume the loop token
     let body = parse_statement(tokens)?;
     Ok(Statement::Loop {
-        body: Box::new(body)
+        body: Box::new(body),
+        label: None,
+        span: tokens.span_from(start)
     })
 }
 
 fn parse_for(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // remove the 'for'
+    let start = tokens.consume().unwrap().start; // remove the 'for'
 
     let ident = Box::new(parse_statement(tokens)?);
     tokens.expect(TokenType::In)?;
     let collection = Box::new(parse_statement(tokens)?);
+    // `step` only means something after the collection, so it isn't a keyword
+    tokens.optional_whitespace();
+    let step = if tokens.peek().is_some_and(|t| t.token_type == TokenType::Ident && t.value.as_deref() == Some("step")) {
+        tokens.consume();
+        Some(Box::new(parse_statement(tokens)?))
+    } else {
+        None
+    };
 
     let body = Box::new(parse_statement(tokens)?);
 
-    Ok(Statement::For { ident, collection, body })
+    Ok(Statement::For {
+        ident, collection, step, body, label: None, element_type: None, length: None, through_pointer: false,
+        span: tokens.span_from(start)
+    })
+}
+
+/// parses `'label:` and the loop it names
+fn parse_labeled_loop(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let token = tokens.consume().unwrap(); // remove the label
+    let start = token.start.clone();
+    tokens.expect(TokenType::Colon)?;
+    tokens.optional_whitespace();
+    let mut stmt = match tokens.peek().map(|t| t.token_type) {
+        Some(TokenType::While) => parse_while(tokens)?,
+        Some(TokenType::Loop) => parse_loop(tokens)?,
+        Some(TokenType::For) => parse_for(tokens)?,
+        _ => return Err(Error::new("Expected a loop", "labels can only be put on `while`, `loop` and `for`",
+                                   token.span()))
+    };
+    let span = tokens.span_from(start);
+    match &mut stmt {
+        Statement::While { label, span: s, .. } | Statement::Loop { label, span: s, .. } |
+        Statement::For { label, span: s, .. } => {
+            *label = Some(Box::new(identifier(token)));
+            *s = span;
+        }
+        _ => {}
+    }
+    Ok(stmt)
 }
 
+/// the label after a `break` or `continue`, if it has one on the same line
+fn optional_label(tokens: &mut TokenList) -> Option<Box<Statement>> {
+    if tokens.newline_ahead() || !tokens.next_after_ws(TokenType::Label) {
+        return None;
+    }
+    tokens.optional_whitespace();
+    Some(Box::new(identifier(tokens.consume().unwrap())))
+}
+
+fn parse_break(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // remove the break token
+    let label = optional_label(tokens);
+    // anything else on the same line is the value the loop gives
+    let ends = tokens.newline_ahead() || tokens.peek_after_ws().is_none_or(|t| matches!(t.token_type,
+        TokenType::CloseBracket | TokenType::Comma | TokenType::NOP | TokenType::EOF));
+    let value = if ends {
+        None
+    } else {
+        Some(Box::new(parse_expression(tokens, false)?))
+    };
+    Ok(Statement::Break { label, value, span: tokens.span_from(start) })
+}
+
+fn parse_continue(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // remove the continue token
+    let label = optional_label(tokens);
+    Ok(Statement::Continue { label, span: tokens.span_from(start) })
+}
+
+#[allow(clippy::needless_return)]
 fn parse_return(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // consume the return token
+    let start = tokens.consume().unwrap().start; // consume the return token
 
     // if the next token is a closing block, there is no return type
     // this is kind of a hack? could probably be done better...
     return if tokens.next_after_ws(TokenType::CloseBracket) {
         tokens.optional_whitespace();
         Ok(Statement::Return {
-            value: Box::new(Statement::Void)
+            value: Box::new(Statement::Void),
+            span: tokens.span_from(start)
         })
     } else {
         tokens.expect_whitespace()?;
+        let value = Box::new(parse_statement(tokens)?);
         Ok(Statement::Return {
-            value: Box::new(parse_statement(tokens)?)
+            value,
+            span: tokens.span_from(start)
         })
     }
 }
 
+/// an argument in a call, which is either a value or `name = value` to pass it to the parameter `name`
+fn parse_argument(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let named = tokens.next_is(TokenType::Ident) &&
+        tokens.second_after_ws().is_some_and(|t| t.op == Some(Operator::Assign));
+    if !named {
+        return parse_statement(tokens);
+    }
+    let name = tokens.consume().unwrap();
+    tokens.expect_op(Operator::Assign)?;
+    let value = parse_statement(tokens)?;
+    Ok(Statement::Assignment {
+        span: tokens.span_from(name.start.clone()),
+        ident: Box::new(identifier(name)),
+        value: Box::new(value)
+    })
+}
+
+#[allow(clippy::needless_return)]
 fn parse_fn_call(tokens: &mut TokenList, ident: Statement) -> Result<Statement, Error> {
     tokens.consume(); // remove the open paren
     let mut params = Vec::new();
@@ -248,35 +611,160 @@ fn parse_fn_call(tokens: &mut TokenList, ident: Statement) -> Result<Statement,
             tokens.consume(); // remove the close paren
             break;
         }
-        params.push(parse_statement(tokens)?);
+        params.push(parse_argument(tokens)?);
         // if the next token is not a comma, break out of the loop
         if tokens.optional_expect(TokenType::Comma)?.is_none() {
             tokens.expect(TokenType::CloseParen)?;
             break;
         }
     }
+    let span = tokens.span_from(start_of(&ident));
+    // `value.method()` calls a method on the value
+    if let Statement::PropertyAccess { expr, property, .. } = ident {
+        return Ok(Statement::MethodCall {
+            receiver: expr,
+            method: property,
+            params,
+            receiver_type: None,
+            span
+        });
+    }
     return Ok(Statement::FnCall {
         ident: Box::new(ident),
-        params
+        params,
+        span
     });
 }
 
+/// returns true if the tokens ahead are `{ field:`, which starts a struct literal rather than a block.
+/// this keeps `if x {` and `while x {` from being read as struct literals
+fn struct_literal_ahead(tokens: &TokenList) -> bool {
+    let mut index: usize = 0;
+    let mut expected = [TokenType::OpenBracket, TokenType::Ident, TokenType::Colon].into_iter();
+    let mut next = expected.next();
+    while let (Some(t), Some(tt)) = (tokens.peek_nth(index), next.clone()) {
+        index += 1;
+        if t.token_type == TokenType::Whitespace {
+            continue;
+        }
+        if t.token_type != tt {
+            return false;
+        }
+        next = expected.next();
+    }
+    next.is_none()
+}
+
+fn parse_struct_literal(tokens: &mut TokenList, ident: Statement) -> Result<Statement, Error> {
+    tokens.optional_whitespace();
+    tokens.consume(); // remove the '{'
+    let mut fields = Vec::new();
+    while let Some(field) = tokens.optional_expect(TokenType::Ident)? {
+        tokens.expect(TokenType::Colon)?;
+        let value = parse_statement(tokens)?;
+        fields.push(Statement::Assignment {
+            span: tokens.span_from(field.start.clone()),
+            ident: Box::new(identifier(field)),
+            value: Box::new(value)
+        });
+        if tokens.optional_expect(TokenType::Comma)?.is_none() {
+            break;
+        }
+    }
+    tokens.expect(TokenType::CloseBracket)?;
+    Ok(Statement::StructLiteral {
+        span: tokens.span_from(start_of(&ident)),
+        ident: Box::new(ident),
+        fields
+    })
+}
+
+#[allow(clippy::needless_return)]
 fn parse_property(tokens: &mut TokenList, accessed: Statement) -> Result<Statement, Error> {
     tokens.consume(); // consume the '.'
-    let property = parse_identifier(tokens, true)?; // the property to access
+    // the property to access. anything after it, such as an operator or call, applies to the whole access
+    let property_token = tokens.expect(TokenType::Ident)?;
+    let property = identifier(property_token);
     // the property access expression
     let expr = Statement::PropertyAccess {
+        span: tokens.span_from(start_of(&accessed)),
         expr: Box::new(accessed),
-        property: Box::from(property)
+        property: Box::from(property),
+        through_pointer: false
     };
-    if tokens.next_is(TokenType::Dot) {
-        // recursively parse the property access expressions
-        // this will handle things like a.b.c().d
-        return parse_property(tokens, expr);
-    }
     return Ok(expr);
 }
 
+/// `base::member`
+fn parse_path(tokens: &mut TokenList, base: Statement) -> Result<Statement, Error> {
+    tokens.consume(); // remove the '::'
+    let member = tokens.expect(TokenType::Ident)?;
+    Ok(Statement::Path {
+        span: tokens.span_from(start_of(&base)),
+        base: Box::new(base),
+        member: Box::new(identifier(member))
+    })
+}
+
+/// parses a pattern in a match arm: a literal, a range of literals, an enum variant or `_`
+fn parse_pattern(tokens: &mut TokenList) -> Result<Statement, Error> {
+    tokens.optional_whitespace();
+    let token = tokens.peek().ok_or_else(|| Error::new("Expected a pattern", "found end of file", tokens.eof()))?;
+    if token.token_type == TokenType::Ident {
+        tokens.consume();
+        if token.value.as_deref() == Some("_") {
+            return Ok(Statement::Wildcard { span: token.span() });
+        }
+        let span = token.span();
+        let base = identifier(token);
+        if !tokens.next_is(TokenType::DoubleColon) {
+            return Err(Error::new("Expected a pattern", "patterns can be literals, ranges, enum variants or `_`",
+                                  span));
+        }
+        // `alias::Enum::Variant` for an enum from a file used with an alias
+        let mut path = parse_path(tokens, base)?;
+        while tokens.next_is(TokenType::DoubleColon) {
+            path = parse_path(tokens, path)?;
+        }
+        return Ok(path);
+    }
+    let start = parse_literal(tokens)?;
+    let inclusive = if tokens.next_is_op(Operator::IRange) {
+        true
+    } else if tokens.next_is_op(Operator::Range) {
+        false
+    } else {
+        return Ok(start);
+    };
+    tokens.consume();
+    let end = parse_literal(tokens)?;
+    let span = tokens.span_from(start_of(&start));
+    Ok(Statement::RangePattern { start: Box::new(start), end: Box::new(end), inclusive, span })
+}
+
+fn parse_match(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // consume the match token
+    let value = parse_statement(tokens)?;
+    tokens.expect(TokenType::OpenBracket)?;
+    let mut arms = Vec::new();
+    while !tokens.next_is(TokenType::CloseBracket) {
+        let pattern = parse_pattern(tokens)?;
+        tokens.expect_op(Operator::Right)?;
+        let body = parse_statement(tokens)?;
+        let span = tokens.span_from(start_of(&pattern));
+        arms.push(Statement::MatchArm { pattern: Box::new(pattern), body: Box::new(body), span });
+        // arms are separated by commas or new lines
+        tokens.optional_expect(TokenType::Comma)?;
+    }
+    tokens.consume(); // remove the '}'
+    Ok(Statement::Match {
+        value: Box::new(value),
+        arms,
+        value_type: None,
+        span: tokens.span_from(start)
+    })
+}
+
 /// array indexes
 fn parse_index(tokens: &mut TokenList, accessed: Statement) -> Result<Statement, Error> {
     tokens.consume(); // remove the open '['
@@ -290,331 +778,282 @@ fn parse_index(tokens: &mut TokenList, accessed: Statement) -> Result<Statement,
     tokens.expect(TokenType::CloseBrace)?;
 
     Ok(Statement::ArrayAccess {
+        span: tokens.span_from(start_of(&accessed)),
         ident: Box::new(accessed),
         index: Box::new(index),
+        length: None,
+        through_pointer: false,
     })
 }
 
 fn parse_assert(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // remove the "assert"
+    let start = tokens.consume().unwrap().start; // remove the "assert"
     let assertion = parse_statement(tokens)?;
-    Ok(Statement::Assert { expr: Box::new(assertion) })
+    Ok(Statement::Assert { expr: Box::new(assertion), span: tokens.span_from(start) })
 }
 
-/// returns (negative, increment, decrement)
-fn parse_shunting_yard_leading_op(op: Token, postfix: &mut ShuntedStack) -> Result<(bool, bool, bool), Error> {
-    return match op.op.unwrap() {
-        Operator::Sub => {
-            Ok((true, false, false))
-        }
-        Operator::Inc => {
-            postfix.push(ShuntedStackItem::new_operand(Statement::NumberLiteral
-            { value: Number::new("1".to_string(), false) }));
-            Ok((false, true, false))
-            // immediately push the add because ++x = (1 + x)
-        }
-        Operator::Dec => {
-            postfix.push(ShuntedStackItem::new_operand(Statement::NumberLiteral
-            { value: Number::new("1".to_string(), false) }));
-            Ok((false, false, true))
-        }
-        _ => {
-            Err(Error::new(
-                "Unexpected operator",
-                "found binary operator after another binary operator",
-                op.start
-            ))
-        }
-    }
+/// builds the literal statement for a number, float, hex, octal, binary or char literal token,
+/// which can all be used as numbers. The span covers the literal and its sign
+fn number_literal(token: Token, negative: bool, span: Span) -> Result<Statement, Error> {
+    let value = token.value.unwrap();
+    let radix = match token.token_type {
+        TokenType::CharLit => return Ok(Statement::CharLiteral { value: value.chars().next().unwrap(), span }),
+        TokenType::FloatLit => return Ok(Statement::FloatLiteral { value: Float::new(value, negative), span }),
+        TokenType::HexLit => 16,
+        TokenType::OctLit => 8,
+        TokenType::BinLit => 2,
+        _ => 10
+    };
+    let number = Number::parse(&value, radix, negative).ok_or_else(|| Error::new(
+        "Number literal is too large", "numbers can be at most 64 bits", span.clone()))?;
+    Ok(Statement::NumberLiteral { value: number, span })
 }
 
-fn shunting_yard(tokens: &mut TokenList, unary_start: bool, leading: Option<Statement>) -> Result<Statement, Error> {
-    // create a postfix stack using the shunting yard algorithm
-    // references:
-    // PEGwiki: https://wcipeg.com/wiki/Shunting_yard_algorithm
-    // wikipedia: https://en.wikipedia.org/wiki/Shunting-yard_algorithm
-    let mut postfix = ShuntedStack::new();
-    let mut op_stack: Vec<Token> = Vec::new();
-
-    if leading.is_some() {
-        // if there is a leading operator, push it onto the stack
-        postfix.push(ShuntedStackItem::new_operand(leading.unwrap()));
+/// parses `x = value` and `x += value`, where `x += value` becomes a `CompoundAssignment`
+/// so that `x` is only evaluated once
+fn parse_assignment(tokens: &mut TokenList, left: Statement) -> Result<Statement, Error> {
+    let op = tokens.expect(TokenType::Operator)?.op.unwrap();
+    let value = parse_statement(tokens)?;
+    let span = tokens.span_from(start_of(&left));
+    if let Some(applied) = op.assign_op() {
+        return Ok(Statement::CompoundAssignment {
+            op: applied,
+            ident: Box::new(left),
+            value: Box::new(value),
+            value_type: None,
+            span
+        });
     }
+    Ok(Statement::Assignment {
+        ident: Box::new(left),
+        value: Box::new(value),
+        span
+    })
+}
 
-    // last_op is used to handle unary operations mixed in with binary operations
-    let mut last_op: Option<Operator> = None;
-    let mut negative = false;
-    let mut last_was_inc = false;
-    let mut last_was_dec = false;
-    let mut last_ident: Option<Statement> = None;
-    let mut last_was_num = false;
-
-    if unary_start {
-        let op = tokens.expect(TokenType::Operator)?;
-        (negative, last_was_inc, last_was_dec) = parse_shunting_yard_leading_op(op, &mut postfix)?;
+/// parses an expression and an assignment to it if one follows, such as `x.y = 5`.
+/// When `multiline` is set the expression can continue onto the next line, which is used inside parentheses
+fn parse_expression(tokens: &mut TokenList, multiline: bool) -> Result<Statement, Error> {
+    let expr = parse_binary(tokens, 0, multiline)?;
+    if tokens.newline_ahead() {
+        return Ok(expr);
+    }
+    match tokens.peek_after_ws().and_then(|t| t.op) {
+        Some(op) if op.is_assignment() => parse_assignment(tokens, expr),
+        _ => Ok(expr)
     }
+}
 
-    while let Some(token) = tokens.peek() {
-        match token.token_type {
-            TokenType::NumberLit => {
-                tokens.consume();
+/// parses operands joined by binary operators that bind tighter than `min_precedence` using precedence
+/// climbing. Every binary operator is left associative, so the right side of one only takes operators
+/// that bind tighter than it. `expr as type` binds tighter than every binary operator
+fn parse_binary(tokens: &mut TokenList, min_precedence: u8, multiline: bool) -> Result<Statement, Error> {
+    let mut left = parse_unary(tokens)?;
+    loop {
+        // a new line ends the expression, unless it is inside of parentheses
+        if !multiline && tokens.newline_ahead() {
+            break;
+        }
+        let next = match tokens.peek_after_ws() {
+            Some(t) => t,
+            None => break
+        };
+        if next.token_type == TokenType::As {
+            tokens.optional_whitespace();
+            tokens.consume(); // remove the 'as'
+            let cast_type = get_type(tokens, false)?;
+            left = Statement::Cast {
+                span: tokens.span_from(start_of(&left)),
+                expr: Box::new(left),
+                cast_type: Box::new(cast_type)
+            };
+            continue;
+        }
+        let op = match next.op {
+            Some(op) if next.token_type == TokenType::Operator => op,
+            _ => break
+        };
+        let precedence = match op.precedence() {
+            Some(p) if p > min_precedence => p,
+            _ => break
+        };
+        tokens.optional_whitespace();
+        tokens.consume(); // remove the operator
+        let right = parse_binary(tokens, precedence, multiline)?;
+        left = Statement::Binary {
+            span: tokens.span_from(start_of(&left)),
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+            value_type: None
+        };
+    }
+    Ok(left)
+}
 
-                if last_ident.is_some() || last_was_num {
-                    // the last token was an identifier or number, which is not allowed
-                    return Err(Error::new(
-                        "Unexpected number",
-                        "found value following another value. did you mean to use a binary operator?",
-                        token.start
-                    ));
-                }
+/// parses a value along with the prefix operators before it, which bind tighter than any binary operator
+fn parse_unary(tokens: &mut TokenList) -> Result<Statement, Error> {
+    tokens.optional_whitespace();
+    let token = tokens.peek().ok_or_else(|| Error::new("Expected an expression", "found end of file", tokens.eof()))?;
+    let op = match token.op {
+        Some(op) if op.is_prefix() => op,
+        Some(op) => return Err(Error::new("Unexpected operator", format!("{} can't be used before a value", op),
+                                          token.span())),
+        None => {
+            let primary = parse_primary(tokens)?;
+            return parse_postfix(tokens, primary);
+        }
+    };
+    tokens.consume();
+    // negative number literals are folded into the literal so `-128` can be an i8
+    if op == Operator::Sub && tokens.peek().is_some_and(|t| matches!(t.token_type,
+        TokenType::NumberLit | TokenType::FloatLit | TokenType::HexLit | TokenType::OctLit | TokenType::BinLit)) {
+        let literal = tokens.consume().unwrap();
+        let span = Span::new(token.start, &literal.end);
+        let number = number_literal(literal, true, span)?;
+        return parse_postfix(tokens, number);
+    }
+    let expr = parse_unary(tokens)?;
+    Ok(Statement::Unary {
+        op,
+        expr: Box::new(expr),
+        postfix: false,
+        value_type: None,
+        span: tokens.span_from(token.start)
+    })
+}
 
-                // numbers get pushed to the stack
-                postfix.push(ShuntedStackItem::new_operand(
-                    Statement::NumberLiteral {
-                        value: Number::new(token.value.unwrap(), negative)
-                    }
-                ));
-                if last_was_inc {
-                    postfix.push(ShuntedStackItem::new_operator(Operator::Add));
-                }
-                if last_was_dec {
-                    postfix.push(ShuntedStackItem::new_operator(Operator::Sub));
-                }
-                last_op = None;
-                negative = false;
-                last_was_inc = false;
-                last_was_dec = false;
-                last_was_num = true;
-                last_ident = None;
+/// parses the calls, indexes, property accesses, paths, struct literals and `++`/`--` that follow a value.
+/// Only a property access can continue on the next line
+fn parse_postfix(tokens: &mut TokenList, mut expr: Statement) -> Result<Statement, Error> {
+    loop {
+        let newline = tokens.newline_ahead();
+        let next = match tokens.peek_after_ws() {
+            Some(t) => t,
+            None => break
+        };
+        if newline && next.token_type != TokenType::Dot {
+            break;
+        }
+        expr = match next.token_type {
+            TokenType::OpenParen => {
+                tokens.optional_whitespace();
+                parse_fn_call(tokens, expr)?
             }
-            TokenType::Ident => {
-                if last_ident.is_some() || last_was_num {
-                    // the last token was an identifier or number, which is not allowed
-                    return Err(Error::new(
-                        "Unexpected number",
-                        "found value following another value. did you mean to use a binary operator?",
-                        token.start
-                    ));
-                }
-
-                let stmt = parse_identifier(tokens, false)?;
-                // todo: handle negative operators before identifiers
-                postfix.push(ShuntedStackItem::new_operand(stmt.clone()));
-                if last_was_inc {
-                    postfix.push(ShuntedStackItem::new_operator(Operator::Add));
-                }
-                if last_was_dec {
-                    postfix.push(ShuntedStackItem::new_operator(Operator::Sub));
-                }
-                last_op = None;
-                negative = false;
-                last_was_inc = false;
-                last_was_dec = false;
-                last_was_num = false;
-                last_ident = Some(stmt);
+            TokenType::OpenBrace => {
+                tokens.optional_whitespace();
+                parse_index(tokens, expr)?
             }
-            TokenType::Operator => {
-                tokens.consume();
-                last_was_num = false;
-                last_ident = None;
-                let operator = token.op.unwrap();
-
-                // handle leading ops (unary)
-                if last_op.is_some() {
-                    (negative, last_was_inc, last_was_dec) = parse_shunting_yard_leading_op(token, &mut postfix)?;
-                    last_op = Some(operator.clone());
-                    continue;
-                }
-
-                // if the operator is a unary operator, push it to the stack
-                if operator == Operator::Inc || operator == Operator::Dec {
-                    // immediately push it to the stack as it directly applies to the number it follows
-                    postfix.push(ShuntedStackItem::new_operator(operator));
-                    continue;
-                }
-
-                // handle normal operators
-                while let Some(o2_token) = op_stack.last() {
-                    if o2_token.token_type == TokenType::OpenParen {
-                        // this means a '(' so the program should not continue,
-                        break;
-                    }
-                    if o2_token.token_type != TokenType::Operator {
-                        return Err(Error::new_singular("unexpected error found in op_stack during shunting yard algorithm",
-                        o2_token.clone().start));
-                    }
-                    let o2 = o2_token.op.unwrap();
-                    if o2.precedence() <= operator.precedence() {
-                        // or that o2 has a lower precedence than operator
-                        break;
-                    }
-                    // pop the operator off the stack and push it to the postfix stack
-                    let op = op_stack.pop().unwrap();
-                    postfix.push(ShuntedStackItem::new_operator(op.op.unwrap()));
-                }
-                op_stack.push(token);
-
-                last_op = Some(operator.clone());
+            TokenType::Dot => {
+                tokens.optional_whitespace();
+                parse_property(tokens, expr)?
             }
-            TokenType::OpenParen => {
-                if last_was_num {
-                    return Err(Error::new(
-                        "Unexpected number",
-                        "found value following another value. did you mean to use a binary operator?",
-                        token.start
-                    ));
-                }
-                if last_ident.is_some() {
-                    // todo(eric): function call
-                    parse_fn_call(tokens, last_ident.unwrap())?;
-                    last_was_num = false;
-                    last_ident = None;
-                    last_op = None;
-                    continue;
-                }
-                tokens.consume();
-                op_stack.push(token);
-                // set to an operator not used by this algorithm, because '(' is not an operator
-                last_op = Some(Operator::AddAssign);
-                // todo(eric): handle negative signs before the '('
-                last_was_num = false;
-                last_ident = None;
+            TokenType::DoubleColon => {
+                tokens.optional_whitespace();
+                parse_path(tokens, expr)?
             }
-            TokenType::CloseParen => {
-                let mut found = false;
-                while let Some(op) = op_stack.pop() {
-                    if op.token_type == TokenType::OpenParen {
-                        // this means a '(' so the program should not continue
-                        found = true;
-                        break;
-                    }
-                    if op.token_type != TokenType::Operator {
-                        return Err(Error::new_singular("unexpected error found in op_stack during shunting yard algorithm",
-                                                       tokens.peek().unwrap().start));
-                    }
-                    postfix.push(ShuntedStackItem::new_operator(op.op.unwrap()));
-                }
-
-                if !found {
-                    // probably not a part of the algorithm, most likely a closing function call
-                    // or something similar. If not, the compiler will probably catch it.
-                    break;
-                    // return Err(Error::new(
-                    //     "Mismatched Parentheses",
-                    //     "Close ')' found with no open '('",
-                    //     token.start
-                    // ));
-                }
+            TokenType::OpenBracket if matches!(expr, Statement::Identifier { .. } | Statement::Path { .. })
+                && struct_literal_ahead(tokens) =>
+                parse_struct_literal(tokens, expr)?,
+            TokenType::Operator if matches!(next.op, Some(Operator::Inc) | Some(Operator::Dec)) => {
+                tokens.optional_whitespace();
                 tokens.consume();
-
-                last_op = None;
-                negative = false;
-                last_was_inc = false;
-                last_was_dec = false;
-                last_was_num = false;
-                last_ident = None;
-            }
-            TokenType::Whitespace => {
-                tokens.consume();
-                continue;
-            }
-            _ => {
-                // another operator found, break and continue to the next expression
-                break;
+                Statement::Unary {
+                    op: next.op.unwrap(),
+                    span: tokens.span_from(start_of(&expr)),
+                    expr: Box::new(expr),
+                    postfix: true,
+                    value_type: None
+                }
             }
-        }
+            _ => break
+        };
     }
+    Ok(expr)
+}
 
-    // loop through remaining operators and push them to the postfix stack
-    while let Some(op) = op_stack.pop() {
-        if op.token_type == TokenType::OpenParen {
-            // this means there's a '(' with no closing brace
-            return Err(Error::new(
-                "Open found with no close",
-                "mismatched parentheses",
-                op.start
-            ));
+/// parses a literal, an identifier or an expression in parentheses
+fn parse_primary(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let token = tokens.peek().ok_or_else(|| Error::new("Expected an expression", "found end of file", tokens.eof()))?;
+    match token.token_type {
+        TokenType::NumberLit | TokenType::FloatLit | TokenType::HexLit | TokenType::OctLit | TokenType::BinLit |
+        TokenType::CharLit => {
+            tokens.consume();
+            let span = token.span();
+            number_literal(token, false, span)
         }
-        if op.token_type != TokenType::Operator {
-            return Err(Error::new_singular("unexpected error found in op_stack during shunting yard algorithm",
-            tokens.peek().unwrap().start));
+        TokenType::BoolTrue => ret_literal(tokens, |span| Statement::BoolLiteral { value: true, span }),
+        TokenType::BoolFalse => ret_literal(tokens, |span| Statement::BoolLiteral { value: false, span }),
+        TokenType::StringLit => {
+            tokens.consume();
+            Ok(Statement::StringLiteral { span: token.span(), value: token.value.unwrap() })
         }
-        postfix.push(ShuntedStackItem::new_operator(op.op.unwrap()));
-    }
-
-    Ok(Statement::Postfix {
-        postfix,
-    })
-}
-
-fn parse_number_lit(tokens: &mut TokenList) -> Result<Statement, Error> {
-    let num = tokens.consume().unwrap().value.unwrap();
-    let mut number = Statement::NumberLiteral {
-        value: Number::new(num, false)
-    };
-    tokens.optional_whitespace();
-    let next = tokens.peek();
-    if let Some(next) = tokens.peek() {
-        if next.token_type == TokenType::Dot {
-            number = parse_property(tokens, number)?
+        TokenType::Ident => {
+            tokens.consume();
+            Ok(identifier(token))
         }
-    }
-    if next.is_some() {
-        let next_token = next.unwrap();
-        match next_token.token_type {
-            TokenType::Dot => return parse_property(tokens, number),
-            TokenType::Operator => return shunting_yard(tokens, false, Some(number)),
-            _ => {}
+        TokenType::OpenParen => {
+            tokens.consume(); // remove the '('
+            let expr = parse_binary(tokens, 0, true)?;
+            tokens.expect(TokenType::CloseParen)?;
+            Ok(expr)
         }
+        TokenType::OpenBrace => parse_array_literal(tokens),
+        _ => Err(Error::new("Expected an expression", format!("found: {}", token.token_type), token.start))
     }
-
-    shunting_yard(tokens, false, Some(number))
 }
 
-fn parse_ident_statement(tokens: &mut TokenList, left: Statement, shunt: bool) -> Result<Option<Statement>, Error> {
-    tokens.optional_whitespace();
-    if tokens.peek().is_none() {
-        return Ok(None);
-    }
-    let next = tokens.peek().unwrap();
-    if shunt {
-        match next.token_type {
-            TokenType::OpenParen =>      Ok(Some(parse_fn_call(tokens, left)?)),
-            TokenType::OpenBrace =>      Ok(Some(parse_index(tokens, left)?)),
-            TokenType::Dot =>            Ok(Some(parse_property(tokens, left)?)),
-            TokenType::Operator =>       Ok(Some(shunting_yard(tokens, false, Some(left))?)),
-            _ => Ok(None)
+/// `[a, b, c]`, or `[value; size]` which repeats the value
+fn parse_array_literal(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let start = tokens.consume().unwrap().start; // remove the '['
+    let mut items = Vec::new();
+    if tokens.optional_expect(TokenType::CloseBrace)?.is_none() {
+        let first = parse_binary(tokens, 0, true)?;
+        if tokens.optional_expect(TokenType::NOP)?.is_some() {
+            let size = parse_binary(tokens, 0, true)?;
+            tokens.expect(TokenType::CloseBrace)?;
+            return Ok(Statement::ArrayRepeat { value: Box::new(first), size: Box::new(size), span: tokens.span_from(start) });
         }
-    } else {
-        match next.token_type {
-            TokenType::OpenParen =>      Ok(Some(parse_fn_call(tokens, left)?)),
-            TokenType::OpenBrace =>      Ok(Some(parse_index(tokens, left)?)),
-            TokenType::Dot =>            Ok(Some(parse_property(tokens, left)?)),
-            _ => Ok(None)
+        items.push(first);
+        while tokens.optional_expect(TokenType::Comma)?.is_some() {
+            // a trailing comma is allowed
+            if tokens.next_is(TokenType::CloseBrace) {
+                break;
+            }
+            items.push(parse_binary(tokens, 0, true)?);
         }
+        tokens.expect(TokenType::CloseBrace)?;
     }
-}
-
-fn parse_identifier(tokens: &mut TokenList, shunt: bool) -> Result<Statement, Error> {
-    let ident = Statement::Identifier { ident: tokens.consume().unwrap().value.unwrap() };
-    tokens.optional_whitespace();
-    let mut expr = ident;
-
-    while let Some(stmnt) = parse_ident_statement(tokens, expr.clone(), shunt)? {
-        expr = stmnt;
-    }
-
-    Ok(expr)
+    Ok(Statement::ArrayLiteral { items, span: tokens.span_from(start) })
 }
 
 fn parse_panic(tokens: &mut TokenList) -> Result<Statement, Error> {
-    tokens.consume(); // consume the '?'
-    return if tokens.next_after_ws(TokenType::CloseParen) {
+    let start = tokens.consume().unwrap().start; // consume the '?'
+    // a `?` on its own panics without a value
+    let value = if tokens.newline_ahead() || tokens.next_after_ws(TokenType::CloseParen) ||
+        tokens.next_after_ws(TokenType::CloseBracket) || tokens.peek().is_none() {
         tokens.optional_whitespace();
-        Ok(Statement::Panic { value: Box::new(Statement::Void) })
+        Statement::Void
     } else {
-        Ok(Statement::Panic { value: Box::new(parse_statement(tokens)?) })
-    }
+        parse_statement(tokens)?
+    };
+    Ok(Statement::Panic { value: Box::new(value), value_type: None, span: tokens.span_from(start) })
+}
+
+/// `@0x80`, where the vector can be a number, hex, octal or binary literal
+fn parse_interrupt(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let at = tokens.consume().unwrap(); // consume the '@'
+    let vector = match tokens.peek() {
+        Some(t) if matches!(t.token_type, TokenType::NumberLit | TokenType::HexLit | TokenType::OctLit | TokenType::BinLit) => {
+            let token = tokens.consume().unwrap();
+            let span = token.span();
+            number_literal(token, false, span)?
+        }
+        Some(t) => return Err(Error::new("Expected an interrupt vector",
+                                         format!("found: {}", t.token_type), t.start.clone())),
+        None => return Err(Error::new("Expected an interrupt vector", "found end of file", at.end)),
+    };
+    Ok(Statement::Interrupt { vector: Box::new(vector), span: tokens.span_from(at.start) })
 }
 
 fn ret(tokens: &mut TokenList, expr: Statement) -> Result<Statement, Error> {
@@ -622,7 +1061,23 @@ fn ret(tokens: &mut TokenList, expr: Statement) -> Result<Statement, Error> {
     Ok(expr)
 }
 
+/// a literal made from the next token, such as `true`
+fn ret_literal<F: Fn(Span) -> Statement>(tokens: &mut TokenList, literal: F) -> Result<Statement, Error> {
+    let span = tokens.consume().unwrap().span();
+    Ok(literal(span))
+}
+
+fn identifier(token: Token) -> Statement {
+    Statement::Identifier { span: token.span(), ident: token.value.unwrap() }
+}
+
+/// where an already parsed statement starts, used for statements that begin with another statement
+fn start_of(stmt: &Statement) -> CodePos {
+    stmt.span().map(|s| s.start.clone()).unwrap_or_default()
+}
+
 // statements inside of blocks
+#[allow(clippy::needless_return)]
 fn parse_statement(tokens: &mut TokenList) -> Result<Statement, Error> {
     // todo(eric): add support for string {} things
     if tokens.peek().is_none() { // if there are no more tokens
@@ -631,28 +1086,24 @@ fn parse_statement(tokens: &mut TokenList) -> Result<Statement, Error> {
     tokens.optional_whitespace(); // remove any whitespace
     match tokens.peek().unwrap().token_type {
         TokenType::OpenBracket => parse_block(tokens),
-        TokenType::OpenParen => shunting_yard(tokens, false, None),
+        TokenType::OpenParen | TokenType::OpenBrace => parse_expression(tokens, false),
         TokenType::Let => parse_declaration(tokens), // if the next token is a let, parse the declaration
         TokenType::If => parse_if(tokens),
         TokenType::While => parse_while(tokens),
         TokenType::Loop => parse_loop(tokens),
         TokenType::For => parse_for(tokens),
+        TokenType::Label => parse_labeled_loop(tokens),
+        TokenType::Break => parse_break(tokens),
+        TokenType::Continue => parse_continue(tokens),
         TokenType::Assert => parse_assert(tokens),
+        TokenType::Match => parse_match(tokens),
         TokenType::Return => return parse_return(tokens),
-        TokenType::NumberLit => parse_number_lit(tokens),
-        TokenType::Ident => parse_identifier(tokens, true),
-        TokenType::Operator => shunting_yard(tokens, true, None),
-        //TokenType::Operator => parse_leading_op(tokens),
+        TokenType::NumberLit | TokenType::FloatLit | TokenType::HexLit | TokenType::OctLit | TokenType::BinLit |
+        TokenType::CharLit | TokenType::BoolTrue | TokenType::BoolFalse | TokenType::StringLit | TokenType::Ident |
+        TokenType::Operator => parse_expression(tokens, false),
         TokenType::Panic => parse_panic(tokens),
-        TokenType::BoolTrue => ret(tokens, Statement::BoolLiteral { value: true }),
-        TokenType::BoolFalse => ret(tokens, Statement::BoolLiteral { value: false }),
-        TokenType::BinLit => Ok(Statement::BinaryLiteral { value: tokens.consume().unwrap().value.unwrap() } ),
-        TokenType::HexLit => Ok(Statement::HexLiteral { value: tokens.consume().unwrap().value.unwrap() } ),
+        TokenType::Interrupt => parse_interrupt(tokens),
         TokenType::NOP => ret(tokens, Statement::NOP), // remove semicolons
-        TokenType::StringLit => {
-            let string = tokens.consume().unwrap().value.unwrap();
-            Ok(Statement::StringLiteral { value: string })
-        },
         _ => {
             Err(Error::new("Expected an expression",
                            format!("found: {}", tokens.peek().unwrap().token_type), tokens.peek().unwrap().start))
@@ -669,9 +1120,14 @@ fn parse_global(tokens: &mut TokenList) -> Result<Statement, Error> {
     match tokens.peek().unwrap().token_type { // check the next token
         // blocks have been removed from global space
         //TokenType::OpenBracket => parse_block(tokens), // if the next token is an open block, parse the block
-        TokenType::Fn => parse_fn(tokens), // if the next token is a function, parse the function
+        TokenType::Fn => parse_fn(tokens, None), // if the next token is a function, parse the function
         TokenType::NOP => ret(tokens, Statement::NOP), // if the next token is a no-op, return a no-op (basically removes semicolons)
         TokenType::Use => parse_use(tokens),
+        TokenType::Struct => parse_struct(tokens),
+        TokenType::Impl => parse_impl(tokens),
+        TokenType::Enum => parse_enum(tokens),
+        TokenType::Const | TokenType::Static => parse_global_var(tokens),
+        TokenType::Pub => parse_pub(tokens),
         _ => { // other tokens
             Err(Error::new("Expected an expression",
                            format!("found: {}", tokens.peek().unwrap().token_type), tokens.peek().unwrap().start))
@@ -679,7 +1135,29 @@ fn parse_global(tokens: &mut TokenList) -> Result<Statement, Error> {
     }
 }
 
-fn parse_file(tokens: &mut TokenList) -> Result<Vec<Statement>, Error> {
+/// parses an item after `pub`, which lets other files use it
+fn parse_pub(tokens: &mut TokenList) -> Result<Statement, Error> {
+    let keyword = tokens.consume().unwrap(); // remove the pub token
+    tokens.expect_whitespace()?; // separator between pub and the item
+    let mut item = match tokens.peek().map(|t| t.token_type) {
+        Some(TokenType::Fn) => parse_fn(tokens, None)?,
+        Some(TokenType::Struct) => parse_struct(tokens)?,
+        Some(TokenType::Enum) => parse_enum(tokens)?,
+        Some(TokenType::Const | TokenType::Static) => parse_global_var(tokens)?,
+        _ => return Err(Error::new("Expected an item",
+                                   "only functions, structs, enums, consts and statics can be `pub`", keyword.span()))
+    };
+    match &mut item {
+        Statement::Fn { public, .. } | Statement::Struct { public, .. } | Statement::Enum { public, .. } |
+        Statement::Const { public, .. } | Statement::Static { public, .. } => *public = true,
+        _ => {}
+    }
+    Ok(item)
+}
+
+/// parses every item in a file. Items that fail to parse become error nodes and their errors are
+/// kept in the token list, so every error in the file is found at once
+fn parse_file(tokens: &mut TokenList) -> Vec<Statement> {
     let mut expressions = Vec::new();
 
     // parse the tokens and build the expression tree
@@ -694,15 +1172,30 @@ fn parse_file(tokens: &mut TokenList) -> Result<Vec<Statement>, Error> {
                 break; // break out of the loop as there are no more tokens to parse
             },
             _ => { // parse other tokens into expressions
-                expressions.push(parse_global(tokens)?); // parse the next few tokens further into the AST
+                let start = token.start.clone();
+                let remaining = tokens.tokens.len();
+                // parse the next few tokens further into the AST
+                match parse_global(tokens) {
+                    Ok(expr) => expressions.push(expr),
+                    Err(e) => expressions.push(recover(tokens, e, start, remaining, false)),
+                }
             }
         }
     }
 
     // return the abstract syntax tree
-    Ok(expressions)
+    expressions
 }
 
-pub fn parse(tokens: &mut TokenList) -> Result<Statement, Error> {
-    Ok(Statement::Program { exprs: parse_file(tokens)? } )
+/// parses a whole program, returning every syntax error in it if there are any
+pub fn parse(tokens: &mut TokenList) -> Result<Statement, Vec<Error>> {
+    let start = tokens.next_start();
+    if let Some(file) = &start.file {
+        tokens.modules.enter_root(file);
+    }
+    let exprs = parse_file(tokens);
+    if !tokens.errors.is_empty() {
+        return Err(std::mem::take(&mut tokens.errors));
+    }
+    Ok(Statement::Program { exprs, span: tokens.span_from(start) } )
 }
\ No newline at end of file
@@ -1,244 +1,408 @@
-use std::fmt::{Display, format, Formatter};
+use std::fmt::{Display, Formatter};
 use cli_tree::TreeNode;
+use crate::Span;
 use crate::operator::Operator;
+use crate::types::Type;
 
+/// An integer literal, parsed from any of the bases it can be written in
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Number {
-    pub value: String,
+    /// the value without its sign
+    pub value: u64,
     pub negative: bool,
+    /// the base the literal was written in: 2, 8, 10 or 16
+    pub radix: u32,
+    /// the type given by a suffix, such as `u8` in `10u8`
+    pub suffix: Option<Type>,
 }
 
 impl Number {
-    pub fn new(value: String, negative: bool) -> Number {
+    pub fn new(value: u64, negative: bool) -> Number {
         Number {
             value,
             negative,
+            radix: 10,
+            suffix: None,
         }
     }
+
+    /// parses the digits of a number token followed by its suffix, if it has one.
+    /// Returns None if the value is too large to fit in 64 bits
+    pub fn parse(text: &str, radix: u32, negative: bool) -> Option<Number> {
+        // no digit in any base is a 'u' or an 'i', so the suffix starts at the first one
+        let split = text.find(['u', 'i']).unwrap_or(text.len());
+        let (digits, suffix) = text.split_at(split);
+        Some(Number {
+            value: u64::from_str_radix(digits, radix).ok()?,
+            negative,
+            radix,
+            suffix: Type::from_name(suffix),
+        })
+    }
+
+    /// the value with its sign applied
+    pub fn signed_value(&self) -> i128 {
+        if self.negative { -(self.value as i128) } else { self.value as i128 }
+    }
 }
 
 impl Display for Number {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         if self.negative {
-            write!(f, "-{}", self.value)
-        } else {
-            write!(f, "{}", self.value)
+            write!(f, "-")?;
+        }
+        match self.radix {
+            2 => write!(f, "0b{:b}", self.value)?,
+            8 => write!(f, "0o{:o}", self.value)?,
+            16 => write!(f, "0x{:X}", self.value)?,
+            _ => write!(f, "{}", self.value)?,
+        }
+        if let Some(suffix) = &self.suffix {
+            write!(f, "{}", suffix)?;
         }
+        Ok(())
     }
 }
 
+/// A float literal, kept as it was written so no precision is lost before it reaches C
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ShuntedStackItem {
-    operator: Option<Operator>,
-    operand: Option<Statement>,
+pub struct Float {
+    pub value: String,
+    pub negative: bool,
+    /// set by the type checker when the literal is used as an `f32`, so it is rounded to one
+    pub single: bool,
 }
 
-impl ShuntedStackItem {
-    pub fn new_operand(statement: Statement) -> Self {
-        Self {
-            operator: None,
-            operand: Some(statement),
-        }
-    }
-
-    pub fn new_operator(operator: Operator) -> Self {
-        Self {
-            operator: Some(operator),
-            operand: None,
-        }
-    }
-
-    pub fn is_operator(&self) -> bool {
-        self.operator.is_some()
-    }
-
-    pub fn is_operand(&self) -> bool {
-        self.operand.is_some()
-    }
-
-    pub fn get_operator(&self) -> Option<&Operator> {
-        self.operator.as_ref()
-    }
-
-    pub fn get_operand(&self) -> Option<&Statement> {
-        self.operand.as_ref()
-    }
-
-    pub fn as_treenode(&self) -> TreeNode {
-        if let Some(operator) = &self.operator {
-            operator.as_treenode()
-        } else if let Some(operand) = &self.operand {
-            operand.as_treenode()
-        } else {
-            TreeNode::new("<error>")
+impl Float {
+    pub fn new(value: String, negative: bool) -> Float {
+        Float {
+            value,
+            negative,
+            single: false,
         }
     }
 }
 
-impl Display for ShuntedStackItem {
+impl Display for Float {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        if self.is_operator() {
-            write!(f, "{}", self.get_operator().unwrap())
+        if self.negative {
+            write!(f, "-{}", self.value)
         } else {
-            write!(f, "{}", self.get_operand().unwrap())
+            write!(f, "{}", self.value)
         }
     }
 }
 
+/// A node of the AST. Every node that comes from source code carries the span it was parsed from,
+/// which is in the file it was written in, even after a `use` puts it in another file's AST
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ShuntedStack {
-    items: Vec<ShuntedStackItem>,
-}
-
-impl ShuntedStack {
-    pub fn new() -> Self {
-        Self {
-            items: Vec::new(),
-        }
-    }
-
-    pub fn push(&mut self, item: ShuntedStackItem) {
-        self.items.push(item);
-    }
-
-    pub fn pop(&mut self) -> Option<ShuntedStackItem> {
-        self.items.pop()
-    }
-
-    pub fn peek(&self) -> Option<&ShuntedStackItem> {
-        self.items.last()
-    }
-
-    pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
-    }
+pub enum Statement {
+    Program{ exprs: Vec<Statement>, span: Span }, // program - contains the expressions of the program
+    // file - contains the expressions of the file, which are empty if an earlier `use` already loaded it.
+    // path is the file that was loaded and alias is the name from `as`, which the file's items can be used through
+    Use { exprs: Vec<Statement>, path: String, alias: Option<Box<Statement>>, span: Span },
+    Block { exprs: Vec<Statement>, span: Span }, // block - holds a list of contained expressions
+    // public is set by `pub`, without it the function can only be used in the file it is in
+    Fn {ident: Box<Statement>, params: Vec<Statement>, return_type: Box<Statement>, body: Box<Statement>, public: bool,
+        span: Span },
+    // parameters are Declaration expressions, where if there is an assignment, its the default value
+    FnCall { ident: Box<Statement>, params: Vec<Statement>, span: Span },
+    // fields are Declaration expressions without values
+    Struct { ident: Box<Statement>, fields: Vec<Statement>, public: bool, span: Span },
+    // fields are Assignment expressions from the field name to its value
+    StructLiteral { ident: Box<Statement>, fields: Vec<Statement>, span: Span },
+    ArrayLiteral { items: Vec<Statement>, span: Span }, // `[a, b, c]`
+    // `[value; size]`, an array holding `size` copies of the value. the size is a constant
+    ArrayRepeat { value: Box<Statement>, size: Box<Statement>, span: Span },
+    // methods are Fn expressions whose first parameter is `self`
+    Impl { ident: Box<Statement>, methods: Vec<Statement>, span: Span },
+    // variants are Declaration expressions, where the value is the variant's discriminant if it has one
+    Enum { ident: Box<Statement>, variants: Vec<Statement>, public: bool, span: Span },
+    // `base::member`, such as `Color::Red`
+    Path { base: Box<Statement>, member: Box<Statement>, span: Span },
+    // arms are MatchArm expressions. value_type is filled in by the type checker
+    Match { value: Box<Statement>, arms: Vec<Statement>, value_type: Option<Box<Statement>>, span: Span },
+    // `pattern => body`, where the pattern is a literal, Path, RangePattern or Wildcard
+    MatchArm { pattern: Box<Statement>, body: Box<Statement>, span: Span },
+    RangePattern { start: Box<Statement>, end: Box<Statement>, inclusive: bool, span: Span },
+    Wildcard { span: Span }, // `_` in a match pattern
+    // `receiver.method(params)`. receiver_type is filled in by the type checker
+    MethodCall { receiver: Box<Statement>, method: Box<Statement>, params: Vec<Statement>,
+                 receiver_type: Option<Box<Statement>>, span: Span },
+    If {condition: Box<Statement>, body: Box<Statement>, else_statement: Option<Box<Statement>>, span: Span }, // else is optional
+    Return { value: Box<Statement>, span: Span },
+    // `op expr` for prefix operators, or `expr op` when postfix is set, which only `++` and `--` can be.
+    // value_type is the type of expr, filled in by the type checker so the result can wrap to fit it and
+    // so the interpreter can lay out the value `&` takes the address of
+    Unary { op: Operator, expr: Box<Statement>, postfix: bool, value_type: Option<Box<Statement>>, span: Span },
+    // `left op right`. value_type is the type both sides become, or the left side's type for shifts, filled
+    // in by the type checker so the result can wrap to fit it
+    Binary { op: Operator, left: Box<Statement>, right: Box<Statement>, value_type: Option<Box<Statement>>, span: Span },
+    // `expr as type`, where the type is a Type statement
+    Cast { expr: Box<Statement>, cast_type: Box<Statement>, span: Span },
+
+    // value_type is filled in by the type checker so backends know how to print the value
+    Panic { value: Box<Statement>, value_type: Option<Box<Statement>>, span: Span },
+    Assert { expr: Box<Statement>, span: Span },
+    // `@vector`, raises a system interrupt. the vector is a number, hex or binary literal
+    Interrupt { vector: Box<Statement>, span: Span },
+
+    Declaration { ident: Box<Statement>, type_ident: Option<Box<Statement>>, value: Option<Box<Statement>>, span: Span },
+    // `const NAME: type = value`. the checker evaluates the value and puts it in place of every use of the name
+    Const { ident: Box<Statement>, type_ident: Box<Statement>, value: Box<Statement>, public: bool, span: Span },
+    // `static NAME: type = value` or `static mut`, a global variable whose value must be a constant
+    Static { ident: Box<Statement>, type_ident: Box<Statement>, value: Box<Statement>, mutable: bool, public: bool,
+             span: Span },
+    Assignment { ident: Box<Statement>, value: Box<Statement>, span: Span },
+    // `ident op= value`, where op is the operator applied, such as `+` for `+=`. value_type is filled in by the
+    // type checker like it is for Binary
+    CompoundAssignment { op: Operator, ident: Box<Statement>, value: Box<Statement>, value_type: Option<Box<Statement>>,
+                         span: Span },
+    // through_pointer is set by the type checker when expr is a reference or pointer to a struct
+    PropertyAccess { expr: Box<Statement>, property: Box<Statement>, through_pointer: bool, span: Span },
+    // length is filled in by the type checker when an array is indexed, so backends can check the index, and
+    // through_pointer when the array is indexed through a reference to it
+    ArrayAccess { ident: Box<Statement>, index: Box<Statement>, length: Option<usize>, through_pointer: bool, span: Span },
+
+    // Loops. label is the identifier from `'label:` before the loop, without the quote
+    While { condition: Box<Statement>, body: Box<Statement>, label: Option<Box<Statement>>, span: Span },
+    // `for ident in collection step n`, where the step is optional. the element type is the type of
+    // `ident`, length is the length of the array iterated over, and through_pointer is set when it is iterated
+    // over through a reference, all filled in by the type checker
+    For { ident: Box<Statement>, collection: Box<Statement>, step: Option<Box<Statement>>, body: Box<Statement>,
+          label: Option<Box<Statement>>, element_type: Option<Box<Statement>>, length: Option<usize>,
+          through_pointer: bool, span: Span },
+    Loop { body: Box<Statement>, label: Option<Box<Statement>>, span: Span },
+    // `break 'label value`, where both are optional. the value is what a `loop` gives
+    Break { label: Option<Box<Statement>>, value: Option<Box<Statement>>, span: Span },
+    Continue { label: Option<Box<Statement>>, span: Span }, // `continue 'label`, where the label is optional
 
-    pub fn len(&self) -> usize {
-        self.items.len()
-    }
+    // Literals / identifiers
+    Type { modifiers: Vec<Statement>, type_ident: Box<Statement>, span: Span },
+    ArrayType { array_type: Box<Statement>, size: Box<Statement>, modifiers: Vec<Statement>, span: Span },
+    Identifier { ident: String, span: Span }, // identifier - holds the name of the identifier and where it is
+    StringLiteral { value: String, span: Span }, // string literal - holds the string
+    NumberLiteral { value: Number, span: Span }, // number literal - holds the number, its base and its suffix
+    FloatLiteral { value: Float, span: Span }, // float literal - holds the number with its decimal point or exponent
+    CharLiteral { value: char, span: Span }, // char literal - holds the character
+    BoolLiteral { value: bool, span: Span }, // boolean literal - holds the boolean
+
+    // code that failed to parse, kept so the rest of the file can still be parsed and checked for errors
+    Error { span: Span },
+
+    // markers with no source of their own, they are covered by the span of the node holding them
+    Reference,
+    Pointer,
+    NOP, // nop - holds nothing
+    Void, // void - holds nothing, means nothing
+}
 
-    pub fn as_treenode(&self) -> TreeNode {
-        let mut tree_nodes = TreeNode::new("Postfix");
-        for item in &self.items {
-            tree_nodes.add_child(item.as_treenode());
+impl Statement {
+    /// the source code the statement was parsed from
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            Statement::Program { span, .. } | Statement::Use { span, .. } | Statement::Block { span, .. } |
+            Statement::Fn { span, .. } | Statement::FnCall { span, .. } | Statement::Struct { span, .. } |
+            Statement::StructLiteral { span, .. } | Statement::ArrayLiteral { span, .. } |
+            Statement::ArrayRepeat { span, .. } | Statement::Impl { span, .. } | Statement::Enum { span, .. } |
+            Statement::Path { span, .. } | Statement::Match { span, .. } | Statement::MatchArm { span, .. } |
+            Statement::RangePattern { span, .. } | Statement::Wildcard { span } | Statement::MethodCall { span, .. } |
+            Statement::If { span, .. } | Statement::Return { span, .. } | Statement::Unary { span, .. } |
+            Statement::Binary { span, .. } | Statement::Cast { span, .. } |
+            Statement::Panic { span, .. } | Statement::Assert { span, .. } | Statement::Interrupt { span, .. } |
+            Statement::Declaration { span, .. } | Statement::Assignment { span, .. } |
+            Statement::CompoundAssignment { span, .. } |
+            Statement::Const { span, .. } | Statement::Static { span, .. } |
+            Statement::PropertyAccess { span, .. } | Statement::ArrayAccess { span, .. } |
+            Statement::While { span, .. } | Statement::For { span, .. } | Statement::Loop { span, .. } |
+            Statement::Break { span, .. } | Statement::Continue { span, .. } | Statement::Type { span, .. } |
+            Statement::ArrayType { span, .. } | Statement::Identifier { span, .. } |
+            Statement::StringLiteral { span, .. } | Statement::NumberLiteral { span, .. } |
+            Statement::FloatLiteral { span, .. } | Statement::CharLiteral { span, .. } |
+            Statement::BoolLiteral { span, .. } | Statement::Error { span } => Some(span),
+            Statement::Reference | Statement::Pointer | Statement::NOP | Statement::Void => None,
         }
-        tree_nodes
     }
-}
 
-impl Display for ShuntedStack {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut result = String::new();
-        for item in self.items.iter() {
-            result.push_str(&format!("{}", item));
+    /// every statement this one holds, including the types the type checker filled in
+    pub fn children(&self) -> Vec<&Statement> {
+        let mut children: Vec<&Statement> = Vec::new();
+        match self {
+            Statement::Program { exprs, .. } | Statement::Use { exprs, .. } | Statement::Block { exprs, .. } => {
+                children.extend(exprs)
+            }
+            Statement::Fn { ident, params, return_type, body, .. } => {
+                children.push(ident);
+                children.extend(params);
+                children.extend([return_type.as_ref(), body.as_ref()]);
+            }
+            Statement::FnCall { ident, params, .. } => {
+                children.push(ident);
+                children.extend(params);
+            }
+            Statement::Struct { ident, fields, .. } | Statement::StructLiteral { ident, fields, .. } => {
+                children.push(ident);
+                children.extend(fields);
+            }
+            Statement::ArrayLiteral { items, .. } => children.extend(items),
+            Statement::ArrayRepeat { value, size, .. } => children.extend([value.as_ref(), size.as_ref()]),
+            Statement::Impl { ident, methods, .. } => {
+                children.push(ident);
+                children.extend(methods);
+            }
+            Statement::Enum { ident, variants, .. } => {
+                children.push(ident);
+                children.extend(variants);
+            }
+            Statement::Path { base, member, .. } => children.extend([base.as_ref(), member.as_ref()]),
+            Statement::Match { value, arms, value_type, .. } => {
+                children.push(value);
+                children.extend(arms);
+                children.extend(value_type.as_deref());
+            }
+            Statement::MatchArm { pattern, body, .. } => children.extend([pattern.as_ref(), body.as_ref()]),
+            Statement::RangePattern { start, end, .. } => children.extend([start.as_ref(), end.as_ref()]),
+            Statement::MethodCall { receiver, method, params, receiver_type, .. } => {
+                children.extend([receiver.as_ref(), method.as_ref()]);
+                children.extend(params);
+                children.extend(receiver_type.as_deref());
+            }
+            Statement::If { condition, body, else_statement, .. } => {
+                children.extend([condition.as_ref(), body.as_ref()]);
+                children.extend(else_statement.as_deref());
+            }
+            Statement::Return { value, .. } | Statement::Interrupt { vector: value, .. } => children.push(value),
+            Statement::Unary { expr, value_type, .. } => {
+                children.push(expr);
+                children.extend(value_type.as_deref());
+            }
+            Statement::Binary { left, right, value_type, .. } => {
+                children.extend([left.as_ref(), right.as_ref()]);
+                children.extend(value_type.as_deref());
+            }
+            Statement::Cast { expr, cast_type, .. } => children.extend([expr.as_ref(), cast_type.as_ref()]),
+            Statement::Panic { value, value_type, .. } => {
+                children.push(value);
+                children.extend(value_type.as_deref());
+            }
+            Statement::Assert { expr, .. } => children.push(expr),
+            Statement::Declaration { ident, type_ident, value, .. } => {
+                children.push(ident);
+                children.extend(type_ident.as_deref());
+                children.extend(value.as_deref());
+            }
+            Statement::Const { ident, type_ident, value, .. } | Statement::Static { ident, type_ident, value, .. } => {
+                children.extend([ident.as_ref(), type_ident.as_ref(), value.as_ref()]);
+            }
+            Statement::Assignment { ident, value, .. } => children.extend([ident.as_ref(), value.as_ref()]),
+            Statement::CompoundAssignment { ident, value, value_type, .. } => {
+                children.extend([ident.as_ref(), value.as_ref()]);
+                children.extend(value_type.as_deref());
+            }
+            Statement::PropertyAccess { expr, property, .. } => children.extend([expr.as_ref(), property.as_ref()]),
+            Statement::ArrayAccess { ident, index, .. } => children.extend([ident.as_ref(), index.as_ref()]),
+            Statement::While { condition, body, label, .. } => {
+                children.extend([condition.as_ref(), body.as_ref()]);
+                children.extend(label.as_deref());
+            }
+            Statement::For { ident, collection, step, body, label, element_type, .. } => {
+                children.extend([ident.as_ref(), collection.as_ref()]);
+                children.extend(step.as_deref());
+                children.push(body);
+                children.extend(label.as_deref());
+                children.extend(element_type.as_deref());
+            }
+            Statement::Loop { body, label, .. } => {
+                children.push(body);
+                children.extend(label.as_deref());
+            }
+            Statement::Break { label, value, .. } => {
+                children.extend(label.as_deref());
+                children.extend(value.as_deref());
+            }
+            Statement::Continue { label, .. } => children.extend(label.as_deref()),
+            Statement::Type { modifiers, type_ident, .. } => {
+                children.extend(modifiers);
+                children.push(type_ident);
+            }
+            Statement::ArrayType { array_type, size, modifiers, .. } => {
+                children.extend([array_type.as_ref(), size.as_ref()]);
+                children.extend(modifiers);
+            }
+            Statement::Wildcard { .. } | Statement::Identifier { .. } | Statement::StringLiteral { .. } |
+            Statement::NumberLiteral { .. } | Statement::FloatLiteral { .. } | Statement::CharLiteral { .. } |
+            Statement::BoolLiteral { .. } | Statement::Error { .. } | Statement::Reference | Statement::Pointer |
+            Statement::NOP | Statement::Void => {}
         }
-        write!(f, "{}", result)
+        children
     }
 }
 
-impl Iterator for ShuntedStack {
-    type Item = ShuntedStackItem;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.items.pop()
+/// ` 'label` for a loop, break or continue with a label, used when displaying them
+fn label_name(label: &Option<Box<Statement>>) -> String {
+    match label.as_deref() {
+        Some(Statement::Identifier { ident, .. }) => format!(" '{}", ident),
+        _ => String::new()
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Statement {
-    Program{ exprs: Vec<Statement> }, // program - contains the expressions of the program
-    Use { exprs: Vec<Statement> }, // file - contains the expressions of the file
-    Block { exprs: Vec<Statement> }, // block - holds a list of contained expressions
-    Fn {ident: Box<Statement>, params: Vec<Statement>, return_type: Box<Statement>, body: Box<Statement> },
-    // parameters are Declaration expressions, where if there is an assignment, its the default value
-    FnCall { ident: Box<Statement>, params: Vec<Statement> },
-    If {condition: Box<Statement>, body: Box<Statement>, else_statement: Option<Box<Statement>> }, // else is optional
-    Return { value: Box<Statement> },
-    Postfix { postfix: ShuntedStack },
-
-    Panic { value: Box<Statement> },
-    Assert { expr: Box<Statement> },
-
-    Declaration { ident: Box<Statement>, type_ident: Option<Box<Statement>>, value: Option<Box<Statement>> },
-    Assignment { ident: Box<Statement>, value: Box<Statement> },
-    PropertyAccess { expr: Box<Statement>, property: Box<Statement> },
-    ArrayAccess { ident: Box<Statement>, index: Box<Statement> },
-
-    // Loops
-    While { condition: Box<Statement>, body: Box<Statement> },
-    For { ident: Box<Statement>, collection: Box<Statement>, body: Box<Statement> },
-    Loop { body: Box<Statement> },
-    Break, // break - holds nothing
-    Continue, // continue - holds nothing
-
-    // Literals / identifiers
-    Type { modifiers: Vec<Statement>, type_ident: Box<Statement> },
-    ArrayType { array_type: Box<Statement>, size: Box<Statement>, modifiers: Vec<Statement> },
-    Identifier { ident: String }, // identifier - holds the name of the identifier
-    StringLiteral { value: String }, // string literal - holds the string
-    NumberLiteral { value: Number }, // number literal - holds the number todo(eric): expand this to different number types
-    BinaryLiteral { value: String }, // binary literal - holds the binary literal
-    HexLiteral { value: String }, // hexadecimal literal - holds the binary literal
-    CharLiteral { value: char }, // char literal - holds the character
-    BoolLiteral { value: bool }, // boolean literal - holds the boolean
-
-    Reference,
-    Pointer,
-    NOP, // nop - holds nothing
-    Void, // void - holds nothing, means nothing
-}
-
+#[allow(clippy::useless_format)]
 fn output_params(params: &Vec<Statement>, depth: &usize, indent: &String) -> String {
     let mut param_out = format!("");
     if !params.is_empty() {
         param_out = format!("{}  - Parameters:\n", indent);
         for p in params {
-            param_out += &p.display(depth + 3).as_str();
+            param_out += p.display(depth + 3).as_str();
         }
     }
     param_out
 }
 
 impl Statement {
+    #[allow(clippy::useless_format)]
     fn display_modifiers(&self, mods: &Vec<Statement>, indent: String, depth: usize) -> String {
         let mut mods_str = format!("");
         if !mods.is_empty() {
             mods_str = format!("{}  - Modifiers:\n", indent);
             for m in mods {
-                mods_str += &m.display(depth + 3).as_str();
+                mods_str += m.display(depth + 3).as_str();
             }
         }
         mods_str
     }
 
+    #[allow(clippy::needless_return, clippy::useless_format)]
     pub fn display(&self, depth: usize) -> String {
         let indent = "  ".repeat(depth);
         match self {
-            Statement::Program { exprs } => {
+            Statement::Program { exprs, .. } => {
                 let mut output = format!("{indent}- Program:\n");
                 for ex in exprs {
-                    output += &ex.display(depth + 1).as_str();
+                    output += ex.display(depth + 1).as_str();
                 }
                 output
             }
-            Statement::Use { exprs } => {
-               let mut output = format!("{indent}- Import:\n");
+            Statement::Use { exprs, path, alias, .. } => {
+               let alias = match alias.as_deref() {
+                   Some(Statement::Identifier { ident, .. }) => format!(" as {}", ident),
+                   _ => String::new()
+               };
+               let mut output = format!("{indent}- Import `{}`{}:\n", path, alias);
                for ex in exprs {
-                   output += &ex.display(depth + 1).as_str();
+                   output += ex.display(depth + 1).as_str();
                }
                output
             }
-            Statement::Block { exprs } => {
+            Statement::Block { exprs, .. } => {
                 let mut output = format!("{indent}- Block:\n");
                 for ex in exprs {
-                    output += &ex.display(depth + 1).as_str();
+                    output += ex.display(depth + 1).as_str();
                 }
                 output
             }
             Statement::Fn {
                 ident, params,
-                return_type: ret, body
-            } => {
+                return_type: ret, body, .. } => {
                 let param_out = output_params(params, &depth, &indent);
                 format!("{indent}- Function:\n{indent}  - Ident:\n{}{}{indent}  - Returns:\n{}{indent}  - Body:\n{}",
                         ident.display(depth + 2),
@@ -246,21 +410,96 @@ impl Statement {
                         ret.display(depth + 2),
                         body.display(depth + 2))
             }
-            Statement::FnCall { ident, params } => {
+            Statement::FnCall { ident, params, .. } => {
                 let param_out = output_params(params, &depth, &indent);
                 format!("{indent}- Function Call:\n{indent}  - Ident:\n{}{}",
                         ident.display(depth + 2),
                         param_out)
             }
-            Statement::Panic { value } => {
+            Statement::Struct { ident, fields, .. } => {
+                let mut output = format!("{indent}- Struct:\n{indent}  - Ident:\n{}{indent}  - Fields:\n",
+                                         ident.display(depth + 2));
+                for f in fields {
+                    output += f.display(depth + 3).as_str();
+                }
+                output
+            }
+            Statement::StructLiteral { ident, fields, .. } => {
+                let mut output = format!("{indent}- Struct Literal:\n{indent}  - Ident:\n{}{indent}  - Fields:\n",
+                                         ident.display(depth + 2));
+                for f in fields {
+                    output += f.display(depth + 3).as_str();
+                }
+                output
+            }
+            Statement::ArrayLiteral { items, .. } => {
+                let mut output = format!("{indent}- Array Literal:\n");
+                for i in items {
+                    output += i.display(depth + 1).as_str();
+                }
+                output
+            }
+            Statement::ArrayRepeat { value, size, .. } => {
+                format!("{indent}- Array Repeat:\n{indent}  - Value:\n{}{indent}  - Size:\n{}",
+                        value.display(depth + 2),
+                        size.display(depth + 2))
+            }
+            Statement::Impl { ident, methods, .. } => {
+                let mut output = format!("{indent}- Impl:\n{indent}  - Ident:\n{}{indent}  - Methods:\n",
+                                         ident.display(depth + 2));
+                for m in methods {
+                    output += m.display(depth + 3).as_str();
+                }
+                output
+            }
+            Statement::MethodCall { receiver, method, params, .. } => {
+                let param_out = output_params(params, &depth, &indent);
+                format!("{indent}- Method Call:\n{indent}  - Receiver:\n{}{indent}  - Method:\n{}{}",
+                        receiver.display(depth + 2),
+                        method.display(depth + 2),
+                        param_out)
+            }
+            Statement::Enum { ident, variants, .. } => {
+                let mut output = format!("{indent}- Enum:\n{indent}  - Ident:\n{}{indent}  - Variants:\n",
+                                         ident.display(depth + 2));
+                for v in variants {
+                    output += v.display(depth + 3).as_str();
+                }
+                output
+            }
+            Statement::Path { base, member, .. } => {
+                format!("{indent}- Path:\n{}{}", base.display(depth + 1), member.display(depth + 1))
+            }
+            Statement::Match { value, arms, .. } => {
+                let mut output = format!("{indent}- Match:\n{indent}  - Value:\n{}{indent}  - Arms:\n",
+                                         value.display(depth + 2));
+                for a in arms {
+                    output += a.display(depth + 3).as_str();
+                }
+                output
+            }
+            Statement::MatchArm { pattern, body, .. } => {
+                format!("{indent}- Arm:\n{indent}  - Pattern:\n{}{indent}  - Body:\n{}",
+                        pattern.display(depth + 2),
+                        body.display(depth + 2))
+            }
+            Statement::RangePattern { start, end, inclusive, .. } => {
+                format!("{indent}- Range Pattern{}:\n{}{}", if *inclusive { " (inclusive)" } else { "" },
+                        start.display(depth + 1), end.display(depth + 1))
+            }
+            Statement::Wildcard { .. } => format!("{indent}- Wildcard\n"),
+            Statement::Panic { value, .. } => {
                 format!("{indent}- Panic:\n{}", value.display(depth + 1))
             }
-            Statement::Assert { expr } => {
+            Statement::Assert { expr, .. } => {
                 format!("{indent}- Assert:\n{}", expr.display(depth + 1))
             }
+            Statement::Interrupt { vector, .. } => {
+                format!("{indent}- Interrupt:\n{}", vector.display(depth + 1))
+            }
             Statement::If {
                 condition, body,
-                else_statement } => {
+                else_statement, .. } => {
                 format!("{indent}- If:\n{indent}  - Condition:\n{}{indent}  - Body:\n{}{}",
                         condition.display(depth + 2),
                         body.display(depth + 2),
@@ -270,14 +509,20 @@ impl Statement {
                             format!("")
                         })
             }
-            Statement::Return { value } => {
+            Statement::Return { value, .. } => {
                 format!("{indent}- Return:\n{indent}  - Value:\n{}", value.display(depth + 2))
             }
-            Statement::Postfix { postfix: shunted } => {
-                format!("{indent}- Postfix:\n{indent}  - Shunted:\n{}", shunted)
+            Statement::Unary { op, expr, postfix, .. } => {
+                format!("{indent}- Unary{}: {}\n{}", if *postfix { " (postfix)" } else { "" }, op, expr.display(depth + 1))
+            }
+            Statement::Binary { op, left, right, .. } => {
+                format!("{indent}- Binary: {}\n{}{}", op, left.display(depth + 1), right.display(depth + 1))
+            }
+            Statement::Cast { expr, cast_type, .. } => {
+                format!("{indent}- Cast:\n{}{indent}  - Type:\n{}", expr.display(depth + 1), cast_type.display(depth + 2))
             }
             Statement::Declaration {
-                ident, type_ident, value} => {
+                ident, type_ident, value, .. } => {
                 format!("{indent}- Declaration:\n{}{}{}",
                         ident.display(depth + 1),
                         if type_ident.is_some() {
@@ -291,58 +536,82 @@ impl Statement {
                             format!("{indent}  - Value: None\n")
                         })
             }
-            Statement::Assignment { ident, value } => {
+            Statement::Const { ident, type_ident, value, .. } => {
+                format!("{indent}- Const:\n{}{indent}  - Type:\n{}{indent}  - Value:\n{}",
+                        ident.display(depth + 1), type_ident.display(depth + 2), value.display(depth + 2))
+            }
+            Statement::Static { ident, type_ident, value, mutable, .. } => {
+                format!("{indent}- Static{}:\n{}{indent}  - Type:\n{}{indent}  - Value:\n{}", if *mutable { " (mut)" } else { "" },
+                        ident.display(depth + 1), type_ident.display(depth + 2), value.display(depth + 2))
+            }
+            Statement::Assignment { ident, value, .. } => {
                 format!("{indent}- Assignment:\n{}{indent}  - Value:\n{}",
                         ident.display(depth + 1),
                         value.display(depth + 2))
             }
-            Statement::PropertyAccess { expr, property } => {
+            Statement::CompoundAssignment { op, ident, value, .. } => {
+                format!("{indent}- Compound Assignment: {}\n{}{indent}  - Value:\n{}", op,
+                        ident.display(depth + 1),
+                        value.display(depth + 2))
+            }
+            Statement::PropertyAccess { expr, property, .. } => {
                 format!("{indent}- Property Access:\n{indent}  - Expression:\n{}{indent}  - Property:\n{}",
                         expr.display(depth + 2),
                         property.display(depth + 2))
             }
-            Statement::ArrayAccess { ident, index } => {
+            Statement::ArrayAccess { ident, index, .. } => {
                 format!("{indent}- Array Access:\n{}{indent}  - Index:\n{}",
                         ident.display(depth + 1),
                         index.display(depth + 2))
             }
-            Statement::While { condition, body } => {
+            Statement::While { condition, body, .. } => {
                 format!("{indent}- While:\n{indent}  - Condition:\n{}{indent}  - Body:\n{}",
                         condition.display(depth + 2),
                         body.display(depth + 2))
             }
-            Statement::For { ident, collection,
-                body } => {
-                format!("{indent}- For:\n{indent}  - Ident:\n{}{indent}  - Collection:\n{}{indent}  - Body:\n{}",
+            Statement::For { ident, collection, step,
+                body, .. } => {
+                let step = match step {
+                    Some(step) => format!("{indent}  - Step:\n{}", step.display(depth + 2)),
+                    None => String::new()
+                };
+                format!("{indent}- For:\n{indent}  - Ident:\n{}{indent}  - Collection:\n{}{}{indent}  - Body:\n{}",
                         ident.display(depth + 2),
                         collection.display(depth + 2),
+                        step,
                         body.display(depth + 2))
             }
-            Statement::Loop { body } => {
+            Statement::Loop { body, .. } => {
                 format!("{indent}- Loop:\n{indent}  - Body:\n{}", body.display(depth + 2))
             }
-            Statement::Break => return format!("{indent}- Break\n"),
-            Statement::Continue => return format!("{indent}- Continue\n"),
-            Statement::Type { modifiers, type_ident } => {
-                let mut mods = self.display_modifiers(modifiers, indent.clone(), depth);
+            Statement::Break { label, value, .. } => {
+                let mut output = format!("{indent}- Break{}\n", label_name(label));
+                if let Some(value) = value {
+                    output += &format!("{indent}  - Value:\n{}", value.display(depth + 2));
+                }
+                output
+            }
+            Statement::Continue { label, .. } => format!("{indent}- Continue{}\n", label_name(label)),
+            Statement::Type { modifiers, type_ident, .. } => {
+                let mods = self.display_modifiers(modifiers, indent.clone(), depth);
                 format!("{indent}-  Type:\n{}{}",
                         type_ident.display(depth + 2),
                         mods)
             }
-            Statement::ArrayType { array_type: type_ident, size, modifiers } => {
-                let mut mods = self.display_modifiers(modifiers, indent.clone(), depth);
-                format!("{indent}-  Array Type:\n{}{}{}",
+            Statement::ArrayType { array_type: type_ident, size, modifiers, .. } => {
+                let mods = self.display_modifiers(modifiers, indent.clone(), depth);
+                format!("{indent}-  Array Type:\n{}{indent}  - Size:\n{}{}",
                         type_ident.display(depth + 2),
-                        format!("{indent}  - Size:\n{}", size.display(depth + 3)),
+                        size.display(depth + 3),
                         mods)
             }
-            Statement::Identifier { ident } => return format!("{indent}- Identifier: {}\n", ident),
-            Statement::StringLiteral { value } => return format!("{indent}- StringLiteral: \"{}\"\n", value),
-            Statement::NumberLiteral { value } => return format!("{indent}- NumberLiteral: {}\n", value),
-            Statement::BinaryLiteral { value } => return format!("{indent}- BinaryLiteral: {}\n", value),
-            Statement::HexLiteral { value } => return format!("{indent}- HexLiteral: {}\n", value),
-            Statement::CharLiteral { value } => return format!("{indent}- CharLiteral: {}\n", value),
-            Statement::BoolLiteral { value } => return format!("{indent}- BoolLiteral: {}\n", value),
+            Statement::Identifier { ident, .. } => return format!("{indent}- Identifier: {}\n", ident),
+            Statement::StringLiteral { value, .. } => return format!("{indent}- StringLiteral: \"{}\"\n", value),
+            Statement::NumberLiteral { value, .. } => return format!("{indent}- NumberLiteral: {}\n", value),
+            Statement::FloatLiteral { value, .. } => return format!("{indent}- FloatLiteral: {}\n", value),
+            Statement::CharLiteral { value, .. } => return format!("{indent}- CharLiteral: {}\n", value),
+            Statement::BoolLiteral { value, .. } => return format!("{indent}- BoolLiteral: {}\n", value),
+            Statement::Error { .. } => return format!("{indent}- Error\n"),
             Statement::Reference => return format!("{indent}- Reference\n"),
             Statement::Pointer => return format!("{indent}- Pointer\n"),
             Statement::Void => return format!("{indent}- Void\n"),
@@ -350,7 +619,7 @@ impl Statement {
         }
     }
 
-    fn mod_tree_child(&self, node: &mut TreeNode, modifiers: &Vec<Statement>) {
+    fn mod_tree_child(&self, node: &mut TreeNode, modifiers: &[Statement]) {
         if !modifiers.is_empty() {
             node.add_child(TreeNode::new_with_children("Modifiers:",
                                                        modifiers.iter().map(|m| m.as_treenode()).collect()));
@@ -359,13 +628,13 @@ impl Statement {
 
     pub fn as_treenode(&self) -> TreeNode {
         match self {
-            Statement::Program { exprs } => TreeNode::new_with_children("AST",
+            Statement::Program { exprs, .. } => TreeNode::new_with_children("AST",
                                                                         exprs.iter().map(|e| e.as_treenode()).collect()),
-            Statement::Block { exprs } => TreeNode::new_with_children("Block",
+            Statement::Block { exprs, .. } => TreeNode::new_with_children("Block",
                                                                       exprs.iter().map(|e| e.as_treenode()).collect()),
-            Statement::Use { exprs } => TreeNode::new_with_children("Use",
+            Statement::Use { exprs, .. } => TreeNode::new_with_children("Use",
                                                                     exprs.iter().map(|e| e.as_treenode()).collect()),
-            Statement::Fn { ident, params, return_type, body } => {
+            Statement::Fn { ident, params, return_type, body, .. } => {
                 let mut node = TreeNode::new("Function");
                 node.add_child(ident.as_treenode());
                 node.add_child(TreeNode::new_with_children("Parameters",
@@ -375,14 +644,85 @@ impl Statement {
                 node.add_child(body.as_treenode());
                 node
             }
-            Statement::FnCall { ident, params } => {
+            Statement::FnCall { ident, params, .. } => {
                 let mut node = TreeNode::new("Function Call");
                 node.add_child(ident.as_treenode());
                 node.add_child(TreeNode::new_with_children("Parameters",
                                                            params.iter().map(|p| p.as_treenode()).collect()));
                 node
             }
-            Statement::Declaration { ident, type_ident, value } => {
+            Statement::Struct { ident, fields, .. } => {
+                let mut node = TreeNode::new("Struct");
+                node.add_child(ident.as_treenode());
+                node.add_child(TreeNode::new_with_children("Fields",
+                                                           fields.iter().map(|f| f.as_treenode()).collect()));
+                node
+            }
+            Statement::StructLiteral { ident, fields, .. } => {
+                let mut node = TreeNode::new("Struct Literal");
+                node.add_child(ident.as_treenode());
+                node.add_child(TreeNode::new_with_children("Fields",
+                                                           fields.iter().map(|f| f.as_treenode()).collect()));
+                node
+            }
+            Statement::ArrayLiteral { items, .. } => {
+                TreeNode::new_with_children("Array Literal", items.iter().map(|i| i.as_treenode()).collect())
+            }
+            Statement::ArrayRepeat { value, size, .. } => {
+                let mut node = TreeNode::new("Array Repeat");
+                node.add_child(value.as_treenode());
+                node.add_child(size.as_treenode());
+                node
+            }
+            Statement::Impl { ident, methods, .. } => {
+                let mut node = TreeNode::new("Impl");
+                node.add_child(ident.as_treenode());
+                node.add_child(TreeNode::new_with_children("Methods",
+                                                           methods.iter().map(|m| m.as_treenode()).collect()));
+                node
+            }
+            Statement::MethodCall { receiver, method, params, .. } => {
+                let mut node = TreeNode::new("Method Call");
+                node.add_child(receiver.as_treenode());
+                node.add_child(method.as_treenode());
+                node.add_child(TreeNode::new_with_children("Parameters",
+                                                           params.iter().map(|p| p.as_treenode()).collect()));
+                node
+            }
+            Statement::Enum { ident, variants, .. } => {
+                let mut node = TreeNode::new("Enum");
+                node.add_child(ident.as_treenode());
+                node.add_child(TreeNode::new_with_children("Variants",
+                                                           variants.iter().map(|v| v.as_treenode()).collect()));
+                node
+            }
+            Statement::Path { base, member, .. } => {
+                let mut node = TreeNode::new("Path");
+                node.add_child(base.as_treenode());
+                node.add_child(member.as_treenode());
+                node
+            }
+            Statement::Match { value, arms, .. } => {
+                let mut node = TreeNode::new("Match");
+                node.add_child(value.as_treenode());
+                node.add_child(TreeNode::new_with_children("Arms",
+                                                           arms.iter().map(|a| a.as_treenode()).collect()));
+                node
+            }
+            Statement::MatchArm { pattern, body, .. } => {
+                let mut node = TreeNode::new("Arm");
+                node.add_child(pattern.as_treenode());
+                node.add_child(body.as_treenode());
+                node
+            }
+            Statement::RangePattern { start, end, inclusive, .. } => {
+                let mut node = TreeNode::new(if *inclusive { "Inclusive Range Pattern" } else { "Range Pattern" });
+                node.add_child(start.as_treenode());
+                node.add_child(end.as_treenode());
+                node
+            }
+            Statement::Wildcard { .. } => TreeNode::new("Wildcard"),
+            Statement::Declaration { ident, type_ident, value, .. } => {
                 let mut node = TreeNode::new("Declaration");
                 node.add_child(ident.as_treenode());
                 if let Some(t) = type_ident {
@@ -393,38 +733,77 @@ impl Statement {
                 }
                 node
             }
-            Statement::Assignment { ident, value } => {
+            Statement::Const { ident, type_ident, value, .. } => {
+                let mut node = TreeNode::new("Const");
+                node.add_child(ident.as_treenode());
+                node.add_child(type_ident.as_treenode());
+                node.add_child(value.as_treenode());
+                node
+            }
+            Statement::Static { ident, type_ident, value, mutable, .. } => {
+                let mut node = TreeNode::new(if *mutable { "Static Mut" } else { "Static" });
+                node.add_child(ident.as_treenode());
+                node.add_child(type_ident.as_treenode());
+                node.add_child(value.as_treenode());
+                node
+            }
+            Statement::Assignment { ident, value, .. } => {
                 let mut node = TreeNode::new("Assignment");
                 node.add_child(ident.as_treenode());
                 node.add_child(value.as_treenode());
                 node
             }
-            Statement::Panic { value } => {
+            Statement::CompoundAssignment { op, ident, value, .. } => {
+                let mut node = TreeNode::new(format!("Compound Assignment: {}", op));
+                node.add_child(ident.as_treenode());
+                node.add_child(value.as_treenode());
+                node
+            }
+            Statement::Panic { value, .. } => {
                 let mut node = TreeNode::new("Panic");
                 node.add_child(TreeNode::new_with_children("Value:", vec![value.as_treenode()]));
                 node
             }
-            Statement::Assert { expr } => {
+            Statement::Assert { expr, .. } => {
                 let mut node = TreeNode::new("Assert");
                 node.add_child(TreeNode::new_with_children("Value:", vec![expr.as_treenode()]));
                 node
             }
-            Statement::Postfix { postfix: shunted } => {
-                shunted.as_treenode()
+            Statement::Interrupt { vector, .. } => {
+                let mut node = TreeNode::new("Interrupt");
+                node.add_child(vector.as_treenode());
+                node
             }
-            Statement::PropertyAccess { property, expr} => {
+            Statement::Unary { op, expr, postfix, .. } => {
+                let mut node = TreeNode::new(format!("Unary{}: {}", if *postfix { " (postfix)" } else { "" }, op));
+                node.add_child(expr.as_treenode());
+                node
+            }
+            Statement::Binary { op, left, right, .. } => {
+                let mut node = TreeNode::new(format!("Binary: {}", op));
+                node.add_child(left.as_treenode());
+                node.add_child(right.as_treenode());
+                node
+            }
+            Statement::Cast { expr, cast_type, .. } => {
+                let mut node = TreeNode::new("Cast");
+                node.add_child(expr.as_treenode());
+                node.add_child(TreeNode::new_with_children("Type:", vec![cast_type.as_treenode()]));
+                node
+            }
+            Statement::PropertyAccess { property, expr, .. } => {
                 let mut node = TreeNode::new("Property Access");
                 node.add_child(property.as_treenode());
                 node.add_child(expr.as_treenode());
                 node
             }
-            Statement::ArrayAccess { ident, index } => {
+            Statement::ArrayAccess { ident, index, .. } => {
                 let mut node = TreeNode::new("Array Access");
                 node.add_child(ident.as_treenode());
                 node.add_child(index.as_treenode());
                 node
             }
-            Statement::If { condition, body, else_statement } => {
+            Statement::If { condition, body, else_statement, .. } => {
                 let mut node = TreeNode::new("If");
                 node.add_child(condition.as_treenode());
                 node.add_child(body.as_treenode());
@@ -433,51 +812,60 @@ impl Statement {
                 }
                 node
             }
-            Statement::Return { value } => {
+            Statement::Return { value, .. } => {
                 let mut node = TreeNode::new("Return");
                 node.add_child(value.as_treenode());
                 node
             }
-            Statement::While { condition, body } => {
-                let mut node = TreeNode::new("While");
+            Statement::While { condition, body, label, .. } => {
+                let mut node = TreeNode::new(format!("While{}", label_name(label)));
                 node.add_child(condition.as_treenode());
                 node.add_child(body.as_treenode());
                 node
             }
-            Statement::Loop { body } => {
-                let mut node = TreeNode::new("Loop");
+            Statement::Loop { body, label, .. } => {
+                let mut node = TreeNode::new(format!("Loop{}", label_name(label)));
                 node.add_child(body.as_treenode());
                 node
             }
-            Statement::For { ident, collection, body } => {
-                let mut node = TreeNode::new("For");
+            Statement::For { ident, collection, step, body, label, .. } => {
+                let mut node = TreeNode::new(format!("For{}", label_name(label)));
                 node.add_child(ident.as_treenode());
                 node.add_child(collection.as_treenode());
+                if let Some(step) = step {
+                    node.add_child(step.as_treenode());
+                }
                 node.add_child(body.as_treenode());
                 node
             }
-            Statement::Type { modifiers, type_ident } => {
+            Statement::Type { modifiers, type_ident, .. } => {
                 let mut node = TreeNode::new("Type");
                 node.add_child(type_ident.as_treenode());
                 self.mod_tree_child(&mut node, modifiers);
                 node
             }
-            Statement::ArrayType { array_type: type_ident, size, modifiers } => {
+            Statement::ArrayType { array_type: type_ident, size, modifiers, .. } => {
                 let mut node = TreeNode::new("Array Type");
                 node.add_child(type_ident.as_treenode());
                 node.add_child(TreeNode::new(format!("Size: {}", size)));
                 self.mod_tree_child(&mut node, modifiers);
                 node
             }
-            Statement::Break => TreeNode::new("Break"),
-            Statement::Continue => TreeNode::new("Continue"),
-            Statement::Identifier { ident } => TreeNode::new(format!("Identifier: {}", ident)),
-            Statement::StringLiteral { value } => TreeNode::new(format!("String: {}", value)),
-            Statement::NumberLiteral { value } => TreeNode::new(format!("Number: {}", value)),
-            Statement::BoolLiteral { value } => TreeNode::new(format!("Boolean: {}", value)),
-            Statement::CharLiteral { value } => TreeNode::new(format!("Char: {}", value)),
-            Statement::HexLiteral { value } => TreeNode::new(format!("Hex: {}", value)),
-            Statement::BinaryLiteral { value } => TreeNode::new(format!("Binary: {}", value)),
+            Statement::Break { label, value, .. } => {
+                let mut node = TreeNode::new(format!("Break{}", label_name(label)));
+                if let Some(value) = value {
+                    node.add_child(value.as_treenode());
+                }
+                node
+            }
+            Statement::Continue { label, .. } => TreeNode::new(format!("Continue{}", label_name(label))),
+            Statement::Identifier { ident, .. } => TreeNode::new(format!("Identifier: {}", ident)),
+            Statement::StringLiteral { value, .. } => TreeNode::new(format!("String: {}", value)),
+            Statement::NumberLiteral { value, .. } => TreeNode::new(format!("Number: {}", value)),
+            Statement::FloatLiteral { value, .. } => TreeNode::new(format!("Float: {}", value)),
+            Statement::BoolLiteral { value, .. } => TreeNode::new(format!("Boolean: {}", value)),
+            Statement::CharLiteral { value, .. } => TreeNode::new(format!("Char: {}", value)),
+            Statement::Error { .. } => TreeNode::new("Error"),
             Statement::Reference => TreeNode::new("Reference"),
             Statement::Pointer => TreeNode::new("Pointer"),
             Statement::Void => TreeNode::new("Void"),
@@ -494,29 +882,47 @@ impl Display for Statement {
             Statement::Block { .. } => write!(f, "Block"),
             Statement::Fn { .. } => write!(f, "Fn"),
             Statement::FnCall { .. } => write!(f, "FnCall"),
+            Statement::Struct { .. } => write!(f, "Struct"),
+            Statement::StructLiteral { .. } => write!(f, "StructLiteral"),
+            Statement::ArrayLiteral { .. } => write!(f, "ArrayLiteral"),
+            Statement::ArrayRepeat { .. } => write!(f, "ArrayRepeat"),
+            Statement::Impl { .. } => write!(f, "Impl"),
+            Statement::Enum { .. } => write!(f, "Enum"),
+            Statement::Path { .. } => write!(f, "Path"),
+            Statement::Match { .. } => write!(f, "Match"),
+            Statement::MatchArm { .. } => write!(f, "MatchArm"),
+            Statement::RangePattern { .. } => write!(f, "RangePattern"),
+            Statement::Wildcard { .. } => write!(f, "Wildcard"),
+            Statement::MethodCall { .. } => write!(f, "MethodCall"),
             Statement::If { .. } => write!(f, "If"),
             Statement::Return { .. } => write!(f, "Return"),
-            Statement::Postfix { .. } => write!(f, "Postfix"),
+            Statement::Unary { .. } => write!(f, "Unary"),
+            Statement::Binary { .. } => write!(f, "Binary"),
+            Statement::Cast { .. } => write!(f, "Cast"),
             Statement::Panic { .. } => write!(f, "Panic"),
             Statement::Assert { .. } => write!(f, "Assert"),
+            Statement::Interrupt { .. } => write!(f, "Interrupt"),
             Statement::Declaration { .. } => write!(f, "Declaration"),
+            Statement::Const { .. } => write!(f, "Const"),
+            Statement::Static { .. } => write!(f, "Static"),
             Statement::Assignment { .. } => write!(f, "Assignment"),
+            Statement::CompoundAssignment { .. } => write!(f, "CompoundAssignment"),
             Statement::PropertyAccess { .. } => write!(f, "PropertyAccess"),
             Statement::ArrayAccess { .. } => write!(f, "ArrayAccess"),
             Statement::While { .. } => write!(f, "While"),
             Statement::For { .. } => write!(f, "For"),
             Statement::Loop { .. } => write!(f, "Loop"),
-            Statement::Break => write!(f, "Break"),
-            Statement::Continue => write!(f, "Continue"),
+            Statement::Break { .. } => write!(f, "Break"),
+            Statement::Continue { .. } => write!(f, "Continue"),
             Statement::Type { .. } => write!(f, "Type"),
             Statement::ArrayType { .. } => write!(f, "ArrayType"),
             Statement::Identifier { .. } => write!(f, "Identifier"),
             Statement::StringLiteral { .. } => write!(f, "StringLiteral"),
             Statement::NumberLiteral { .. } => write!(f, "NumberLiteral"),
-            Statement::BinaryLiteral { .. } => write!(f, "BinaryLiteral"),
-            Statement::HexLiteral { .. } => write!(f, "HexLiteral"),
+            Statement::FloatLiteral { .. } => write!(f, "FloatLiteral"),
             Statement::CharLiteral { .. } => write!(f, "CharLiteral"),
             Statement::BoolLiteral { .. } => write!(f, "BoolLiteral"),
+            Statement::Error { .. } => write!(f, "Error"),
             Statement::Reference => write!(f, "Reference"),
             Statement::Pointer => write!(f, "Pointer"),
             Statement::NOP => write!(f, "NOP"),
@@ -1,5 +1,6 @@
-use std::fmt::{Display, write};
-use crate::{CodePos, Error};
+use std::fmt::Display;
+use crate::{CodePos, Error, Span};
+use crate::module::Modules;
 use crate::operator::Operator;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -20,11 +21,14 @@ pub enum TokenType {
     Panic, // ?
 
     Ident,
+    Label, // 'name, the value is the name without the quote
 
     StringLit,
     CharLit,
     NumberLit,
+    FloatLit,
     HexLit,
+    OctLit,
     BinLit,
 
     Operator,
@@ -32,16 +36,25 @@ pub enum TokenType {
     // keywords
     Fn,        // "fn"
     Let,       // "let"
+    Const,     // "const"
+    Static,    // "static"
+    Mut,       // "mut"
+    Pub,       // "pub"
     If,        // "if"
     Else,      // "else"
     While,     // "while"
     For,       // "for"
     Loop,      // "loop"
+    Break,     // "break"
+    Continue,  // "continue"
     Return,    // "return"
     Match,     // "match"
     Struct,    // "struct"
+    Impl,      // "impl"
+    Enum,      // "enum"
     Assert,    // "assert"
     In,        // "in"
+    As,        // "as"
     Use,       // "use"
     Macro,     // "macro" (currently unused)
     BoolTrue,  // "true"
@@ -56,22 +69,32 @@ impl Display for TokenType {
             TokenType::Whitespace => write!(f, "Whitespace"),
             TokenType::Comma => write!(f, "Comma"),
             TokenType::Ident => write!(f, "Ident"),
+            TokenType::Label => write!(f, "Label"),
             TokenType::StringLit => write!(f, "StringLit"),
             TokenType::CharLit => write!(f, "CharLit"),
             TokenType::NumberLit => write!(f, "NumberLit"),
             TokenType::Operator => write!(f, "Operator"),
             TokenType::Fn => write!(f, "Fn"),
             TokenType::Let => write!(f, "Let"),
+            TokenType::Const => write!(f, "Const"),
+            TokenType::Static => write!(f, "Static"),
+            TokenType::Mut => write!(f, "Mut"),
+            TokenType::Pub => write!(f, "Pub"),
             TokenType::If => write!(f, "If"),
             TokenType::Else => write!(f, "Else"),
             TokenType::While => write!(f, "While"),
             TokenType::For => write!(f, "For"),
             TokenType::Loop => write!(f, "Loop"),
+            TokenType::Break => write!(f, "Break"),
+            TokenType::Continue => write!(f, "Continue"),
             TokenType::Return => write!(f, "Return"),
             TokenType::Match => write!(f, "Match"),
             TokenType::Struct => write!(f, "Struct"),
+            TokenType::Impl => write!(f, "Impl"),
+            TokenType::Enum => write!(f, "Enum"),
             TokenType::Assert => write!(f, "Assert"),
             TokenType::In => write!(f, "In"),
+            TokenType::As => write!(f, "As"),
             TokenType::BoolTrue => write!(f, "BoolTrue"),
             TokenType::BoolFalse => write!(f, "BoolFalse"),
             TokenType::Dot => write!(f, "Dot"),
@@ -87,7 +110,9 @@ impl Display for TokenType {
             TokenType::Panic => write!(f, "Panic"),
             TokenType::Use => write!(f, "Use"),
             TokenType::Macro => write!(f, "Macro"),
+            TokenType::FloatLit => write!(f, "FloatLit"),
             TokenType::HexLit => write!(f, "HexLit"),
+            TokenType::OctLit => write!(f, "OctLit"),
             TokenType::BinLit => write!(f, "BinLit"),
         }
     }
@@ -103,6 +128,11 @@ pub struct Token {
 }
 
 impl Token {
+    /// the source code the token was lexed from
+    pub fn span(&self) -> Span {
+        Span::new(self.start.clone(), &self.end)
+    }
+
     pub fn new(token_type: TokenType, start: CodePos, end: CodePos) -> Token {
         Token {
             token_type,
@@ -162,6 +192,12 @@ pub struct TokenList {
     pub tokens: Vec<Token>,
     pub iter_place: usize,
     eof_loc: CodePos,
+    /// where the last token that wasn't whitespace ended, so statements know where they end
+    last_end: CodePos,
+    /// errors the parser recovered from, which are reported once the whole file has been parsed
+    pub errors: Vec<Error>,
+    /// the files loaded so far, which is passed on to the token list of each file that is used
+    pub modules: Modules,
 }
 
 impl TokenList {
@@ -172,6 +208,9 @@ impl TokenList {
             tokens,
             iter_place: 0,
             eof_loc,
+            last_end: CodePos::default(),
+            errors: Vec::new(),
+            modules: Modules::default(),
         }
     }
 
@@ -183,11 +222,7 @@ impl TokenList {
     /// Peek at the nth token in the list without removing it
     /// Returns None if the index is out of bounds
     pub fn peek_nth(&self, n: usize) -> Option<Token> {
-        return if let Some(t) = self.tokens.get(n) {
-            Some(t.clone())
-        } else {
-            None
-        }
+        self.tokens.get(n).cloned()
     }
 
     /// Peek at the next token in the list without removing it.
@@ -207,14 +242,30 @@ impl TokenList {
 
     /// Removes the nth token in the list and returns it.
     /// Returns None if the index is out of bounds
+    #[allow(clippy::needless_return)]
     pub fn consume_nth(&mut self, n: usize) -> Option<Token> {
         return if n >= self.tokens.len() {
             None
         } else {
-            Some(self.tokens.remove(n))
+            let token = self.tokens.remove(n);
+            if token.token_type != TokenType::Whitespace {
+                self.last_end = token.end.clone();
+            }
+            Some(token)
         }
     }
 
+    /// the span from `start` to the end of the last token consumed, not counting whitespace
+    pub fn span_from(&self, start: CodePos) -> Span {
+        Span::new(start, &self.last_end)
+    }
+
+    /// where the next token that isn't whitespace starts, which is where a statement parsed from it starts
+    pub fn next_start(&mut self) -> CodePos {
+        self.optional_whitespace();
+        self.peek_loc().unwrap_or_else(|| self.eof())
+    }
+
     /// Removes the next token in the list and returns it.
     /// Returns None if there is no next token.
     pub fn consume(&mut self) -> Option<Token> {
@@ -242,6 +293,7 @@ impl TokenList {
         }
     }
 
+    #[allow(clippy::useless_format)]
     pub fn expect_whitespace(&mut self) -> Result<Token, Error> {
         let token = self.consume()
             .ok_or_else(|| Error::new("Unexpected EOF", "expected Whitespace", self.eof()))?;
@@ -325,7 +377,7 @@ impl TokenList {
 
     pub fn next_is_op(&mut self, op: Operator) -> bool {
         self.optional_whitespace();
-        self.peek().map(|t| t.op.unwrap() == op).unwrap_or(false)
+        self.peek().map(|t| t.op == Some(op)).unwrap_or(false)
     }
 
     // returns the next token after whitespace without removing the whitespace
@@ -337,6 +389,26 @@ impl TokenList {
         self.peek_nth(index).map(|t| t.token_type == tt).unwrap_or(false)
     }
 
+    /// returns true if the whitespace before the next token contains a new line
+    pub fn newline_ahead(&self) -> bool {
+        let mut index : usize = 0;
+        while let Some(t) = self.peek_nth(index) {
+            if t.token_type != TokenType::Whitespace {
+                break;
+            }
+            if t.end.line > t.start.line {
+                return true;
+            }
+            index += 1;
+        }
+        false
+    }
+
+    /// returns the next token that isn't whitespace without removing anything
+    pub fn peek_after_ws(&self) -> Option<Token> {
+        self.tokens.iter().find(|t| t.token_type != TokenType::Whitespace).cloned()
+    }
+
     pub fn second_after_ws(&mut self) -> Option<Token> {
         let mut index : usize = 1;
         while self.peek_nth(index).map(|t| t.token_type == TokenType::Whitespace).unwrap_or(false) {
@@ -350,6 +422,7 @@ impl TokenList {
 impl Iterator for TokenList {
     type Item = Token;
 
+    #[allow(clippy::needless_return)]
     fn next(&mut self) -> Option<Self::Item> {
         return if self.iter_place < self.len() {
             let place = self.iter_place;
@@ -362,6 +435,7 @@ impl Iterator for TokenList {
 }
 
 impl Display for TokenList {
+    #[allow(clippy::useless_format)]
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let mut total = String::new();
         for x in 0..self.tokens.len() {
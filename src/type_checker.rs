@@ -0,0 +1,1170 @@
+use std::collections::HashMap;
+use crate::Span;
+use crate::checker::order_args;
+use crate::error::Error;
+use crate::operator::Operator;
+use crate::statement::{Number, Statement};
+use crate::types::{literal_value, type_span, Type};
+
+/// The parameter and return types of a function.
+/// Types that failed to resolve are None so they don't cause more errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FnType {
+    params: Vec<Option<Type>>,
+    return_type: Option<Type>,
+    /// the name and default value of each parameter, which method calls are put in order with
+    declared: Vec<(String, Option<Statement>)>,
+}
+
+/// A struct's fields in the order they were declared
+type StructFields = Vec<(String, Option<Type>)>;
+
+/// A loop's label, the type of the values `break` has given it so far, whether any `break` has given it
+/// one, and where the first `break` without a value left it
+type LoopBreaks = (Option<String>, Option<Type>, bool, Option<Span>);
+
+/// Checks that every value is used where its type is allowed
+struct TypeChecker {
+    functions: HashMap<String, FnType>,
+    structs: HashMap<String, StructFields>,
+    /// the variants of every enum
+    enums: HashMap<String, Vec<String>>,
+    /// the methods of every struct by type and then method name, including the `self` parameter
+    methods: HashMap<String, HashMap<String, FnType>>,
+    scopes: Vec<HashMap<String, Option<Type>>>,
+    return_type: Option<Type>,
+    /// every loop around the statement being checked, innermost last
+    loops: Vec<LoopBreaks>,
+    /// the statement being checked, for errors in expressions that have no span of their own
+    span: Span,
+    /// false when the target has no floating point unit, so floats can't be used
+    fpu: bool,
+    errors: Vec<Error>,
+}
+
+/// the name of a struct, or the struct a reference or pointer points to
+fn struct_name(t: &Type) -> Option<&String> {
+    match t {
+        Type::Struct(name) => Some(name),
+        Type::Reference(inner) | Type::Pointer(inner) => match inner.as_ref() {
+            Type::Struct(name) => Some(name),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+/// finds every struct's name and fields, including those from imported files
+fn find_structs<'a>(exprs: &'a Vec<Statement>, structs: &mut Vec<(String, &'a Vec<Statement>)>) {
+    for expr in exprs {
+        match expr {
+            Statement::Struct { ident, fields, .. } => {
+                if let Some((name, _)) = ident_of(ident) {
+                    structs.push((name.clone(), fields));
+                }
+            }
+            Statement::Use { exprs, .. } => find_structs(exprs, structs),
+            _ => {}
+        }
+    }
+}
+
+/// the source code an expression was parsed from
+fn expr_span(expr: &Statement) -> Option<Span> {
+    expr.span().cloned()
+}
+
+/// the integer literal an expression is made of, if it is only a literal without a suffix
+fn literal_number(expr: &Statement) -> Option<&Number> {
+    match expr {
+        Statement::NumberLiteral { value, .. } if value.suffix.is_none() => Some(value),
+        _ => None
+    }
+}
+
+fn ident_of(stmt: &Statement) -> Option<(&String, &Span)> {
+    match stmt {
+        Statement::Identifier { ident, span, .. } => Some((ident, span)),
+        _ => None
+    }
+}
+
+/// returns true if a statement can't reach its end because every path through it returns or panics.
+/// A `loop` that nothing breaks out of never reaches its end either
+fn always_returns(stmt: &Statement) -> bool {
+    match stmt {
+        Statement::Return { .. } | Statement::Panic { .. } => true,
+        Statement::Block { exprs, .. } => exprs.iter().any(always_returns),
+        Statement::If { body, else_statement: Some(else_body), .. } => always_returns(body) && always_returns(else_body),
+        // matches that don't cover every value are reported on their own
+        Statement::Match { arms, .. } => arms.iter().all(|a| match a {
+            Statement::MatchArm { body, .. } => always_returns(body),
+            _ => true
+        }),
+        Statement::Loop { body, label, .. } => !leaves_loop(body, label.as_deref().and_then(ident_of).map(|(n, _)| n), false),
+        _ => false
+    }
+}
+
+/// returns true if a statement contains a break that leaves the loop with the given label. A break without
+/// a label only leaves it when it isn't inside a nested loop
+fn leaves_loop(stmt: &Statement, label: Option<&String>, nested: bool) -> bool {
+    match stmt {
+        Statement::Break { label: target, .. } => match target.as_deref().and_then(ident_of) {
+            Some((target, _)) => Some(target) == label,
+            None => !nested
+        },
+        Statement::Block { exprs, .. } => exprs.iter().any(|e| leaves_loop(e, label, nested)),
+        Statement::If { body, else_statement, .. } => {
+            leaves_loop(body, label, nested) || else_statement.as_ref().is_some_and(|e| leaves_loop(e, label, nested))
+        }
+        Statement::Match { arms, .. } => arms.iter().any(|a| leaves_loop(a, label, nested)),
+        Statement::MatchArm { body, .. } => leaves_loop(body, label, nested),
+        Statement::While { body, .. } | Statement::Loop { body, .. } | Statement::For { body, .. } => {
+            leaves_loop(body, label, true)
+        }
+        // a `loop` can be the value of a declaration or assignment
+        Statement::Declaration { value: Some(value), .. } | Statement::Assignment { value, .. } |
+        Statement::CompoundAssignment { value, .. } => {
+            leaves_loop(value, label, nested)
+        }
+        _ => false
+    }
+}
+
+impl TypeChecker {
+    fn new(fpu: bool) -> Self {
+        Self {
+            functions: HashMap::new(),
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            methods: HashMap::new(),
+            // the first scope holds the consts and statics
+            scopes: vec![HashMap::new()],
+            return_type: None,
+            loops: Vec::new(),
+            span: Span::default(),
+            fpu,
+            errors: Vec::new(),
+        }
+    }
+
+    fn error<S: Into<String>, S2: Into<String>>(&mut self, core: S, msg: S2, span: Span) {
+        self.errors.push(Error::new(core, msg, span));
+    }
+
+    /// where an expression is, or the statement it is in if it has no span
+    fn span_of(&self, expr: &Statement) -> Span {
+        expr_span(expr).unwrap_or_else(|| self.span.clone())
+    }
+
+    fn mismatch(&mut self, expected: &Type, found: &Type, span: Span) {
+        self.error("Mismatched types", format!("expected `{}` but found `{}`", expected, found), span);
+    }
+
+    /// resolves a type statement, recording an error if it is not a valid type
+    fn resolve(&mut self, stmt: &Statement) -> Option<Type> {
+        let (structs, enums) = (&self.structs, &self.enums);
+        let user_type = |name: &str| {
+            structs.get(name).map(|_| Type::Struct(name.to_string()))
+                .or_else(|| enums.get(name).map(|_| Type::Enum(name.to_string())))
+        };
+        match Type::from_statement(stmt, &user_type) {
+            Ok(t) if !self.fpu && t.has_float() => {
+                self.no_fpu(&t, type_span(stmt));
+                None
+            }
+            Ok(t) => Some(t),
+            Err(e) => {
+                self.errors.push(e);
+                None
+            }
+        }
+    }
+
+    fn no_fpu(&mut self, found: &dyn std::fmt::Display, span: Span) {
+        self.error("Floats are not supported", format!("`{}` needs a target with an FPU", found), span);
+    }
+
+    fn declare(&mut self, name: &str, t: Option<Type>) {
+        self.scopes.last_mut().unwrap().insert(name.to_string(), t);
+    }
+
+    fn lookup(&self, name: &str) -> Option<Type> {
+        self.scopes.iter().rev().find_map(|s| s.get(name)).cloned().flatten()
+    }
+
+    /// checks that a value of type `found` can be used where `expected` is required
+    fn expect(&mut self, expected: &Option<Type>, found: &Option<Type>, span: Span) {
+        if let (Some(expected), Some(found)) = (expected, found) {
+            if !found.assignable_to(expected) {
+                self.mismatch(expected, found, span);
+            }
+        }
+    }
+
+    /// checks that an integer literal, or a value that is only an integer literal, fits in the type
+    /// it is given. Literals with a suffix are checked against their suffix instead. Float literals and
+    /// operators on untyped literals become the type they are given, so they are computed in that type
+    fn check_literal(&mut self, value: &mut Statement, target: &Type) {
+        // the elements of an array literal are checked against the array's element type
+        match (value, target) {
+            (Statement::ArrayLiteral { items, .. }, Type::Array(inner, _)) => {
+                items.iter_mut().for_each(|i| self.check_literal(i, inner));
+            }
+            (Statement::ArrayRepeat { value, .. }, Type::Array(inner, _)) => self.check_literal(value, inner),
+            (Statement::FloatLiteral { value, .. }, Type::F32) => value.single = true,
+            (Statement::Binary { op, left, right, value_type: value_type @ None, span }, t)
+                if t.is_number() && op.is_arithmetic() => {
+                *value_type = t.to_statement(span).map(Box::new);
+                self.check_literal(left, t);
+                // the right side of a shift is only a count
+                if !matches!(op, Operator::Shl | Operator::Shr | Operator::Shlu | Operator::Shru) {
+                    self.check_literal(right, t);
+                }
+            }
+            (Statement::Unary { op: Operator::Sub | Operator::Not, expr, value_type: value_type @ None, span, .. }, t)
+                if t.is_number() => {
+                *value_type = t.to_statement(span).map(Box::new);
+                self.check_literal(expr, t);
+            }
+            (value, _) => {
+                if let Some(number) = literal_number(value) {
+                    let span = self.span_of(value);
+                    self.check_number(number, target, span);
+                }
+            }
+        }
+    }
+
+    fn check_number(&mut self, number: &Number, target: &Type, span: Span) {
+
+        let (min, max) = match target.value_range() {
+            Some(range) if target.is_integer() && *target != Type::UntypedInt => range,
+            _ => return
+        };
+        let value = number.signed_value();
+        if value < min || value > max {
+            let msg = format!("`{}` does not fit in `{}`, which holds {} to {}", number, target, min, max);
+            self.error("Literal out of range", msg, span);
+        }
+    }
+
+    /// collects every struct, first by name so fields can use structs declared after them
+    fn collect_structs(&mut self, exprs: &Vec<Statement>) {
+        let mut structs = Vec::new();
+        find_structs(exprs, &mut structs);
+        for (name, _) in &structs {
+            self.structs.entry(name.clone()).or_default();
+        }
+        for (name, fields) in structs {
+            // duplicates are reported by the checker, only the first definition is kept
+            if !self.structs[&name].is_empty() {
+                continue;
+            }
+            let mut field_types = Vec::new();
+            for f in fields {
+                if let Statement::Declaration { ident, type_ident: Some(t), .. } = f {
+                    if let Some((field, _)) = ident_of(ident) {
+                        let t = self.resolve(t);
+                        field_types.push((field.clone(), t));
+                    }
+                }
+            }
+            self.structs.insert(name, field_types);
+        }
+    }
+
+    /// collects the variants of every enum, before structs so fields can be enums
+    fn collect_enums(&mut self, exprs: &Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Enum { ident, variants, .. } => {
+                    let name = match ident_of(ident) {
+                        Some((name, _)) => name.clone(),
+                        None => continue
+                    };
+                    let variants = variants.iter().filter_map(|v| match v {
+                        Statement::Declaration { ident, .. } => ident_of(ident).map(|(n, _)| n.clone()),
+                        _ => None
+                    }).collect();
+                    self.enums.entry(name).or_insert(variants);
+                }
+                Statement::Use { exprs, .. } => self.collect_enums(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    /// the type of a field of a struct, which is also accessible through references and pointers to it
+    fn field_type(&mut self, base: &Type, property: &Statement) -> Option<Type> {
+        let (field, span) = ident_of(property)?;
+        let name = match struct_name(base) {
+            Some(name) => name,
+            None => {
+                self.error("Unknown field", format!("`{}` has no fields", base), span.clone());
+                return None;
+            }
+        };
+        let found = self.structs.get(name)?.iter().find(|(f, _)| f == field).map(|(_, t)| t.clone());
+        match found {
+            Some(t) => t,
+            None => {
+                self.error("Unknown field", format!("`{}` has no field named `{}`", name, field), span.clone());
+                None
+            }
+        }
+    }
+
+    fn struct_literal_type(&mut self, ident: &Statement, fields: &mut Vec<Statement>) -> Option<Type> {
+        let (name, _) = ident_of(ident)?;
+        let declared = self.structs.get(name).cloned();
+        for f in fields {
+            if let Statement::Assignment { ident: field, value, .. } = f {
+                let found = self.type_of(value);
+                // unknown structs and fields are reported by the checker
+                let expected = declared.as_ref().and_then(|d| {
+                    let (field, _) = ident_of(field)?;
+                    d.iter().find(|(n, _)| n == field).and_then(|(_, t)| t.clone())
+                });
+                let span = expr_span(value).or_else(|| expr_span(field)).unwrap_or_else(|| self.span.clone());
+                self.expect(&expected, &found, span);
+                if let Some(t) = &expected {
+                    self.check_literal(value, t);
+                }
+            }
+        }
+        declared.map(|_| Type::Struct(name.clone()))
+    }
+
+    /// the name and signature of a function
+    fn fn_type(&mut self, function: &Statement) -> Option<(String, FnType)> {
+        let (ident, params, return_type) = match function {
+            Statement::Fn { ident, params, return_type, .. } => (ident, params, return_type),
+            _ => return None
+        };
+        let (name, _) = ident_of(ident)?;
+        let mut param_types = Vec::new();
+        let mut declared = Vec::new();
+        for p in params {
+            if let Statement::Declaration { type_ident: Some(t), .. } = p {
+                param_types.push(self.resolve(t));
+            } else {
+                param_types.push(None);
+            }
+            if let Statement::Declaration { ident, value, .. } = p {
+                let param = ident_of(ident).map(|(name, _)| name.clone()).unwrap_or_default();
+                declared.push((param, value.as_deref().cloned()));
+            }
+        }
+        let return_type = self.resolve(return_type);
+        Some((name.clone(), FnType { params: param_types, return_type, declared }))
+    }
+
+    fn collect_functions(&mut self, exprs: &Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Fn { .. } => {
+                    if let Some((name, signature)) = self.fn_type(expr) {
+                        // duplicates are reported by the checker, only the first definition is kept
+                        self.functions.entry(name).or_insert(signature);
+                    }
+                }
+                Statement::Impl { ident, methods, .. } => {
+                    let type_name = match ident_of(ident) {
+                        Some((name, _)) => name.clone(),
+                        None => continue
+                    };
+                    for m in methods {
+                        if let Some((name, signature)) = self.fn_type(m) {
+                            self.methods.entry(type_name.clone()).or_default().entry(name).or_insert(signature);
+                        }
+                    }
+                }
+                Statement::Const { ident, type_ident, .. } | Statement::Static { ident, type_ident, .. } => {
+                    if let Some((name, _)) = ident_of(ident) {
+                        let t = self.resolve(type_ident);
+                        self.scopes[0].entry(name.clone()).or_insert(t);
+                    }
+                }
+                Statement::Use { exprs, .. } => self.collect_functions(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    /// checks a function's body. A function that returns a value must not be able to reach the end of its body
+    fn check_fn(&mut self, ident: &Statement, signature: FnType, params: &mut [Statement], body: &mut Statement) {
+        if let (Some(t), Some((name, span))) = (&signature.return_type, ident_of(ident)) {
+            if *t != Type::Void && !always_returns(body) {
+                let msg = format!("`{}` returns `{}`, but can reach the end of its body without returning", name, t);
+                self.error("Missing return", msg, span.clone());
+            }
+        }
+        self.return_type = signature.return_type;
+        // default values are checked before any parameter is in scope, like they are by the checker
+        for (p, t) in params.iter_mut().zip(&signature.params) {
+            if let Statement::Declaration { value: Some(value), .. } = p {
+                let found = self.type_of(value);
+                let span = self.span_of(value);
+                self.expect(t, &found, span);
+                if let Some(t) = t {
+                    self.check_literal(value, t);
+                }
+            }
+        }
+        self.scopes.push(HashMap::new());
+        for (p, t) in params.iter().zip(signature.params) {
+            if let Statement::Declaration { ident, .. } = p {
+                if let Some((name, _)) = ident_of(ident) {
+                    self.declare(name, t);
+                }
+            }
+        }
+        self.check(body);
+        self.scopes.pop();
+    }
+
+    fn check_globals(&mut self, exprs: &mut Vec<Statement>) {
+        for expr in exprs {
+            match expr {
+                Statement::Fn { ident, params, body, .. } => {
+                    let signature = match ident_of(ident).and_then(|(name, _)| self.functions.get(name)) {
+                        Some(f) => f.clone(),
+                        None => continue
+                    };
+                    // the panic hook is called with the message of every panic
+                    if let Some(("panic_hook", span)) = ident_of(ident).map(|(n, p)| (n.as_str(), p)) {
+                        if signature.params != [Some(Type::Str)] || signature.return_type != Some(Type::Void) {
+                            self.error("Invalid panic hook", "`panic_hook` must take a `string` and return nothing",
+                                       span.clone());
+                        }
+                    }
+                    self.check_fn(ident, signature, params, body);
+                }
+                Statement::Impl { ident, methods, .. } => {
+                    let type_name = match ident_of(ident) {
+                        Some((name, _)) => name.clone(),
+                        None => continue
+                    };
+                    for m in methods {
+                        if let Statement::Fn { ident, params, body, .. } = m {
+                            let signature = ident_of(ident).and_then(|(name, _)| {
+                                self.methods.get(&type_name).and_then(|methods| methods.get(name))
+                            });
+                            if let Some(signature) = signature.cloned() {
+                                self.check_fn(ident, signature, params, body);
+                            }
+                        }
+                    }
+                }
+                // the checker has already replaced the value with a literal if it is a constant
+                Statement::Const { ident, value, .. } | Statement::Static { ident, value, .. } => {
+                    let t = ident_of(ident).and_then(|(name, _)| self.lookup(name));
+                    let found = self.type_of(value);
+                    let span = self.span_of(value);
+                    self.expect(&t, &found, span);
+                    if let Some(t) = t {
+                        self.check_literal(value, &t);
+                    }
+                }
+                Statement::Use { exprs, .. } => self.check_globals(exprs),
+                _ => {}
+            }
+        }
+    }
+
+    /// checks the body of a loop, returning the type of the values `break` gave it. A loop that no
+    /// `break` gave a value to has no value
+    fn check_loop_body(&mut self, label: &Option<Box<Statement>>, body: &mut Statement) -> Option<Type> {
+        let label = label.as_deref().and_then(ident_of).map(|(name, _)| name.clone());
+        self.loops.push((label, None, false, None));
+        self.check(body);
+        match self.loops.pop() {
+            // a loop that gives a value has to give it however it is left, or what it gives is uninitialized
+            Some((_, given, true, Some(span))) => {
+                self.error("Missing break value", "this loop gives a value, so every `break` that leaves it must give one", span);
+                given
+            }
+            Some((_, given, true, None)) => given,
+            _ => Some(Type::Void)
+        }
+    }
+
+    /// the loop a `break` or `continue` with the label leaves. Breaks without a loop or with an
+    /// unknown label are reported by the checker
+    fn loop_index(&self, label: &Option<Box<Statement>>) -> Option<usize> {
+        match label.as_deref().and_then(ident_of).map(|(name, _)| name) {
+            Some(label) => self.loops.iter().rposition(|(l, ..)| l.as_ref() == Some(label)),
+            None => self.loops.len().checked_sub(1)
+        }
+    }
+
+    /// checks that a condition is a bool
+    fn check_condition(&mut self, condition: &mut Statement) {
+        let t = self.type_of(condition);
+        let span = self.span_of(condition);
+        self.expect(&Some(Type::Bool), &t, span);
+    }
+
+    /// checks a statement, filling in the type of any declaration that doesn't have one
+    fn check(&mut self, stmt: &mut Statement) {
+        match stmt {
+            Statement::Block { exprs, .. } => {
+                self.scopes.push(HashMap::new());
+                for expr in exprs {
+                    self.check(expr);
+                }
+                self.scopes.pop();
+            }
+            Statement::Declaration { ident, type_ident, value, .. } => {
+                let (name, span) = match ident_of(ident) {
+                    Some((name, span)) => (name.clone(), span.clone()),
+                    None => return
+                };
+                self.span = span.clone();
+                let declared = type_ident.as_ref().and_then(|t| self.resolve(t));
+                let found = value.as_mut().and_then(|v| self.type_of(v));
+                let value_span = value.as_ref().and_then(|v| expr_span(v)).unwrap_or_else(|| span.clone());
+                if type_ident.is_some() {
+                    self.expect(&declared, &found, value_span);
+                    if let (Some(t), Some(v)) = (&declared, value.as_mut()) {
+                        self.check_literal(v, t);
+                    }
+                    self.declare(&name, declared);
+                    return;
+                }
+                // infer the type from the value, integer literals default to `int`
+                let inferred = found.map(|t| t.with_default_types());
+                if let (Some(t), Some(v)) = (&inferred, value.as_mut()) {
+                    self.check_literal(v, t);
+                }
+                let type_stmt = inferred.as_ref().and_then(|t| t.to_statement(&span));
+                match (&inferred, type_stmt) {
+                    (Some(Type::Void), _) => {
+                        self.error("Can not infer type", format!("`{}` can not be given a value that is void", name), value_span);
+                        self.declare(&name, None);
+                    }
+                    (Some(t), None) => {
+                        let msg = format!("`{}` can not store a value of type `{}`", name, t);
+                        self.error("Can not infer type", msg, value_span);
+                        self.declare(&name, None);
+                    }
+                    (_, type_stmt) => {
+                        *type_ident = type_stmt.map(Box::new);
+                        self.declare(&name, inferred);
+                    }
+                }
+            }
+            Statement::Assignment { ident, value, .. } => {
+                self.span = self.span_of(ident);
+                let target = self.type_of(ident);
+                let found = self.type_of(value);
+                let span = self.span_of(value);
+                self.expect(&target, &found, span);
+                if let Some(t) = &target {
+                    self.check_literal(value, t);
+                }
+            }
+            Statement::CompoundAssignment { op, ident, value, value_type, .. } => {
+                self.span = self.span_of(ident);
+                let target = self.type_of(ident);
+                let found = self.binary_expr_type(*op, ident, value, value_type);
+                let span = self.span_of(value);
+                self.expect(&target, &found, span);
+            }
+            Statement::If { condition, body, else_statement, span, .. } => {
+                self.span = span.clone();
+                self.check_condition(condition);
+                self.check(body);
+                if let Some(else_body) = else_statement {
+                    self.check(else_body);
+                }
+            }
+            Statement::While { condition, body, label, span } => {
+                self.span = span.clone();
+                self.check_condition(condition);
+                self.check_loop_body(label, body);
+            }
+            Statement::Loop { .. } => {
+                self.type_of(stmt);
+            }
+            Statement::For { ident, collection, step, body, label, element_type, length, through_pointer, span } => {
+                self.span = self.span_of(ident);
+                let found = self.type_of(collection);
+                *through_pointer = matches!(found, Some(Type::Reference(_)));
+                *length = match &found {
+                    Some(Type::Array(_, size)) => Some(*size),
+                    Some(Type::Reference(inner)) => match inner.as_ref() {
+                        Type::Array(_, size) => Some(*size),
+                        _ => None
+                    },
+                    _ => None
+                };
+                let element = match &found {
+                    Some(Type::Range(t)) | Some(Type::Array(t, _)) => Some(t.as_ref().clone()),
+                    // iterating over a reference to an array gives a reference to each element
+                    Some(Type::Reference(inner)) => match inner.as_ref() {
+                        Type::Array(t, _) => Some(Type::Reference(t.clone())),
+                        _ => None
+                    },
+                    _ => None
+                };
+                if let (Some(other), None) = (&found, &element) {
+                    let span = self.span_of(collection);
+                    self.error("Not iterable", format!("can not iterate over `{}`", other), span);
+                }
+                self.scopes.push(HashMap::new());
+                if let Some((name, _)) = ident_of(ident) {
+                    let name = name.clone();
+                    // a range of literals counts with the default integer type
+                    let element = element.map(|t| if t == Type::UntypedInt { Type::Int } else { t });
+                    *element_type = element.as_ref().and_then(|t| t.to_statement(span)).map(Box::new);
+                    self.declare(&name, element);
+                }
+                if let Some(step) = step {
+                    if let Some(t) = self.type_of(step).filter(|t| !t.is_integer()) {
+                        let span = self.span_of(step);
+                        self.error("Mismatched types", format!("a for loop's step must be an integer but found `{}`", t), span);
+                    }
+                }
+                self.check_loop_body(label, body);
+                self.scopes.pop();
+            }
+            Statement::Break { label, value: Some(value), span } => {
+                self.span = span.clone();
+                let found = self.type_of(value);
+                if let Some(index) = self.loop_index(label) {
+                    self.loops[index].2 = true;
+                    match (self.loops[index].1.clone(), found) {
+                        (Some(given), Some(found)) => match given.unify(&found) {
+                            Some(t) => self.loops[index].1 = Some(t),
+                            None => {
+                                let span = self.span_of(value);
+                                self.mismatch(&given, &found, span);
+                            }
+                        },
+                        (None, found) => self.loops[index].1 = found,
+                        _ => {}
+                    }
+                }
+            }
+            Statement::Break { label, value: None, span } => {
+                if let Some(index) = self.loop_index(label) {
+                    self.loops[index].3.get_or_insert_with(|| span.clone());
+                }
+            }
+            Statement::Return { value, span, .. } => {
+                self.span = span.clone();
+                let found = self.type_of(value);
+                let expected = self.return_type.clone();
+                match (&expected, &found) {
+                    (Some(Type::Void), Some(t)) if *t != Type::Void => {
+                        self.error("Mismatched types", format!("this function returns nothing but found `{}`", t),
+                                   self.span_of(value));
+                    }
+                    (Some(t), Some(Type::Void)) if *t != Type::Void => {
+                        self.error("Missing return value", format!("this function returns `{}`", t), span.clone());
+                    }
+                    _ => {
+                        let value_span = self.span_of(value);
+                        self.expect(&expected, &found, value_span);
+                        if let Some(t) = &expected {
+                            self.check_literal(value, t);
+                        }
+                    }
+                }
+            }
+            Statement::Match { .. } => {
+                self.match_type(stmt, false);
+            }
+            Statement::Assert { expr, span, .. } => {
+                self.span = span.clone();
+                self.check_condition(expr);
+            }
+            Statement::Panic { value, value_type, span, .. } => {
+                self.span = span.clone();
+                let found = self.type_of(value).map(|t| t.with_default_types());
+                *value_type = found.and_then(|t| t.to_statement(span)).map(Box::new);
+            }
+            _ => {
+                self.type_of(stmt);
+            }
+        }
+    }
+
+    /// checks the arguments of a call against the parameters of a function
+    fn check_args(&mut self, params: &[Option<Type>], args: &mut [Statement]) {
+        let arg_types: Vec<Option<Type>> = args.iter_mut().map(|a| self.type_of(a)).collect();
+        // the checker already reports calls with the wrong number of arguments
+        if params.len() == args.len() {
+            for ((expected, found), arg) in params.iter().zip(arg_types.iter()).zip(args.iter_mut()) {
+                let arg_pos = self.span_of(arg);
+                self.expect(expected, found, arg_pos);
+                if let Some(t) = expected {
+                    self.check_literal(arg, t);
+                }
+            }
+        }
+    }
+
+    fn check_call(&mut self, ident: &Statement, params: &mut [Statement]) -> Option<Type> {
+        let (name, span) = ident_of(ident)?;
+        self.span = span.clone();
+        let signature = match self.functions.get(name) {
+            Some(s) => s.clone(),
+            None => {
+                for p in params.iter_mut() {
+                    self.type_of(p);
+                }
+                return None;
+            }
+        };
+        self.check_args(&signature.params, params);
+        signature.return_type
+    }
+
+    /// checks a method call, filling in the type of the receiver so backends know which method is called
+    fn check_method_call(&mut self, receiver: &mut Statement, method: &Statement, params: &mut Vec<Statement>,
+                         receiver_type: &mut Option<Box<Statement>>) -> Option<Type> {
+        let (name, span) = ident_of(method)?;
+        self.span = span.clone();
+        let found = self.type_of(receiver);
+        let unordered = |checker: &mut TypeChecker, params: &mut Vec<Statement>| {
+            for p in params.iter_mut() {
+                match p {
+                    Statement::Assignment { value, .. } => checker.type_of(value),
+                    p => checker.type_of(p)
+                };
+            }
+        };
+        let base = match found {
+            Some(t) => t,
+            None => {
+                unordered(self, params);
+                return None;
+            }
+        };
+        let signature = struct_name(&base)
+            .and_then(|type_name| self.methods.get(type_name))
+            .and_then(|methods| methods.get(name)).cloned();
+        let signature = match signature {
+            Some(s) => s,
+            None => {
+                self.error("Unknown method", format!("`{}` has no method named `{}`", base, name), span.clone());
+                return None;
+            }
+        };
+        // the method takes a reference to the value, so the value has to live somewhere
+        let by_value = matches!(base, Type::Struct(_));
+        if by_value && !matches!(receiver, Statement::Identifier { .. } | Statement::PropertyAccess { .. } |
+                                           Statement::ArrayAccess { .. } | Statement::StructLiteral { .. }) {
+            let msg = format!("`{}` can only be called on a variable, field or struct literal", name);
+            self.error("Invalid method receiver", msg, self.span_of(receiver));
+        }
+        // the arguments are put in order here rather than in the checker, since which method is called
+        // depends on the type of the receiver
+        let declared = &signature.declared[signature.declared.len().min(1)..];
+        let mut errors = Vec::new();
+        let scopes = &self.scopes;
+        let is_local = |n: &str| scopes[1..].iter().any(|s| s.contains_key(n));
+        let ordered = order_args(name, declared, params, span, &is_local, &mut errors);
+        self.errors.extend(errors);
+        match ordered {
+            Some(args) => *params = args,
+            None => {
+                unordered(self, params);
+                *receiver_type = base.to_statement(span).map(Box::new);
+                return signature.return_type;
+            }
+        }
+        self.check_args(&signature.params[signature.params.len().min(1)..], params);
+        *receiver_type = base.to_statement(span).map(Box::new);
+        signature.return_type
+    }
+
+    /// checks that a pattern can match a value of the given type, returning the values it covers
+    /// if it is an integer literal or range
+    fn check_pattern(&mut self, pattern: &Statement, value: &Option<Type>) -> Option<(i128, i128)> {
+        let span = self.span_of(pattern);
+        match pattern {
+            Statement::Wildcard { .. } => None,
+            Statement::Path { base, .. } => {
+                let found = ident_of(base).map(|(name, _)| Type::Enum(name.clone()));
+                self.expect(value, &found, span);
+                None
+            }
+            Statement::RangePattern { start, end, inclusive, .. } => {
+                let (start_value, end_value) = (literal_value(start)?, literal_value(end)?);
+                let mut start = start.clone();
+                let found = self.type_of(&mut start);
+                if !matches!(found, Some(Type::Char)) && !found.as_ref().is_some_and(|t| t.is_integer()) {
+                    self.error("Invalid pattern", "ranges can only match integers and chars", span.clone());
+                    return None;
+                }
+                self.expect(value, &found, span.clone());
+                let end_value = if *inclusive { end_value } else { end_value - 1 };
+                if end_value < start_value {
+                    self.error("Invalid pattern", "this range matches nothing", span);
+                    return None;
+                }
+                Some((start_value, end_value))
+            }
+            _ => {
+                let found = self.type_of(&mut pattern.clone());
+                self.expect(value, &found, span);
+                if let Some(t) = value {
+                    self.check_literal(&mut pattern.clone(), t);
+                }
+                literal_value(pattern).map(|v| (v, v))
+            }
+        }
+    }
+
+    /// checks that every possible value is matched by one of the patterns
+    fn check_exhaustive(&mut self, value: &Type, patterns: &[&Statement], ranges: Vec<(i128, i128)>, span: Span) {
+        if patterns.iter().any(|p| matches!(p, Statement::Wildcard { .. })) {
+            return;
+        }
+        let missing: Vec<String> = match value {
+            Type::Enum(name) => {
+                let covered: Vec<&String> = patterns.iter().filter_map(|p| match p {
+                    Statement::Path { member, .. } => ident_of(member).map(|(n, _)| n),
+                    _ => None
+                }).collect();
+                self.enums.get(name).cloned().unwrap_or_default().into_iter()
+                    .filter(|v| !covered.contains(&v))
+                    .map(|v| format!("`{}::{}`", name, v)).collect()
+            }
+            Type::Bool => ["true", "false"].iter()
+                .filter(|b| !patterns.iter().any(|p| matches!(p, Statement::BoolLiteral { value, .. } if value.to_string() == **b)))
+                .map(|b| format!("`{}`", b)).collect(),
+            t => match t.value_range() {
+                Some((min, max)) => {
+                    let mut ranges = ranges;
+                    ranges.sort();
+                    let mut next = min;
+                    for (start, end) in ranges {
+                        if start > next {
+                            break;
+                        }
+                        next = next.max(end + 1);
+                    }
+                    if next > max { Vec::new() } else { vec![format!("`{}`", next)] }
+                }
+                None => vec!["`_`".to_string()]
+            }
+        };
+        if !missing.is_empty() {
+            self.error("Non-exhaustive match", format!("{} is not matched", missing.join(", ")), span);
+        }
+    }
+
+    /// checks a match and returns the type of its arms. When it is used as a value every arm must
+    /// have the same type, otherwise the arms are checked as statements
+    fn match_type(&mut self, stmt: &mut Statement, is_value: bool) -> Option<Type> {
+        let (value, arms, value_type, span) = match stmt {
+            Statement::Match { value, arms, value_type, span, .. } => (value, arms, value_type, span.clone()),
+            _ => return None
+        };
+        self.span = span.clone();
+        let found = self.type_of(value).map(|t| t.with_default_types());
+        let mut ranges = Vec::new();
+        let mut result: Option<Type> = None;
+        for arm in arms.iter_mut() {
+            if let Statement::MatchArm { pattern, body, .. } = arm {
+                if let Some(range) = self.check_pattern(pattern, &found) {
+                    ranges.push(range);
+                }
+                if !is_value || matches!(body.as_ref(), Statement::Block { .. }) {
+                    self.check(body);
+                    continue;
+                }
+                let arm_type = self.type_of(body);
+                result = match (result, arm_type) {
+                    (Some(a), Some(b)) => match a.unify(&b) {
+                        Some(t) => Some(t),
+                        None => {
+                            let msg = format!("expected `{}` but found `{}`", a, b);
+                            let arm_pos = self.span_of(body);
+                            self.error("Match arms have different types", msg, arm_pos);
+                            Some(a)
+                        }
+                    },
+                    (a, b) => a.or(b)
+                };
+            }
+        }
+        if let Some(t) = &found {
+            let patterns: Vec<&Statement> = arms.iter().filter_map(|a| match a {
+                Statement::MatchArm { pattern, .. } => Some(pattern.as_ref()),
+                _ => None
+            }).collect();
+            self.check_exhaustive(t, &patterns, ranges, span.clone());
+            *value_type = t.to_statement(&span).map(Box::new);
+        }
+        if is_value { result } else { Some(Type::Void) }
+    }
+
+    fn unary_type(&mut self, op: Operator, t: Type, span: Span) -> Option<Type> {
+        let valid = match op {
+            Operator::Not => t == Type::Bool || t.is_integer(),
+            Operator::Sub => t.is_signed() || t.is_float(),
+            _ => false
+        };
+        if !valid {
+            self.error("Invalid operand", format!("{} can not be applied to `{}`", op, t), span);
+            return None;
+        }
+        Some(t)
+    }
+
+    fn binary_type(&mut self, op: Operator, left: Type, right: Type, span: Span) -> Option<Type> {
+        // adding to a pointer moves it by whole elements, like it does in C
+        match (op, &left, &right) {
+            (Operator::Add | Operator::Sub, Type::Pointer(_), t) if t.is_integer() => return Some(left),
+            (Operator::Add, t, Type::Pointer(_)) if t.is_integer() => return Some(right),
+            _ => {}
+        }
+        let result = match op {
+            Operator::Add | Operator::Sub | Operator::Mul | Operator::Div => {
+                left.unify(&right).filter(|t| t.is_number())
+            }
+            Operator::Mod => left.unify(&right).filter(|t| t.is_integer()),
+            Operator::And | Operator::Or | Operator::Xor => {
+                left.unify(&right).filter(|t| t.is_integer() || *t == Type::Bool)
+            }
+            Operator::Shl | Operator::Shr | Operator::Shlu | Operator::Shru => {
+                if left.is_integer() && right.is_integer() { Some(left.clone()) } else { None }
+            }
+            // C can't compare structs with ==
+            Operator::Eq | Operator::Neq => {
+                left.unify(&right).filter(|t| !matches!(t, Type::Struct(_))).map(|_| Type::Bool)
+            }
+            Operator::Lt | Operator::Lte | Operator::Gt | Operator::Gte => {
+                left.unify(&right).filter(|t| t.is_number() || matches!(t, Type::Char | Type::Pointer(_)))
+                    .map(|_| Type::Bool)
+            }
+            Operator::BoolAnd | Operator::BoolOr => {
+                if left == Type::Bool && right == Type::Bool { Some(Type::Bool) } else { None }
+            }
+            Operator::Range | Operator::IRange => {
+                left.unify(&right).filter(|t| t.is_integer()).map(|t| Type::Range(Box::new(t)))
+            }
+            _ => None
+        };
+        if result.is_none() {
+            self.error("Invalid operands", format!("{} can not be applied to `{}` and `{}`", op, left, right), span);
+        }
+        result
+    }
+
+    /// the type of `left op right`. A literal used with a typed value becomes that type, so it has to fit in it
+    /// `value_type` is given the type the operation is done in, which is the left side's type for shifts
+    fn binary_expr_type(&mut self, op: Operator, left: &mut Statement, right: &mut Statement,
+                        value_type: &mut Option<Box<Statement>>) -> Option<Type> {
+        let l = self.type_of(left);
+        let r = self.type_of(right);
+        let (l, r) = (l?, r?);
+        let shift = matches!(op, Operator::Shl | Operator::Shr | Operator::Shlu | Operator::Shru);
+        if let Some(t) = l.unify(&r).filter(|_| !shift) {
+            self.check_literal(left, &t);
+            self.check_literal(right, &t);
+        }
+        let span = self.span_of(left);
+        let operands = if shift { Some(l.clone()) } else { l.unify(&r) };
+        // untyped literals are given a type by what they are used as
+        *value_type = operands.and_then(|t| t.to_statement(&span)).map(Box::new);
+        self.binary_type(op, l, r, span)
+    }
+
+    /// the type of a prefix operator or `++`/`--` applied to a value. `value_type` is given the type of
+    /// the value, which untyped literals are given by what they are used as
+    fn unary_expr_type(&mut self, op: Operator, expr: &mut Statement, value_type: &mut Option<Box<Statement>>)
+        -> Option<Type> {
+        let t = self.type_of(expr)?;
+        let span = self.span_of(expr);
+        *value_type = t.to_statement(&span).map(Box::new);
+        match op {
+            Operator::Inc | Operator::Dec => {
+                if !t.is_integer() {
+                    self.error("Invalid operand", format!("{} can not be applied to `{}`", op, t), span);
+                }
+                Some(t)
+            }
+            Operator::And => {
+                if !matches!(expr, Statement::Identifier { .. } | Statement::PropertyAccess { .. } |
+                                   Statement::ArrayAccess { .. } | Statement::Unary { op: Operator::Mul, .. }) {
+                    let msg = "only variables, fields, array elements and dereferenced pointers have an address";
+                    self.error("Invalid operand", msg, span.clone());
+                }
+                let t = t.with_default_types();
+                *value_type = t.to_statement(&span).map(Box::new);
+                Some(Type::Reference(Box::new(t)))
+            }
+            Operator::Mul => match t {
+                Type::Pointer(inner) | Type::Reference(inner) => Some(*inner),
+                t => {
+                    self.error("Invalid operand", format!("`{}` is not a pointer, so it can't be dereferenced", t), span);
+                    None
+                }
+            },
+            _ => self.unary_type(op, t, span)
+        }
+    }
+
+    /// the type of `expr as type`. Numbers, chars and bools can be cast to numbers and chars, integers and
+    /// pointers can be cast to pointers, and pointers to integers. References can be cast like pointers
+    fn cast_type(&mut self, expr: &mut Statement, cast_type: &Statement) -> Option<Type> {
+        let found = self.type_of(expr);
+        let target = self.resolve(cast_type)?;
+        let found = found?;
+        let castable = |t: &Type| t.is_number() || *t == Type::Char;
+        let pointer = |t: &Type| matches!(t, Type::Pointer(_) | Type::Reference(_));
+        let valid = match (&found, &target) {
+            (from, Type::Pointer(_)) => from.is_integer() || pointer(from),
+            (from, to) if pointer(from) => to.is_integer(),
+            (from, to) => (castable(from) || *from == Type::Bool) && castable(to),
+        };
+        if !valid {
+            let span = self.span_of(expr);
+            self.error("Invalid cast", format!("`{}` can not be cast to `{}`", found, target), span);
+            return None;
+        }
+        Some(target)
+    }
+
+    fn type_of(&mut self, expr: &mut Statement) -> Option<Type> {
+        match expr {
+            Statement::NumberLiteral { value, .. } => match value.suffix.clone() {
+                Some(t) => {
+                    let (number, span) = (value.clone(), self.span.clone());
+                    self.check_number(&number, &t, span);
+                    Some(t)
+                }
+                None => Some(Type::UntypedInt)
+            },
+            Statement::FloatLiteral { value, .. } => {
+                if !self.fpu {
+                    let span = self.span.clone();
+                    self.no_fpu(value, span);
+                    return None;
+                }
+                Some(Type::UntypedFloat)
+            }
+            Statement::BoolLiteral { .. } => Some(Type::Bool),
+            Statement::CharLiteral { .. } => Some(Type::Char),
+            Statement::StringLiteral { .. } => Some(Type::Str),
+            Statement::Void => Some(Type::Void),
+            Statement::Identifier { ident, .. } => self.lookup(ident),
+            Statement::FnCall { ident, params, .. } => self.check_call(ident, params),
+            Statement::MethodCall { receiver, method, params, receiver_type, .. } => {
+                self.check_method_call(receiver, method, params, receiver_type)
+            }
+            Statement::Binary { op, left, right, value_type, .. } => self.binary_expr_type(*op, left, right, value_type),
+            Statement::Unary { op, expr, value_type, .. } => self.unary_expr_type(*op, expr, value_type),
+            Statement::Cast { expr, cast_type, .. } => self.cast_type(expr, cast_type),
+            Statement::ArrayAccess { ident, index, length, through_pointer, .. } => {
+                let base = self.type_of(ident);
+                *through_pointer = matches!(base, Some(Type::Reference(_)));
+                *length = match &base {
+                    Some(Type::Array(_, size)) => Some(*size),
+                    Some(Type::Reference(inner)) => match inner.as_ref() {
+                        Type::Array(_, size) => Some(*size),
+                        _ => None
+                    },
+                    _ => None
+                };
+                let index_type = self.type_of(index);
+                if let Some(t) = &index_type {
+                    if !t.is_integer() {
+                        let span = self.span_of(index);
+                        self.error("Mismatched types", format!("array indexes must be integers but found `{}`", t), span);
+                    }
+                }
+                match base? {
+                    Type::Array(t, _) | Type::Pointer(t) => Some(*t),
+                    Type::Reference(inner) => match *inner {
+                        Type::Array(t, _) => Some(*t),
+                        other => {
+                            let span = self.span_of(ident);
+                            self.error("Not indexable", format!("`&{}` can not be indexed", other), span);
+                            None
+                        }
+                    },
+                    other => {
+                        let span = self.span_of(ident);
+                        self.error("Not indexable", format!("`{}` can not be indexed", other), span);
+                        None
+                    }
+                }
+            }
+            Statement::PropertyAccess { expr, property, through_pointer, .. } => {
+                let base = self.type_of(expr)?;
+                *through_pointer = matches!(base, Type::Reference(_) | Type::Pointer(_));
+                self.field_type(&base, property)
+            }
+            Statement::StructLiteral { ident, fields, .. } => self.struct_literal_type(ident, fields),
+            Statement::ArrayLiteral { items, span } => {
+                if items.is_empty() {
+                    self.error("Empty array", "arrays need at least one element", span.clone());
+                    return None;
+                }
+                let length = items.len();
+                // every element becomes the type they all share, so `[1, x]` is an array of x's type
+                let mut element: Option<Type> = None;
+                for item in items {
+                    let found = self.type_of(item)?;
+                    element = match element {
+                        None => Some(found),
+                        Some(t) => match t.unify(&found) {
+                            Some(t) => Some(t),
+                            None => {
+                                let span = self.span_of(item);
+                                self.mismatch(&t, &found, span);
+                                return None;
+                            }
+                        }
+                    };
+                }
+                Some(Type::Array(Box::new(element?), length))
+            }
+            Statement::ArrayRepeat { value, size, .. } => {
+                let element = self.type_of(value)?;
+                // the checker has already replaced the size with its value
+                let size = literal_value(size).and_then(|s| usize::try_from(s).ok())?;
+                Some(Type::Array(Box::new(element), size))
+            }
+            Statement::Path { base, .. } => {
+                // unknown enums and variants are reported by the checker
+                let (name, _) = ident_of(base)?;
+                self.enums.get(name).map(|_| Type::Enum(name.clone()))
+            }
+            Statement::Match { .. } => self.match_type(expr, true),
+            Statement::Loop { body, label, .. } => self.check_loop_body(label, body),
+            // statements are checked by `check`, they have no value
+            Statement::Block { .. } | Statement::Declaration { .. } | Statement::Assignment { .. } |
+            Statement::CompoundAssignment { .. } | Statement::If { .. } | Statement::While { .. } |
+            Statement::For { .. } | Statement::Return { .. } | Statement::Assert { .. } | Statement::Panic { .. } |
+            Statement::Interrupt { .. } => Some(Type::Void),
+            _ => None
+        }
+    }
+}
+
+/// Checks the types of every expression in the program and infers the type of every declaration
+/// without one, returning every error found. Floats are an error when the target has no `fpu`
+pub fn type_check(ast: &mut Statement, fpu: bool) -> Result<(), Vec<Error>> {
+    let mut checker = TypeChecker::new(fpu);
+    if let Statement::Program { exprs, .. } = ast {
+        checker.collect_enums(exprs);
+        checker.collect_structs(exprs);
+        checker.collect_functions(exprs);
+        checker.check_globals(exprs);
+    }
+    if checker.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(checker.errors)
+    }
+}
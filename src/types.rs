@@ -0,0 +1,326 @@
+use std::fmt::Display;
+use crate::error::Error;
+use crate::Span;
+use crate::statement::{Number, Statement};
+
+/// The types of values in boulder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    U8, U16, U32, U64,
+    I8, I16, I32, I64,
+    Int,
+    /// an integer literal that has not been given a type yet, which can become any integer type
+    UntypedInt,
+    F32, F64,
+    /// a float literal that has not been given a type yet, which can become either float type
+    UntypedFloat,
+    Bool,
+    Char,
+    Str,
+    Void,
+    Pointer(Box<Type>),
+    Reference(Box<Type>),
+    Array(Box<Type>, usize),
+    /// the result of `a..b` and `a..=b`, only used by for loops
+    Range(Box<Type>),
+    /// a struct, by name
+    Struct(String),
+    /// an enum, by name
+    Enum(String),
+}
+
+impl Type {
+    /// returns the primitive type with the given name
+    pub fn from_name(name: &str) -> Option<Type> {
+        Some(match name {
+            "u8" => Type::U8,
+            "u16" => Type::U16,
+            "u32" => Type::U32,
+            "u64" => Type::U64,
+            "i8" => Type::I8,
+            "i16" => Type::I16,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            "int" => Type::Int,
+            "f32" => Type::F32,
+            "f64" => Type::F64,
+            "bool" => Type::Bool,
+            "char" => Type::Char,
+            "string" => Type::Str,
+            "void" => Type::Void,
+            _ => return None
+        })
+    }
+
+    /// builds a type from a `Statement::Type`, `Statement::ArrayType` or `Statement::Void`.
+    /// `user_type` resolves names that aren't primitive types, such as structs
+    pub fn from_statement(stmt: &Statement, user_type: &dyn Fn(&str) -> Option<Type>) -> Result<Type, Error> {
+        let (base, modifiers) = match stmt {
+            Statement::Void => return Ok(Type::Void),
+            Statement::Type { type_ident, modifiers, .. } => {
+                let base = match type_ident.as_ref() {
+                    Statement::Identifier { ident, span, .. } => Type::from_name(ident).or_else(|| user_type(ident)).ok_or_else(|| {
+                        Error::new("Unknown type", format!("`{}` is not a type", ident), span.clone())
+                    })?,
+                    // the checker replaces `alias::Type` with the type's name
+                    _ => return Err(Error::new_singular("Unknown type", type_span(stmt)))
+                };
+                (base, modifiers)
+            }
+            Statement::ArrayType { array_type, size, modifiers, .. } => {
+                let inner = Type::from_statement(array_type, user_type)?;
+                let len = match size.as_ref() {
+                    Statement::NumberLiteral { value, .. } if !value.negative => usize::try_from(value.value).ok(),
+                    _ => None
+                };
+                let len = len.ok_or_else(|| Error::new_singular(
+                    "Array sizes must be a positive integer literal", type_span(array_type)))?;
+                (Type::Array(Box::new(inner), len), modifiers)
+            }
+            _ => unreachable!()
+        };
+        // the modifier closest to the type applies first, so `&*u8` is a reference to a pointer to a u8
+        let mut t = base;
+        for m in modifiers.iter().rev() {
+            t = match m {
+                Statement::Reference => Type::Reference(Box::new(t)),
+                Statement::Pointer => Type::Pointer(Box::new(t)),
+                _ => t
+            };
+        }
+        Ok(t)
+    }
+
+    /// builds the type statement the parser would produce for this type, used to fill in
+    /// inferred declarations. Returns None for types that can't be written in boulder
+    pub fn to_statement(&self, span: &Span) -> Option<Statement> {
+        let mut modifiers = Vec::new();
+        let mut t = self;
+        loop {
+            match t {
+                Type::Pointer(inner) => {
+                    modifiers.push(Statement::Pointer);
+                    t = inner;
+                }
+                Type::Reference(inner) => {
+                    modifiers.push(Statement::Reference);
+                    t = inner;
+                }
+                _ => break
+            }
+        }
+        match t {
+            Type::Void if modifiers.is_empty() => Some(Statement::Void),
+            Type::UntypedInt | Type::UntypedFloat | Type::Range(_) | Type::Pointer(_) | Type::Reference(_) => None,
+            Type::Array(inner, size) => Some(Statement::ArrayType {
+                array_type: Box::new(inner.to_statement(span)?),
+                size: Box::new(Statement::NumberLiteral { value: Number::new(*size as u64, false), span: span.clone() }),
+                modifiers,
+                span: span.clone(),
+            }),
+            _ => Some(Statement::Type {
+                modifiers,
+                type_ident: Box::new(Statement::Identifier { ident: t.to_string(), span: span.clone() }),
+                span: span.clone(),
+            }),
+        }
+    }
+
+    /// the smallest and largest values of an integer type, or of the bytes a char holds in C
+    pub fn value_range(&self) -> Option<(i128, i128)> {
+        Some(match self {
+            Type::U8 | Type::Char => (0, u8::MAX as i128),
+            Type::U16 => (0, u16::MAX as i128),
+            Type::U32 => (0, u32::MAX as i128),
+            Type::U64 => (0, u64::MAX as i128),
+            Type::I8 => (i8::MIN as i128, i8::MAX as i128),
+            Type::I16 => (i16::MIN as i128, i16::MAX as i128),
+            // int is a C int, which is 32 bits on every target boulder supports
+            Type::I32 | Type::Int => (i32::MIN as i128, i32::MAX as i128),
+            Type::I64 | Type::UntypedInt => (i64::MIN as i128, i64::MAX as i128),
+            _ => return None
+        })
+    }
+
+    /// the unsigned integer type with the same width as an integer type
+    pub fn unsigned(&self) -> Type {
+        match self {
+            Type::I8 | Type::U8 => Type::U8,
+            Type::I16 | Type::U16 => Type::U16,
+            Type::I32 | Type::U32 | Type::Int => Type::U32,
+            _ => Type::U64
+        }
+    }
+
+    /// replaces untyped literals with the type they default to when nothing else is known,
+    /// `int` for integers and `f64` for floats
+    pub fn with_default_types(self) -> Type {
+        match self {
+            Type::UntypedInt => Type::Int,
+            Type::UntypedFloat => Type::F64,
+            Type::Pointer(t) => Type::Pointer(Box::new(t.with_default_types())),
+            Type::Reference(t) => Type::Reference(Box::new(t.with_default_types())),
+            Type::Array(t, size) => Type::Array(Box::new(t.with_default_types()), size),
+            Type::Range(t) => Type::Range(Box::new(t.with_default_types())),
+            t => t
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::I8 | Type::I16 |
+                       Type::I32 | Type::I64 | Type::Int | Type::UntypedInt)
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::F32 | Type::F64 | Type::UntypedFloat)
+    }
+
+    /// integers and floats, which support arithmetic
+    pub fn is_number(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+
+    /// returns true if the type is, or holds, a float
+    pub fn has_float(&self) -> bool {
+        match self {
+            Type::Pointer(t) | Type::Reference(t) | Type::Array(t, _) | Type::Range(t) => t.has_float(),
+            t => t.is_float()
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self, Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::Int | Type::UntypedInt)
+    }
+
+    /// returns true if a value of this type can be stored where `target` is expected.
+    /// A reference can be used as a pointer to the same type
+    pub fn assignable_to(&self, target: &Type) -> bool {
+        match (self, target) {
+            (Type::Array(inner, size), Type::Array(target_inner, target_size)) => {
+                return size == target_size && inner.assignable_to(target_inner);
+            }
+            (Type::Reference(inner), Type::Pointer(target_inner)) => return inner == target_inner,
+            _ => {}
+        }
+        self == target || (*self == Type::UntypedInt && target.is_integer()) ||
+            (*self == Type::UntypedFloat && target.is_float())
+    }
+
+    /// the type both sides of a binary operation become, if they are compatible
+    pub fn unify(&self, other: &Type) -> Option<Type> {
+        if self == other {
+            Some(self.clone())
+        } else if self.assignable_to(other) {
+            Some(other.clone())
+        } else if other.assignable_to(self) {
+            Some(self.clone())
+        } else {
+            None
+        }
+    }
+
+    /// the size and alignment of the type in bytes, the way C lays it out on a 64 bit target. `fields`
+    /// gives the types of a struct's fields. `void` is a byte, so a `*void` steps a byte at a time
+    pub fn layout(&self, fields: &dyn Fn(&str) -> Vec<Type>) -> (u64, u64) {
+        match self {
+            Type::U8 | Type::I8 | Type::Bool | Type::Char | Type::Void => (1, 1),
+            Type::U16 | Type::I16 => (2, 2),
+            Type::U32 | Type::I32 | Type::Int | Type::UntypedInt | Type::F32 | Type::Enum(_) => (4, 4),
+            Type::U64 | Type::I64 | Type::F64 | Type::UntypedFloat | Type::Str | Type::Pointer(_) |
+            Type::Reference(_) => (8, 8),
+            Type::Range(_) => (16, 8),
+            Type::Array(t, size) => {
+                let (element, align) = t.layout(fields);
+                (element * *size as u64, align)
+            }
+            Type::Struct(name) => {
+                let (_, size, align) = Type::struct_layout(&fields(name), fields);
+                (size, align)
+            }
+        }
+    }
+
+    /// where each field of a struct starts, along with the struct's size and alignment. Fields are
+    /// padded to their alignment and the struct to the largest one, like C does
+    pub fn struct_layout(field_types: &[Type], fields: &dyn Fn(&str) -> Vec<Type>) -> (Vec<u64>, u64, u64) {
+        let mut offsets = Vec::new();
+        let (mut size, mut align) = (0u64, 1);
+        for t in field_types {
+            let (field_size, field_align) = t.layout(fields);
+            size = size.next_multiple_of(field_align);
+            offsets.push(size);
+            size += field_size;
+            align = align.max(field_align);
+        }
+        (offsets, size.next_multiple_of(align), align)
+    }
+}
+
+/// the value of an integer or char literal, used for enum discriminants and match patterns
+pub fn literal_value(stmt: &Statement) -> Option<i128> {
+    match stmt {
+        Statement::NumberLiteral { value, .. } => Some(value.signed_value()),
+        Statement::CharLiteral { value, .. } => Some(*value as i128),
+        _ => None
+    }
+}
+
+/// the name of the struct a field holds by value, if it holds one, as the identifier or `alias::name` path naming it
+pub fn field_struct_name(field: &Statement) -> Option<&Statement> {
+    let mut type_ident = match field {
+        Statement::Declaration { type_ident: Some(t), .. } => t.as_ref(),
+        _ => return None
+    };
+    // arrays of structs hold them by value too
+    while let Statement::ArrayType { array_type, modifiers, .. } = type_ident {
+        if !modifiers.is_empty() {
+            return None;
+        }
+        type_ident = array_type;
+    }
+    match type_ident {
+        Statement::Type { type_ident, modifiers, .. } if modifiers.is_empty() => Some(type_ident),
+        _ => None
+    }
+}
+
+/// where the type name in a type statement is, used for errors
+pub fn type_span(stmt: &Statement) -> Span {
+    match stmt {
+        Statement::Type { type_ident, .. } => match type_ident.as_ref() {
+            Statement::Identifier { span, .. } | Statement::Path { span, .. } => span.clone(),
+            _ => Span::default()
+        },
+        Statement::ArrayType { array_type, .. } => type_span(array_type),
+        _ => Span::default()
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::U8 => write!(f, "u8"),
+            Type::U16 => write!(f, "u16"),
+            Type::U32 => write!(f, "u32"),
+            Type::U64 => write!(f, "u64"),
+            Type::I8 => write!(f, "i8"),
+            Type::I16 => write!(f, "i16"),
+            Type::I32 => write!(f, "i32"),
+            Type::I64 => write!(f, "i64"),
+            Type::Int => write!(f, "int"),
+            Type::UntypedInt => write!(f, "{{integer}}"),
+            Type::F32 => write!(f, "f32"),
+            Type::F64 => write!(f, "f64"),
+            Type::UntypedFloat => write!(f, "{{float}}"),
+            Type::Bool => write!(f, "bool"),
+            Type::Char => write!(f, "char"),
+            Type::Str => write!(f, "string"),
+            Type::Void => write!(f, "void"),
+            Type::Pointer(t) => write!(f, "*{}", t),
+            Type::Reference(t) => write!(f, "&{}", t),
+            Type::Array(t, size) => write!(f, "[{}; {}]", t, size),
+            Type::Range(t) => write!(f, "range of {}", t),
+            Type::Struct(name) | Type::Enum(name) => write!(f, "{}", name),
+        }
+    }
+}